@@ -0,0 +1,275 @@
+// Player animation: which clip of the sprite sheet plays, picked from the
+// player's velocity and ground contact, and the squash-and-stretch on top.
+// Frames and timing come from an asset file (assets/sprites/player.anim.toml);
+// systems::animation loads it and drives the player's sprite.
+
+use bevy::asset::Asset;
+use bevy::ecs::component::Component;
+use bevy::math::Vec2;
+use bevy::reflect::TypePath;
+use serde::Deserialize;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnimState {
+    #[default]
+    Idle,
+    Run,
+    // Going up after a jump from the ground
+    Rise,
+    Fall,
+    // Touching down
+    Land,
+    // Turning around on the ground
+    Skid,
+    // Air jump
+    Flip,
+}
+
+// How long each frame of a clip shows
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum FrameMs {
+    All(f32),
+    Each(Vec<f32>),
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct Clip {
+    // Sheet frames, numbered across then down
+    pub frames: Vec<usize>,
+    pub frame_ms: FrameMs,
+    // Start over after the last frame, or hold it
+    #[serde(default = "default_repeat")]
+    pub repeat: bool,
+}
+
+fn default_repeat() -> bool { true }
+
+impl Clip {
+    fn ms(&self, i: usize) -> f32 {
+        match &self.frame_ms {
+            FrameMs::All(ms) => *ms,
+            FrameMs::Each(list) => list.get(i).or(list.last()).copied().unwrap_or(0.0),
+        }
+    }
+
+    pub fn duration_ms(&self) -> f32 {
+        (0..self.frames.len()).map(|i| self.ms(i)).sum()
+    }
+
+    pub fn finished(&self, elapsed_ms: f32) -> bool {
+        !self.repeat && elapsed_ms >= self.duration_ms()
+    }
+
+    // Sheet frame `elapsed_ms` into the clip
+    pub fn frame_at(&self, elapsed_ms: f32) -> usize {
+        let total = self.duration_ms();
+        let mut t = if self.repeat && total > 0.0 { elapsed_ms % total } else { elapsed_ms };
+        for (i, frame) in self.frames.iter().enumerate() {
+            t -= self.ms(i);
+            if t < 0.0 {
+                return *frame;
+            }
+        }
+        self.frames.last().copied().unwrap_or(0)
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Stretch {
+    // Scale as (width, height) right after a jump and on landing
+    pub jump: [f32; 2],
+    pub land: [f32; 2],
+    // Fall speed that gets the full land squash
+    pub land_speed: f32,
+    // Time to ease back to normal size
+    pub recover_ms: f32,
+    // Taller and thinner with vertical speed in the air: extra height per unit
+    // of speed, and the most extra height
+    pub air_per_speed: f32,
+    pub air_max: f32,
+}
+
+impl Default for Stretch {
+    fn default() -> Self {
+        Self { jump: [1.0, 1.0], land: [1.0, 1.0], land_speed: 900.0, recover_ms: 180.0, air_per_speed: 0.0, air_max: 0.0 }
+    }
+}
+
+// Contents of an animation file
+#[derive(Asset, TypePath, Deserialize, Clone, Debug, PartialEq)]
+pub struct PlayerAnimations {
+    // Horizontal speed below which the player stands
+    #[serde(default = "default_run_speed")]
+    pub run_speed: f32,
+    pub idle: Clip,
+    pub run: Clip,
+    pub rise: Clip,
+    pub fall: Clip,
+    pub land: Clip,
+    pub skid: Clip,
+    pub flip: Clip,
+    #[serde(default)]
+    pub stretch: Stretch,
+}
+
+fn default_run_speed() -> f32 { 20.0 }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationFileError {
+    pub message: String,
+}
+
+impl std::fmt::Display for AnimationFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "animation file: {}", self.message)
+    }
+}
+
+impl std::error::Error for AnimationFileError {}
+
+impl PlayerAnimations {
+    pub fn from_toml_str(s: &str) -> Result<Self, AnimationFileError> {
+        let anims: Self = toml::from_str(s).map_err(|e| AnimationFileError { message: e.to_string() })?;
+        for (name, clip) in anims.clips() {
+            let error = |message: &str| Err(AnimationFileError { message: format!("{}: {}", name, message) });
+            if clip.frames.is_empty() {
+                return error("needs at least one frame");
+            }
+            if let FrameMs::Each(list) = &clip.frame_ms {
+                if list.len() != clip.frames.len() {
+                    return error("frame_ms needs one time per frame, or a single time for all");
+                }
+            }
+            if (0..clip.frames.len()).any(|i| clip.ms(i) <= 0.0) {
+                return error("frame times must be above 0");
+            }
+        }
+        Ok(anims)
+    }
+
+    pub fn clip(&self, state: AnimState) -> &Clip {
+        match state {
+            AnimState::Idle => &self.idle,
+            AnimState::Run => &self.run,
+            AnimState::Rise => &self.rise,
+            AnimState::Fall => &self.fall,
+            AnimState::Land => &self.land,
+            AnimState::Skid => &self.skid,
+            AnimState::Flip => &self.flip,
+        }
+    }
+
+    pub fn clips(&self) -> [(&'static str, &Clip); 7] {
+        [
+            ("idle", &self.idle),
+            ("run", &self.run),
+            ("rise", &self.rise),
+            ("fall", &self.fall),
+            ("land", &self.land),
+            ("skid", &self.skid),
+            ("flip", &self.flip),
+        ]
+    }
+}
+
+// What the player is doing, sampled once per frame
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Motion {
+    pub vel: Vec2,
+    pub grounded: bool,
+    // Turned around on the ground since the last sample (DirtKickEvent)
+    pub skid: bool,
+    // Jumped in the air since the last sample (StepEvents::air_jump); the
+    // first jump after walking off a ledge isn't one
+    pub air_jump: bool,
+}
+
+// Current clip and squash-and-stretch of the player
+#[derive(Component, Clone, Debug, Default)]
+pub struct Animator {
+    pub state: AnimState,
+    // Time since the state was entered
+    pub elapsed_ms: f32,
+    last: Motion,
+    // Scale right after the last jump or landing, and the time since
+    kick: Vec2,
+    kick_ms: f32,
+}
+
+impl Animator {
+    pub fn update(&mut self, anims: &PlayerAnimations, motion: Motion, dt_ms: f32) {
+        self.elapsed_ms += dt_ms;
+        self.kick_ms += dt_ms;
+        let was_grounded = self.last.grounded;
+        let busy = !anims.clip(self.state).finished(self.elapsed_ms);
+        let mut restart = false;
+        let state = if motion.grounded {
+            if motion.skid {
+                restart = true;
+                AnimState::Skid
+            } else if !was_grounded {
+                let impact = (-self.last.vel.y / anims.stretch.land_speed.max(1.0)).clamp(0.0, 1.0);
+                self.kick(Vec2::ONE.lerp(Vec2::from(anims.stretch.land), impact));
+                AnimState::Land
+            } else if matches!(self.state, AnimState::Land | AnimState::Skid) && busy {
+                self.state
+            } else if motion.vel.x.abs() > anims.run_speed {
+                AnimState::Run
+            } else {
+                AnimState::Idle
+            }
+        } else if was_grounded && motion.vel.y > 0.0 {
+            self.kick(Vec2::from(anims.stretch.jump));
+            AnimState::Rise
+        } else if motion.air_jump {
+            self.kick(Vec2::from(anims.stretch.jump));
+            restart = true;
+            AnimState::Flip
+        } else if self.state == AnimState::Flip && busy {
+            AnimState::Flip
+        } else if motion.vel.y > 0.0 {
+            AnimState::Rise
+        } else {
+            AnimState::Fall
+        };
+        if state != self.state || restart {
+            self.state = state;
+            self.elapsed_ms = 0.0;
+        }
+        self.last = motion;
+    }
+
+    fn kick(&mut self, scale: Vec2) {
+        self.kick = scale;
+        self.kick_ms = 0.0;
+    }
+
+    // Sheet frame to show
+    pub fn frame(&self, anims: &PlayerAnimations) -> usize {
+        anims.clip(self.state).frame_at(self.elapsed_ms)
+    }
+
+    // Scale to draw the player at
+    pub fn scale(&self, anims: &PlayerAnimations) -> Vec2 {
+        let s = &anims.stretch;
+        let mut scale = Vec2::ONE;
+        if self.kick != Vec2::ZERO {
+            // Ease out: most of the way back early on
+            let left = (1.0 - self.kick_ms / s.recover_ms.max(1.0)).max(0.0);
+            scale = Vec2::ONE + (self.kick - Vec2::ONE) * left * left;
+        }
+        if !self.last.grounded {
+            let air = (self.last.vel.y.abs() * s.air_per_speed).min(s.air_max);
+            scale *= Vec2::new(1.0 / (1.0 + air), 1.0 + air);
+        }
+        scale
+    }
+}
+
+// Sprite anchor height that keeps the bottom edge in place when the sprite is
+// scaled to `scale_y` about its anchor, so squashing doesn't lift the feet
+pub fn feet_anchor(scale_y: f32) -> f32 {
+    0.5 / scale_y.max(0.01) - 0.5
+}
@@ -0,0 +1,145 @@
+// Command line and environment options of the game binary
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use game::config::ConfigOverride;
+
+pub const USAGE: &str = "\
+Usage: game [options]
+
+Options:
+  --config <file>        Config file (default: config.toml here, else next to the executable)
+  --level <name|file>    Level to start on (default: level1)
+  --seed <n>             Seed for the game's randomness (default: random)
+  --window-size <WxH>    Window size in pixels (same as --set display.window_width=W etc.)
+  --fullscreen           Start in borderless fullscreen (same as --set display.fullscreen=true)
+  --set <key=value>      Override a config value, e.g. --set jump.velocity=700 (repeatable)
+  --replay <file>        Play back a recorded replay
+  --record <file>        Write the run as a replay when it ends
+  --ghost <file>         Race an exported ghost
+  --headless             Run without a window (needs --replay)
+  -h, --help             Show this help
+
+Environment (command line options win):
+  GAME_CONFIG=<file>, GAME_LEVEL=<name|file>, GAME_SEED=<n>
+  GAME_SET__<SECTION>__<KEY>=<value>   e.g. GAME_SET__JUMP__VELOCITY=700
+";
+
+const ENV_SET_PREFIX: &str = "GAME_SET__";
+
+// Arguments as text; one that isn't valid Unicode is an error
+pub fn args_from_os(args: impl IntoIterator<Item = OsString>) -> Result<Vec<String>, String> {
+    args.into_iter()
+        .map(|a| a.into_string().map_err(|a| format!("argument is not valid Unicode: {}", a.to_string_lossy())))
+        .collect()
+}
+
+// The GAME_* environment variables as text. Other variables are never looked
+// at; GAME_* ones that aren't valid Unicode are left out, with a warning each.
+pub fn game_vars(vars: impl IntoIterator<Item = (OsString, OsString)>) -> (Vec<(String, String)>, Vec<String>) {
+    let mut out = Vec::new();
+    let mut warnings = Vec::new();
+    for (name, value) in vars {
+        if !name.to_string_lossy().starts_with("GAME_") {
+            continue;
+        }
+        match (name.into_string(), value.into_string()) {
+            (Ok(name), Ok(value)) => out.push((name, value)),
+            (name, _) => {
+                let name = name.unwrap_or_else(|n| n.to_string_lossy().into_owned());
+                warnings.push(format!("Ignoring {}: not valid Unicode", name));
+            }
+        }
+    }
+    (out, warnings)
+}
+
+#[derive(Debug, Default)]
+pub struct Cli {
+    pub help: bool,
+    pub config: Option<PathBuf>,
+    pub level: Option<String>,
+    pub seed: Option<u64>,
+    // Environment overrides first, then --set ones, so the command line wins
+    pub overrides: Vec<ConfigOverride>,
+    pub replay: Option<String>,
+    pub record: Option<String>,
+    pub ghost: Option<String>,
+    pub headless: bool,
+}
+
+impl Cli {
+    pub fn parse(args: impl IntoIterator<Item = String>, env: impl IntoIterator<Item = (String, String)>) -> Result<Self, String> {
+        let mut cli = Cli::default();
+        let mut env_overrides: Vec<(String, String)> = Vec::new();
+        for (name, value) in env {
+            match name.as_str() {
+                "GAME_CONFIG" => cli.config = Some(value.into()),
+                "GAME_LEVEL" => cli.level = Some(value),
+                "GAME_SEED" => cli.seed = Some(parse_seed(&value).map_err(|e| format!("GAME_SEED: {}", e))?),
+                _ => {
+                    if let Some(path) = name.strip_prefix(ENV_SET_PREFIX) {
+                        env_overrides.push((path.split("__").collect::<Vec<_>>().join(".").to_lowercase(), value));
+                    }
+                }
+            }
+        }
+        // Same order whatever the environment's
+        env_overrides.sort();
+        for (key, value) in env_overrides {
+            let origin = format!("{}{}", ENV_SET_PREFIX, key.replace('.', "__").to_uppercase());
+            cli.overrides.push(ConfigOverride { key, value, origin });
+        }
+
+        let mut args = args.into_iter();
+        while let Some(a) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", a));
+            match a.as_str() {
+                "-h" | "--help" => cli.help = true,
+                "--config" => cli.config = Some(value()?.into()),
+                "--level" => cli.level = Some(value()?),
+                "--seed" => cli.seed = Some(parse_seed(&value()?).map_err(|e| format!("--seed: {}", e))?),
+                "--window-size" => {
+                    let v = value()?;
+                    let (w, h) = parse_size(&v)?;
+                    let origin = format!("--window-size {}", v);
+                    for (key, value) in [("display.window_width", w), ("display.window_height", h)] {
+                        cli.overrides.push(ConfigOverride { key: key.into(), value: value.to_string(), origin: origin.clone() });
+                    }
+                }
+                "--fullscreen" => cli.overrides.push(ConfigOverride {
+                    key: "display.fullscreen".into(),
+                    value: "true".into(),
+                    origin: a.clone(),
+                }),
+                "--set" => {
+                    let v = value()?;
+                    let o = ConfigOverride::parse(&v, &format!("--set {}", v)).map_err(|e| format!("--set: {}", e))?;
+                    cli.overrides.push(o);
+                }
+                "--replay" => cli.replay = Some(value()?),
+                "--record" => cli.record = Some(value()?),
+                "--ghost" => cli.ghost = Some(value()?),
+                "--headless" => cli.headless = true,
+                _ => return Err(format!("unknown option: {}", a)),
+            }
+        }
+        Ok(cli)
+    }
+}
+
+fn parse_seed(s: &str) -> Result<u64, String> {
+    s.parse().map_err(|_| format!("not a seed: {}", s))
+}
+
+// "1280x720"
+fn parse_size(s: &str) -> Result<(u32, u32), String> {
+    let bad = || format!("--window-size: expected WIDTHxHEIGHT, got '{}'", s);
+    let (w, h) = s.split_once(['x', 'X']).ok_or_else(bad)?;
+    let (w, h): (u32, u32) = (w.parse().map_err(|_| bad())?, h.parse().map_err(|_| bad())?);
+    if w == 0 || h == 0 {
+        return Err(bad());
+    }
+    Ok((w, h))
+}
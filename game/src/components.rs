@@ -4,26 +4,63 @@ use bevy::prelude::*;
 #[derive(Component, Default)]
 pub struct Player;
 
+// Translucent replay of a recorded run: a position every `step` fixed ticks
+#[derive(Component, Default)]
+pub struct Ghost {
+    pub frames: Vec<Vec2>,
+    pub step: u32,
+    pub tick: usize,
+    // Toggled with G
+    pub hidden: bool,
+}
+
 #[derive(Component, Deref, DerefMut, Default)]
 pub struct Velocity(pub Vec2);
 
 #[derive(Component)]
 pub struct Ground;
 
+// Sloped ground segment; points are in world space so the entity sits at the origin
+#[derive(Component, Deref)]
+pub struct Slope(pub level_format::Slope);
+
 #[derive(Component)]
 pub struct Exit {
     pub next: String,
     pub size: Vec2,
 }
 
+// Level dressing the player passes through. `base` is where it is drawn with
+// the camera at the origin; its layer's parallax shifts it as the camera moves.
+#[derive(Component)]
+pub struct Decoration {
+    pub base: Vec2,
+    pub parallax: f32,
+}
+
+// Image to put on this entity's sprite, loaded once there is an asset server;
+// removed when the image has loaded or a placeholder has replaced it
+#[derive(Component, Clone, Debug)]
+pub struct Textured(pub level_format::Texture);
+
+// Tile layer drawn as one mesh (attached by build_tile_meshes); its collision
+// is separate Ground rectangles
+#[derive(Component)]
+pub struct TileMap {
+    pub layer: level_format::TileLayer,
+    pub tileset: level_format::Tileset,
+}
+
 #[derive(Component)]
 pub struct LevelEntity; // marker to cleanup when switching levels
 
-#[derive(Component, Default)]
+#[derive(Component, Default, Clone, Debug)]
 pub struct JumpState {
     pub jumping: bool,
     pub hold_ms: f32,
     pub jumps_used: u8,
+    // Grounded at the end of the previous physics step (used to stick to slopes)
+    pub grounded: bool,
 }
 
 // UI markers
@@ -33,5 +70,36 @@ pub struct LivesUi; // container for hearts
 #[derive(Component)]
 pub struct HeartSlot(pub usize);
 
+// Speedrun splits text in the top-right corner
+#[derive(Component)]
+pub struct SplitsUi;
+
+#[derive(Component)]
+pub struct TitleUi;
+
+#[derive(Component)]
+pub struct PauseUi;
+
+#[derive(Component)]
+pub struct LevelCompleteUi;
+
 #[derive(Component)]
 pub struct GameOverUi;
+
+#[derive(Component)]
+pub struct LoadErrorUi;
+
+// Menu button; the action is applied when the button is pressed
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuAction {
+    Start,
+    LoadSave,
+    Pause,
+    Resume,
+    Continue,
+    Restart,
+    Retry,
+    PreviousLevel,
+    QuitToTitle,
+    Quit,
+}
@@ -1,79 +1,667 @@
 use bevy::prelude::*;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 
+// Defaults match the shipped config.toml, i.e. the "classic" profile
 const DEFAULT_PLAYER_MAX_SPEED: f32 = 300.0;
-const DEFAULT_ACCELERATION: f32 = 2000.0;
-const DEFAULT_DECELERATION: f32 = 1800.0;
+const DEFAULT_ACCELERATION: f32 = 1500.0;
+const DEFAULT_DECELERATION: f32 = 800.0;
 const DEFAULT_GRAVITY: f32 = 1400.0;
 const DEFAULT_JUMP_VELOCITY: f32 = 600.0;
 const DEFAULT_JUMP_MAX_HOLD_MS: f32 = 180.0;
 const DEFAULT_JUMP_CUT_FACTOR: f32 = 0.5;
+const DEFAULT_PROFILE: &str = "classic";
 
-#[derive(Deserialize, Clone)]
+pub const CONFIG_PATH: &str = "config.toml";
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Scalar { pub value: f32 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
 pub struct JumpCfg {
     pub velocity: f32,
     pub max_hold_ms: f32,
     pub cut_factor: f32,
-    #[serde(default = "default_max_jumps")]
     pub max_jumps: u8,
 }
 
-#[derive(Deserialize, Clone)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
 pub struct CameraCfg {
     pub lag_s: f32,
     pub lookahead_s: f32,
     pub noise_amp: f32,
     pub noise_freq_hz: f32,
-    #[serde(default)]
     pub noise_amp_x: Option<f32>,
-    #[serde(default)]
     pub noise_amp_y: Option<f32>,
 }
 
-#[derive(Deserialize, Resource, Clone)]
+// How the virtual resolution is fitted into the window
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Scaling {
+    // Largest whole multiple that fits (crisp pixels), black bars around it
+    Integer,
+    // Largest size that fits, black bars on the sides or top and bottom
+    Letterbox,
+}
+
+// The world is always shown at the virtual resolution, whatever the window size,
+// so a bigger window doesn't show more of the level
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct DisplayCfg {
+    pub width: u32,
+    pub height: u32,
+    pub scaling: Scaling,
+    pub window_width: u32,
+    pub window_height: u32,
+    pub resizable: bool,
+    pub fullscreen: bool,
+}
+
+// The player's sprite sheet: equal frames in rows, read left to right
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct PlayerCfg {
+    // Image path relative to the assets folder
+    pub sheet: String,
+    pub frame_width: u32,
+    pub frame_height: u32,
+    pub columns: u32,
+    pub rows: u32,
+    // Frames and timing of each animation, relative to the assets folder
+    pub animations: String,
+}
+
+// Named set of movement overrides on top of the base values; unset fields keep the base
+#[derive(Deserialize, Serialize, Clone, Default, Debug, PartialEq)]
+pub struct ProfileCfg {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_speed: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub acceleration: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deceleration: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gravity: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jump_velocity: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jump_max_hold_ms: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jump_cut_factor: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_jumps: Option<u8>,
+}
+
+#[derive(Deserialize, Serialize, Resource, Clone, Debug)]
+#[serde(default)]
 pub struct GameConfig {
+    // Movement profile used unless the level or the player picks another
+    pub profile: String,
     pub max_speed: Scalar,
     pub acceleration: Scalar,
     pub deceleration: Scalar,
     pub gravity: Scalar,
     pub jump: JumpCfg,
     pub camera: CameraCfg,
+    pub display: DisplayCfg,
+    pub player: PlayerCfg,
+    pub profiles: BTreeMap<String, ProfileCfg>,
+}
+
+impl Default for JumpCfg {
+    fn default() -> Self {
+        Self {
+            velocity: DEFAULT_JUMP_VELOCITY,
+            max_hold_ms: DEFAULT_JUMP_MAX_HOLD_MS,
+            cut_factor: DEFAULT_JUMP_CUT_FACTOR,
+            max_jumps: 2,
+        }
+    }
+}
+
+impl Default for CameraCfg {
+    fn default() -> Self {
+        Self {
+            lag_s: 1.0,
+            lookahead_s: 0.8,
+            noise_amp: 0.1,
+            noise_freq_hz: 0.1,
+            noise_amp_x: None,
+            noise_amp_y: None,
+        }
+    }
+}
+
+impl Default for DisplayCfg {
+    fn default() -> Self {
+        Self {
+            width: 960,
+            height: 540,
+            scaling: Scaling::Letterbox,
+            window_width: 960,
+            window_height: 540,
+            resizable: true,
+            fullscreen: false,
+        }
+    }
+}
+
+impl Default for PlayerCfg {
+    fn default() -> Self {
+        Self {
+            sheet: "sprites/player.png".to_string(),
+            frame_width: 32,
+            frame_height: 32,
+            columns: 8,
+            rows: 2,
+            animations: "sprites/player.anim.toml".to_string(),
+        }
+    }
 }
 
 impl Default for GameConfig {
     fn default() -> Self {
         Self {
+            profile: DEFAULT_PROFILE.to_string(),
             max_speed: Scalar { value: DEFAULT_PLAYER_MAX_SPEED },
             acceleration: Scalar { value: DEFAULT_ACCELERATION },
             deceleration: Scalar { value: DEFAULT_DECELERATION },
             gravity: Scalar { value: DEFAULT_GRAVITY },
+            jump: JumpCfg::default(),
+            camera: CameraCfg::default(),
+            display: DisplayCfg::default(),
+            player: PlayerCfg::default(),
+            profiles: builtin_profiles(),
+        }
+    }
+}
+
+// Shipped profiles; all three reach about the same jump height
+fn builtin_profiles() -> BTreeMap<String, ProfileCfg> {
+    let floaty = ProfileCfg {
+        acceleration: Some(1100.0),
+        deceleration: Some(500.0),
+        gravity: Some(900.0),
+        jump_velocity: Some(480.0),
+        jump_max_hold_ms: Some(260.0),
+        jump_cut_factor: Some(0.65),
+        ..default()
+    };
+    let tight = ProfileCfg {
+        max_speed: Some(340.0),
+        acceleration: Some(3000.0),
+        deceleration: Some(2600.0),
+        gravity: Some(2000.0),
+        jump_velocity: Some(720.0),
+        jump_max_hold_ms: Some(140.0),
+        jump_cut_factor: Some(0.4),
+        ..default()
+    };
+    BTreeMap::from([
+        (DEFAULT_PROFILE.to_string(), ProfileCfg::default()),
+        ("floaty".to_string(), floaty),
+        ("tight".to_string(), tight),
+    ])
+}
+
+// Movement values the physics actually uses: the base values with one profile applied
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Movement {
+    pub profile: String,
+    pub max_speed: f32,
+    pub acceleration: f32,
+    pub deceleration: f32,
+    pub gravity: f32,
+    pub jump: JumpCfg,
+}
+
+impl FromWorld for Movement {
+    fn from_world(world: &mut World) -> Self {
+        let cfg = world.resource::<GameConfig>();
+        cfg.movement(&cfg.profile)
+    }
+}
+
+impl GameConfig {
+    // Stable fingerprint of the movement values and profiles (FNV-1a over the
+    // serialized form), stored in replays to detect playback under different
+    // physics. Nothing else in the config changes the simulation.
+    pub fn hash(&self) -> u64 {
+        #[derive(Serialize)]
+        struct Tuning<'a> {
+            max_speed: &'a Scalar,
+            acceleration: &'a Scalar,
+            deceleration: &'a Scalar,
+            gravity: &'a Scalar,
+            jump: &'a JumpCfg,
+            profiles: &'a BTreeMap<String, ProfileCfg>,
+        }
+        let tuning = Tuning {
+            max_speed: &self.max_speed,
+            acceleration: &self.acceleration,
+            deceleration: &self.deceleration,
+            gravity: &self.gravity,
+            jump: &self.jump,
+            profiles: &self.profiles,
+        };
+        let text = toml::to_string(&tuning).unwrap_or_default();
+        text.bytes().fold(0xcbf2_9ce4_8422_2325u64, |h, b| (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3))
+    }
+
+    pub fn has_profile(&self, name: &str) -> bool {
+        self.profiles.contains_key(name)
+    }
+
+    // Base values with the named profile applied; an unknown name gives the base values
+    pub fn movement(&self, profile: &str) -> Movement {
+        let p = self.profiles.get(profile).cloned().unwrap_or_default();
+        Movement {
+            profile: profile.to_string(),
+            max_speed: p.max_speed.unwrap_or(self.max_speed.value),
+            acceleration: p.acceleration.unwrap_or(self.acceleration.value),
+            deceleration: p.deceleration.unwrap_or(self.deceleration.value),
+            gravity: p.gravity.unwrap_or(self.gravity.value),
             jump: JumpCfg {
-                velocity: DEFAULT_JUMP_VELOCITY,
-                max_hold_ms: DEFAULT_JUMP_MAX_HOLD_MS,
-                cut_factor: DEFAULT_JUMP_CUT_FACTOR,
-                max_jumps: 2,
-            },
-            camera: CameraCfg {
-                lag_s: 0.15,
-                lookahead_s: 0.25,
-                noise_amp: 2.0,
-                noise_freq_hz: 0.7,
-                noise_amp_x: None,
-                noise_amp_y: None,
+                velocity: p.jump_velocity.unwrap_or(self.jump.velocity),
+                max_hold_ms: p.jump_max_hold_ms.unwrap_or(self.jump.max_hold_ms),
+                cut_factor: p.jump_cut_factor.unwrap_or(self.jump.cut_factor),
+                max_jumps: p.max_jumps.unwrap_or(self.jump.max_jumps),
             },
         }
     }
 }
 
-pub fn default_max_jumps() -> u8 { 2 }
+// Where each config value came from, logged at startup so a typo in config.toml
+// (silently falling back to a default) is easy to spot
+#[derive(Default, Debug)]
+pub struct ConfigReport {
+    // None when there was no config file
+    pub source: Option<String>,
+    pub from_file: Vec<String>,
+    pub defaulted: Vec<String>,
+    // Values set from the command line or the environment
+    pub overridden: Vec<String>,
+    // Keys in the file that the game doesn't know, with their location
+    pub unknown: Vec<String>,
+}
+
+impl ConfigReport {
+    pub fn log(&self) {
+        match &self.source {
+            Some(src) => bevy::log::info!("Config loaded from {}", src),
+            None => bevy::log::info!("No {}; using default config", CONFIG_PATH),
+        }
+        if !self.from_file.is_empty() {
+            bevy::log::info!("From file: {}", self.from_file.join(", "));
+        }
+        if !self.defaulted.is_empty() {
+            bevy::log::info!("Defaults: {}", self.defaulted.join(", "));
+        }
+        if !self.overridden.is_empty() {
+            bevy::log::info!("Overridden: {}", self.overridden.join(", "));
+        }
+        for key in &self.unknown {
+            bevy::log::warn!("Unknown config key (ignored): {}", key);
+        }
+    }
+}
+
+// 1-based line and column in `content` where a TOML error starts, when known.
+// Shared by config and level files.
+pub(crate) fn error_position(content: &str, err: &toml::de::Error) -> (Option<usize>, Option<usize>) {
+    let Some(span) = err.span() else { return (None, None); };
+    let before = &content[..span.start.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.chars().rev().take_while(|c| *c != '\n').count() + 1;
+    (Some(line), Some(column))
+}
+
+// A config file that can't be used: bad TOML, a value of the wrong type, or a
+// value out of range. Line and column are 1-based when known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub source: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
 
-pub fn load_config() -> GameConfig {
-    match fs::read_to_string("config.toml") {
-        Ok(content) => toml::from_str::<GameConfig>(&content).unwrap_or_default(),
-        Err(_) => GameConfig::default(),
+impl ConfigError {
+    fn parse(source: &str, content: &str, err: &toml::de::Error) -> Self {
+        let (line, column) = error_position(content, err);
+        Self { source: source.to_string(), line, column, message: err.message().trim().to_string() }
     }
 }
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.line, self.column) {
+            (Some(l), Some(c)) => write!(f, "{}:{}:{}: {}", self.source, l, c, self.message),
+            (Some(l), None) => write!(f, "{}:{}: {}", self.source, l, self.message),
+            _ => write!(f, "{}: {}", self.source, self.message),
+        }
+    }
+}
+
+// One `key=value` override from the command line or the environment, applied on
+// top of the file; `origin` names where it came from in errors and the report
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigOverride {
+    pub key: String,
+    pub value: String,
+    pub origin: String,
+}
+
+impl ConfigOverride {
+    pub fn parse(arg: &str, origin: &str) -> Result<Self, String> {
+        match arg.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => Ok(Self {
+                key: key.trim().to_string(),
+                value: value.trim().to_string(),
+                origin: origin.to_string(),
+            }),
+            _ => Err(format!("expected key=value, got '{}'", arg)),
+        }
+    }
+}
+
+// config.toml in the working directory, else the first one found next to the
+// executable or in a folder above it (so `target/debug/game` finds the repo's)
+pub fn default_config_path() -> PathBuf {
+    let local = PathBuf::from(CONFIG_PATH);
+    if local.exists() {
+        return local;
+    }
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.ancestors().skip(1).map(|dir| dir.join(CONFIG_PATH)).find(|p| p.exists()))
+        .unwrap_or(local)
+}
+
+// A missing default config file gives the defaults; a missing explicit one, or a
+// broken one, is an error rather than a silent fallback, since it would mean
+// completely different physics
+pub fn load_config(path: Option<&Path>, overrides: &[ConfigOverride]) -> Result<(GameConfig, ConfigReport), Vec<ConfigError>> {
+    let (file, explicit) = match path {
+        Some(p) => (p.to_path_buf(), true),
+        None => (default_config_path(), false),
+    };
+    let source = file.display().to_string();
+    match fs::read_to_string(&file) {
+        Ok(content) => parse_config(&source, &content, overrides),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound && !explicit => {
+            let (cfg, mut report) = parse_config(&source, "", overrides)?;
+            report.source = None;
+            Ok((cfg, report))
+        }
+        Err(e) => Err(vec![ConfigError { source, line: None, column: None, message: e.to_string() }]),
+    }
+}
+
+// Parse and validate config text with the overrides applied; `source` names it
+// in errors and the report. Sections and values left out fall back to their
+// defaults one by one.
+pub fn parse_config(source: &str, content: &str, overrides: &[ConfigOverride]) -> Result<(GameConfig, ConfigReport), Vec<ConfigError>> {
+    let table = toml::from_str::<toml::Table>(content).map_err(|e| vec![ConfigError::parse(source, content, &e)])?;
+    let mut cfg = toml::from_str::<GameConfig>(content).map_err(|e| vec![ConfigError::parse(source, content, &e)])?;
+    let known = known_keys();
+
+    // Overrides go into the file's table (with the shipped profiles filled in, so
+    // overriding one value of a shipped profile keeps the rest) one at a time,
+    // so a bad one can be blamed on its origin
+    let mut applied: Vec<(String, &ConfigOverride)> = Vec::new();
+    let mut errors = Vec::new();
+    if !overrides.is_empty() {
+        let mut work = table.clone();
+        let profiles = work.entry("profiles").or_insert_with(|| toml::Table::new().into());
+        if let (Some(profiles), Ok(toml::Value::Table(shipped))) = (profiles.as_table_mut(), toml::Value::try_from(builtin_profiles())) {
+            for (name, p) in shipped {
+                profiles.entry(name).or_insert(p);
+            }
+        }
+        for o in overrides {
+            let fail = |message: String| ConfigError { source: o.origin.clone(), line: None, column: None, message };
+            let Some(key) = override_key(&known, &o.key) else {
+                errors.push(fail(format!("unknown config key: {}", o.key)));
+                continue;
+            };
+            let previous = set_path(&mut work, &key, parse_value(&o.value));
+            match work.clone().try_into::<GameConfig>() {
+                Ok(c) => {
+                    cfg = c;
+                    applied.retain(|(k, _)| *k != key);
+                    applied.push((key, o));
+                }
+                Err(e) => {
+                    errors.push(fail(format!("{}: {}", key, e.message().trim())));
+                    restore_path(&mut work, &key, previous);
+                }
+            }
+        }
+    }
+    // Profiles named in the file replace the shipped ones of the same name only
+    for (name, profile) in builtin_profiles() {
+        cfg.profiles.entry(name).or_insert(profile);
+    }
+
+    for (path, message) in cfg.problems() {
+        let message = format!("{}: {}", path, message);
+        errors.push(match applied.iter().find(|(k, _)| *k == path) {
+            Some((_, o)) => ConfigError { source: o.origin.clone(), line: None, column: None, message },
+            None => ConfigError { source: source.to_string(), line: key_line(content, &path), column: None, message },
+        });
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut unknown = Vec::new();
+    unknown_keys("", &table, &known, &mut unknown);
+    let unknown = unknown
+        .into_iter()
+        .map(|path| match key_line(content, &path) {
+            Some(line) => format!("{}:{}: {}", source, line, path),
+            None => format!("{}: {}", source, path),
+        })
+        .collect();
+    let overridden = applied.iter().map(|(k, o)| format!("{} = {} ({})", k, o.value, o.origin)).collect();
+    let (from_file, defaulted) = leaf_values(&cfg)
+        .into_iter()
+        .filter(|entry| !applied.iter().any(|(k, _)| entry_path(entry) == k))
+        .partition(|entry| has_path(&table, entry));
+    let report = ConfigReport { source: Some(source.to_string()), from_file, defaulted, overridden, unknown };
+    Ok((cfg, report))
+}
+
+// Full path of an override key, or None if the config has no such value.
+// Single-value sections can be set directly: `gravity` means `gravity.value`.
+fn override_key(known: &toml::Table, key: &str) -> Option<String> {
+    let mut cur = known;
+    let mut found = None;
+    for k in key.split('.') {
+        found = cur.get(k).or_else(|| cur.get("*"));
+        match found {
+            Some(toml::Value::Table(t)) => cur = t,
+            Some(_) => {}
+            None => return None,
+        }
+    }
+    match found? {
+        toml::Value::Table(t) if t.len() == 1 && t.contains_key("value") => Some(format!("{}.value", key)),
+        toml::Value::Table(_) => None,
+        _ => Some(key.to_string()),
+    }
+}
+
+// A TOML value as written (700, 0.5, true, "x"); anything else is a string
+fn parse_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("v = {}", raw))
+        .ok()
+        .and_then(|mut t| t.remove("v"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+// Set a dotted path, creating tables on the way; returns the previous value
+fn set_path(table: &mut toml::Table, path: &str, value: toml::Value) -> Option<toml::Value> {
+    let (parents, last) = path.rsplit_once('.').map_or((None, path), |(p, l)| (Some(p), l));
+    let mut cur = table;
+    for k in parents.into_iter().flat_map(|p| p.split('.')) {
+        let entry = cur.entry(k).or_insert_with(|| toml::Table::new().into());
+        if !entry.is_table() {
+            *entry = toml::Table::new().into();
+        }
+        cur = entry.as_table_mut().expect("just made a table");
+    }
+    cur.insert(last.to_string(), value)
+}
+
+fn restore_path(table: &mut toml::Table, path: &str, previous: Option<toml::Value>) {
+    match previous {
+        Some(v) => {
+            set_path(table, path, v);
+        }
+        None => {
+            let (parents, last) = path.rsplit_once('.').map_or((None, path), |(p, l)| (Some(p), l));
+            let mut cur = Some(table);
+            for k in parents.into_iter().flat_map(|p| p.split('.')) {
+                cur = cur.and_then(|t| t.get_mut(k)).and_then(|v| v.as_table_mut());
+            }
+            if let Some(t) = cur {
+                t.remove(last);
+            }
+        }
+    }
+}
+
+impl GameConfig {
+    // Out-of-range values as (key path, what's wrong), base values first
+    pub(crate) fn problems(&self) -> Vec<(String, String)> {
+        let mut out = Vec::new();
+        let mut check = |path: String, ok: bool, rule: &str| {
+            if !ok {
+                out.push((path, rule.to_string()));
+            }
+        };
+        check("gravity.value".into(), self.gravity.value > 0.0, "must be positive");
+        check("jump.cut_factor".into(), (0.0..=1.0).contains(&self.jump.cut_factor), "must be between 0 and 1");
+        check("jump.max_jumps".into(), self.jump.max_jumps >= 1, "must be at least 1");
+        check("display.width".into(), self.display.width >= 1, "must be at least 1");
+        check("display.height".into(), self.display.height >= 1, "must be at least 1");
+        check("display.window_width".into(), self.display.window_width >= 1, "must be at least 1");
+        check("display.window_height".into(), self.display.window_height >= 1, "must be at least 1");
+        check("player.frame_width".into(), self.player.frame_width >= 1, "must be at least 1");
+        check("player.frame_height".into(), self.player.frame_height >= 1, "must be at least 1");
+        check("player.columns".into(), self.player.columns >= 1, "must be at least 1");
+        check("player.rows".into(), self.player.rows >= 1, "must be at least 1");
+        for (name, p) in &self.profiles {
+            if let Some(g) = p.gravity {
+                check(format!("profiles.{}.gravity", name), g > 0.0, "must be positive");
+            }
+            if let Some(c) = p.jump_cut_factor {
+                check(format!("profiles.{}.jump_cut_factor", name), (0.0..=1.0).contains(&c), "must be between 0 and 1");
+            }
+            if let Some(n) = p.max_jumps {
+                check(format!("profiles.{}.max_jumps", name), n >= 1, "must be at least 1");
+            }
+        }
+        out
+    }
+}
+
+// Every key the config understands, as a table shaped like the file; the
+// entries of `profiles` are checked against its "*" entry
+fn known_keys() -> toml::Table {
+    let mut cfg = GameConfig::default();
+    cfg.camera.noise_amp_x = Some(0.0);
+    cfg.camera.noise_amp_y = Some(0.0);
+    let all = ProfileCfg {
+        max_speed: Some(0.0),
+        acceleration: Some(0.0),
+        deceleration: Some(0.0),
+        gravity: Some(0.0),
+        jump_velocity: Some(0.0),
+        jump_max_hold_ms: Some(0.0),
+        jump_cut_factor: Some(0.0),
+        max_jumps: Some(0),
+    };
+    cfg.profiles = BTreeMap::from([("*".to_string(), all)]);
+    toml::Table::try_from(&cfg).unwrap_or_default()
+}
+
+fn unknown_keys(prefix: &str, table: &toml::Table, known: &toml::Table, out: &mut Vec<String>) {
+    for (k, v) in table {
+        let path = if prefix.is_empty() { k.clone() } else { format!("{}.{}", prefix, k) };
+        let expected = known.get(k).or_else(|| known.get("*"));
+        match (v, expected) {
+            (_, None) => out.push(path),
+            (toml::Value::Table(t), Some(toml::Value::Table(kt))) => unknown_keys(&path, t, kt, out),
+            _ => {}
+        }
+    }
+}
+
+// Line of a "a.b.c" key in the file, for keys written as `c = ...` under `[a.b]`
+// or as dotted keys under a parent table
+fn key_line(content: &str, path: &str) -> Option<usize> {
+    let mut table = String::new();
+    for (i, raw) in content.lines().enumerate() {
+        let line = raw.trim();
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            table = header.trim().to_string();
+            if table == path {
+                return Some(i + 1);
+            }
+            continue;
+        }
+        let Some((key, _)) = line.split_once('=') else { continue; };
+        let key: String = key.split('.').map(str::trim).collect::<Vec<_>>().join(".");
+        let full = if table.is_empty() { key } else { format!("{}.{}", table, key) };
+        if full == path {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+// "path = value" for every leaf value of the config
+fn leaf_values(cfg: &GameConfig) -> Vec<String> {
+    fn walk(prefix: &str, value: &toml::Value, out: &mut Vec<String>) {
+        match value {
+            toml::Value::Table(t) => {
+                for (k, v) in t {
+                    let path = if prefix.is_empty() { k.clone() } else { format!("{}.{}", prefix, k) };
+                    walk(&path, v, out);
+                }
+            }
+            // Values are f32 in the config; print them as such (0.8, not 0.800000011920929)
+            toml::Value::Float(f) => out.push(format!("{} = {}", prefix, *f as f32)),
+            v => out.push(format!("{} = {}", prefix, v)),
+        }
+    }
+    let mut out = Vec::new();
+    if let Ok(value) = toml::Value::try_from(cfg) {
+        walk("", &value, &mut out);
+    }
+    out
+}
+
+// Whether the "a.b.c = value" entry's path is present in the file
+fn has_path(table: &toml::Table, entry: &str) -> bool {
+    let mut keys = entry_path(entry).split('.');
+    let Some(first) = keys.next() else { return false; };
+    let mut cur = table.get(first);
+    for k in keys {
+        cur = cur.and_then(|v| v.as_table()).and_then(|t| t.get(k));
+    }
+    cur.is_some()
+}
+
+fn entry_path(entry: &str) -> &str {
+    entry.split(" = ").next().unwrap_or_default()
+}
@@ -0,0 +1,341 @@
+// Developer console (` to open) and physics debug overlay (F3). Only built in
+// debug builds, or in release with the `dev_tools` feature.
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+
+use crate::components::{Exit, Ground, JumpState, Player, Slope, Velocity};
+use crate::config::{GameConfig, Movement};
+use crate::resources::{GameState, GodMode, LevelRequest, Lives, PendingStart, ProfileChoice, PLAYER_SIZE};
+
+const CONSOLE_LOG_LINES: usize = 8;
+// Length of the drawn velocity vector per px/s
+const VELOCITY_ARROW_SCALE: f32 = 0.15;
+
+pub struct DevToolsPlugin;
+
+impl Plugin for DevToolsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<DevConsole>()
+            .init_resource::<DebugOverlay>()
+            .init_resource::<GodMode>()
+            .add_systems(Startup, spawn_dev_ui)
+            .add_systems(PreUpdate, swallow_game_keys.after(InputSystem))
+            .add_systems(Update, (
+                console_input,
+                run_console_commands,
+                update_console_ui,
+            ).chain())
+            .add_systems(Update, (toggle_overlay, draw_overlay, update_overlay_text));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DevConsole {
+    pub open: bool,
+    pub line: String,
+    pub log: Vec<String>,
+    queued: Vec<String>,
+}
+
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+#[derive(Component)]
+struct ConsoleUi;
+
+#[derive(Component)]
+struct ConsoleText;
+
+#[derive(Component)]
+struct OverlayText;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleCommand {
+    Level(String),
+    Set(String, String),
+    God,
+    Teleport(Vec2),
+    Lives(u8),
+    Help,
+    Clear,
+}
+
+const HELP: &str = "level <name> | set <key> <value> | god | tp <x> <y> | lives <n> | clear\n\
+set keys: profile, max_speed, acceleration, deceleration, gravity, jump_velocity, \
+jump_max_hold_ms, jump_cut_factor, max_jumps";
+
+pub fn parse_command(line: &str) -> Result<ConsoleCommand, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let number = |s: &str| s.parse::<f32>().map_err(|_| format!("not a number: {}", s));
+    match words.as_slice() {
+        ["level", name] => Ok(ConsoleCommand::Level(name.to_string())),
+        ["set", key, value] => Ok(ConsoleCommand::Set(key.to_string(), value.to_string())),
+        ["god"] => Ok(ConsoleCommand::God),
+        ["tp", x, y] => Ok(ConsoleCommand::Teleport(Vec2::new(number(x)?, number(y)?))),
+        ["lives", n] => n.parse().map(ConsoleCommand::Lives).map_err(|_| format!("not a life count: {}", n)),
+        ["help"] => Ok(ConsoleCommand::Help),
+        ["clear"] => Ok(ConsoleCommand::Clear),
+        [] => Err(String::new()),
+        [cmd, ..] => Err(format!("unknown command or arguments: {} (try help)", cmd)),
+    }
+}
+
+// Apply a command to the world; returns the line to print
+pub fn run_command(world: &mut World, cmd: ConsoleCommand) -> Result<String, String> {
+    match cmd {
+        ConsoleCommand::Level(name) => {
+            world.resource_mut::<LevelRequest>().0 = Some(name.clone());
+            world.resource_mut::<NextState<GameState>>().set(GameState::Loading);
+            Ok(format!("loading {}", name))
+        }
+        ConsoleCommand::Set(key, value) => set_tuning(world, &key, &value),
+        ConsoleCommand::God => {
+            let mut god = world.get_resource_or_insert_with(GodMode::default);
+            god.0 = !god.0;
+            Ok(format!("god mode {}", if god.0 { "on" } else { "off" }))
+        }
+        ConsoleCommand::Teleport(pos) => {
+            world.resource_mut::<PendingStart>().0 = Some(pos);
+            Ok(format!("teleported to {} {}", pos.x, pos.y))
+        }
+        ConsoleCommand::Lives(n) => {
+            world.resource_mut::<Lives>().current = n;
+            Ok(format!("lives {}", n))
+        }
+        ConsoleCommand::Help => Ok(HELP.to_string()),
+        ConsoleCommand::Clear => Ok(String::new()),
+    }
+}
+
+// Tuning changes go to the active movement profile (or the base values when
+// there is none), so they take effect whichever profile is selected
+fn set_tuning(world: &mut World, key: &str, value: &str) -> Result<String, String> {
+    if key == "profile" {
+        if !world.resource::<GameConfig>().has_profile(value) {
+            return Err(format!("unknown profile: {}", value));
+        }
+        world.resource_mut::<ProfileChoice>().0 = Some(value.to_string());
+        return Ok(format!("profile {}", value));
+    }
+    let v: f32 = value.parse().map_err(|_| format!("not a number: {}", value))?;
+    let key = key.replace('.', "_");
+    let jumps = || match v {
+        v if v.fract() == 0.0 && (0.0..=255.0).contains(&v) => Ok(v as u8),
+        _ => Err(format!("{} must be a whole number", key)),
+    };
+    let active = world.resource::<Movement>().profile.clone();
+    // Changed on a copy, checked like config.toml, and only then kept
+    let mut cfg = world.resource::<GameConfig>().clone();
+    match cfg.profiles.get_mut(&active) {
+        Some(p) => match key.as_str() {
+            "max_speed" => p.max_speed = Some(v),
+            "acceleration" => p.acceleration = Some(v),
+            "deceleration" => p.deceleration = Some(v),
+            "gravity" => p.gravity = Some(v),
+            "jump_velocity" => p.jump_velocity = Some(v),
+            "jump_max_hold_ms" => p.jump_max_hold_ms = Some(v),
+            "jump_cut_factor" => p.jump_cut_factor = Some(v),
+            "max_jumps" => p.max_jumps = Some(jumps()?),
+            _ => return Err(format!("unknown key: {}", key)),
+        },
+        None => match key.as_str() {
+            "max_speed" => cfg.max_speed.value = v,
+            "acceleration" => cfg.acceleration.value = v,
+            "deceleration" => cfg.deceleration.value = v,
+            "gravity" => cfg.gravity.value = v,
+            "jump_velocity" => cfg.jump.velocity = v,
+            "jump_max_hold_ms" => cfg.jump.max_hold_ms = v,
+            "jump_cut_factor" => cfg.jump.cut_factor = v,
+            "max_jumps" => cfg.jump.max_jumps = jumps()?,
+            _ => return Err(format!("unknown key: {}", key)),
+        },
+    }
+    let before = world.resource::<GameConfig>().problems();
+    if let Some((path, rule)) = cfg.problems().into_iter().find(|p| !before.contains(p)) {
+        return Err(format!("{} {}", path, rule));
+    }
+    *world.resource_mut::<GameConfig>() = cfg;
+    Ok(format!("{} = {}", key, v))
+}
+
+fn console_input(mut reader: EventReader<KeyboardInput>, mut console: ResMut<DevConsole>) {
+    for ev in reader.read() {
+        if ev.state != ButtonState::Pressed { continue; }
+        if ev.key_code == KeyCode::Backquote {
+            console.open = !console.open;
+            continue;
+        }
+        if !console.open { continue; }
+        match &ev.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.line);
+                if !line.trim().is_empty() {
+                    console.log.push(format!("> {}", line));
+                    console.queued.push(line);
+                }
+            }
+            Key::Backspace => {
+                console.line.pop();
+            }
+            Key::Escape => console.open = false,
+            Key::Space => console.line.push(' '),
+            Key::Character(s) => console.line.extend(s.chars().filter(|c| !c.is_control())),
+            _ => {}
+        }
+    }
+}
+
+fn run_console_commands(world: &mut World) {
+    let queued = std::mem::take(&mut world.resource_mut::<DevConsole>().queued);
+    for line in queued {
+        let out = parse_command(&line).and_then(|cmd| {
+            let clear = cmd == ConsoleCommand::Clear;
+            let out = run_command(world, cmd);
+            if clear {
+                world.resource_mut::<DevConsole>().log.clear();
+            }
+            out
+        });
+        let text = out.unwrap_or_else(|e| e);
+        let mut console = world.resource_mut::<DevConsole>();
+        console.log.extend(text.lines().filter(|l| !l.is_empty()).map(String::from));
+    }
+}
+
+// While the console is open the game doesn't see the keyboard
+fn swallow_game_keys(console: Res<DevConsole>, mut keys: ResMut<ButtonInput<KeyCode>>) {
+    if console.open {
+        keys.reset_all();
+    }
+}
+
+fn spawn_dev_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font: Handle<Font> = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(0.0),
+                    right: Val::Px(0.0),
+                    bottom: Val::Px(0.0),
+                    padding: UiRect::all(Val::Px(6.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            ConsoleUi,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                TextBundle::from_section("", TextStyle { font: font.clone(), font_size: 16.0, color: Color::WHITE }),
+                ConsoleText,
+            ));
+        });
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section("", TextStyle { font, font_size: 14.0, color: Color::srgb(1.0, 1.0, 0.6) }),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(32.0),
+                left: Val::Px(10.0),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        OverlayText,
+    ));
+}
+
+fn update_console_ui(
+    console: Res<DevConsole>,
+    mut q_ui: Query<&mut Visibility, With<ConsoleUi>>,
+    mut q_text: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() { return; }
+    for mut vis in q_ui.iter_mut() {
+        *vis = if console.open { Visibility::Visible } else { Visibility::Hidden };
+    }
+    if let Ok(mut text) = q_text.get_single_mut() {
+        let skip = console.log.len().saturating_sub(CONSOLE_LOG_LINES);
+        let mut lines: Vec<&str> = console.log[skip..].iter().map(String::as_str).collect();
+        let prompt = format!("> {}_", console.line);
+        lines.push(&prompt);
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+fn toggle_overlay(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+    mut q_text: Query<&mut Visibility, With<OverlayText>>,
+) {
+    if keyboard.just_pressed(KeyCode::F3) {
+        overlay.enabled = !overlay.enabled;
+        for mut vis in q_text.iter_mut() {
+            *vis = if overlay.enabled { Visibility::Visible } else { Visibility::Hidden };
+        }
+    }
+}
+
+fn draw_overlay(
+    overlay: Res<DebugOverlay>,
+    mut gizmos: Gizmos,
+    q_ground: Query<(&Transform, &Sprite), With<Ground>>,
+    q_slopes: Query<&Slope>,
+    q_exits: Query<(&Transform, &Exit)>,
+    q_player: Query<(&Transform, &Velocity, &JumpState), With<Player>>,
+) {
+    if !overlay.enabled { return; }
+    for (t, sprite) in q_ground.iter() {
+        let size = sprite.custom_size.unwrap_or(Vec2::ZERO);
+        gizmos.rect_2d(t.translation.truncate(), 0.0, size, Color::srgb(0.2, 1.0, 0.2));
+    }
+    for slope in q_slopes.iter() {
+        let surface = slope.points.iter().map(|p| Vec2::new(p.x, p.y));
+        gizmos.linestrip_2d(surface, Color::srgb(0.2, 1.0, 0.2));
+    }
+    for (t, exit) in q_exits.iter() {
+        gizmos.rect_2d(t.translation.truncate(), 0.0, exit.size, Color::srgb(0.3, 0.5, 1.0));
+    }
+    for (t, v, jump) in q_player.iter() {
+        let pos = t.translation.truncate();
+        let color = if jump.grounded { Color::srgb(1.0, 1.0, 0.3) } else { Color::srgb(1.0, 0.4, 0.2) };
+        gizmos.rect_2d(pos, 0.0, PLAYER_SIZE, color);
+        gizmos.arrow_2d(pos, pos + v.0 * VELOCITY_ARROW_SCALE, Color::srgb(1.0, 0.2, 1.0));
+    }
+}
+
+fn update_overlay_text(
+    overlay: Res<DebugOverlay>,
+    movement: Res<Movement>,
+    god: Res<GodMode>,
+    q_player: Query<(&Transform, &Velocity, &JumpState), With<Player>>,
+    mut q_text: Query<&mut Text, With<OverlayText>>,
+) {
+    if !overlay.enabled { return; }
+    let (Ok((t, v, jump)), Ok(mut text)) = (q_player.get_single(), q_text.get_single_mut()) else { return; };
+    text.sections[0].value = format!(
+        "pos {:.1} {:.1}\nvel {:.1} {:.1}\ngrounded {}  jumping {}  jumps_used {}/{}\nprofile {}{}",
+        t.translation.x,
+        t.translation.y,
+        v.x,
+        v.y,
+        jump.grounded,
+        jump.jumping,
+        jump.jumps_used,
+        movement.jump.max_jumps,
+        movement.profile,
+        if god.0 { "  GOD" } else { "" },
+    );
+}
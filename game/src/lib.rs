@@ -0,0 +1,13 @@
+pub mod components;
+pub mod resources;
+pub mod config;
+pub mod physics;
+pub mod animation;
+pub mod reach;
+pub mod save;
+pub mod systems;
+mod plugins;
+#[cfg(any(debug_assertions, feature = "dev_tools"))]
+pub mod devtools;
+
+pub use plugins::{BackgroundPlugin, CameraPlugin, GamePlugin, ParticlesPlugin, PhysicsPlugin, ProgressPlugin, SpritesPlugin, UiPlugin};
@@ -1,35 +1,87 @@
 use bevy::prelude::*;
+use bevy::window::WindowMode;
 
-mod components;
-mod resources;
-mod config;
-mod systems;
+use game::config::load_config;
+use game::systems::ghost::GhostFile;
+use game::systems::replay::{config_hash_hex, fresh_seed, Replay};
+use game::GamePlugin;
 
-use config::load_config;
-use resources::{GameState, LevelManager, LevelRequest, Lives, PendingStart};
-#[cfg(target_arch = "wasm32")]
-use crate::systems::levels::poll_wasm_level_task;
+mod cli;
 
-fn main() {
-    let cfg = load_config();
-    // Parse optional --level <name-or-path>
-    let mut args = std::env::args().skip(1);
-    let mut level_arg: Option<String> = None;
-    while let Some(a) = args.next() {
-        if a == "--level" {
-            if let Some(v) = args.next() { level_arg = Some(v); }
-            break;
+fn main() -> AppExit {
+    let (vars, var_warnings) = cli::game_vars(std::env::vars_os());
+    for w in &var_warnings {
+        eprintln!("{}", w);
+    }
+    let cli = match cli::args_from_os(std::env::args_os().skip(1)).and_then(|args| cli::Cli::parse(args, vars)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            return AppExit::error();
+        }
+    };
+    if cli.help {
+        print!("{}", cli::USAGE);
+        return AppExit::Success;
+    }
+    let (cfg, cfg_report) = match load_config(cli.config.as_deref(), &cli.overrides) {
+        Ok(loaded) => loaded,
+        Err(errors) => {
+            for e in &errors {
+                eprintln!("{}", e);
+            }
+            eprintln!("Fix the config (or remove the file to use the defaults)");
+            return AppExit::error();
+        }
+    };
+    // Builds without the `windowed` feature have no window backend at all
+    let headless = cli.headless || cfg!(not(feature = "windowed"));
+
+    let replay = match cli.replay.map(|p| Replay::load(std::path::Path::new(&p))) {
+        Some(Ok(r)) => Some(r),
+        Some(Err(e)) => {
+            eprintln!("Failed to load replay: {}", e);
+            return AppExit::error();
+        }
+        None => None,
+    };
+    if let Some(r) = &replay {
+        if r.config_hash != config_hash_hex(&cfg) {
+            eprintln!("warning: replay was recorded with a different config; playback may diverge");
         }
     }
+    if headless && replay.is_none() {
+        eprintln!("Headless mode has no keyboard; pass --replay <file> to drive the player");
+        return AppExit::error();
+    }
+
+    let ghost = match cli.ghost.map(|p| GhostFile::load(std::path::Path::new(&p))) {
+        Some(Ok(g)) => Some(g),
+        Some(Err(e)) => {
+            eprintln!("Failed to load ghost: {}", e);
+            return AppExit::error();
+        }
+        None => None,
+    };
 
     let mut app = App::new();
-    app
-        .add_plugins(
+    if headless {
+        app.add_plugins((
+            MinimalPlugins,
+            bevy::log::LogPlugin::default(),
+            bevy::state::app::StatesPlugin,
+            bevy::input::InputPlugin,
+        ));
+    } else {
+        let display = &cfg.display;
+        app.add_plugins(
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(Window {
                         title: "Rust Side Scroller".to_string(),
-                        resolution: (960.0, 540.0).into(),
+                        resolution: (display.window_width as f32, display.window_height as f32).into(),
+                        resizable: display.resizable,
+                        mode: if display.fullscreen { WindowMode::BorderlessFullscreen } else { WindowMode::Windowed },
                         present_mode: bevy::window::PresentMode::AutoVsync,
                         ..default()
                     }),
@@ -41,39 +93,17 @@ fn main() {
                     file_path: "assets".into(),
                     ..default()
                 }),
-        )
-        .insert_resource(cfg)
-        .insert_resource(PendingStart::default())
-        .insert_resource(LevelManager { current: level_arg.unwrap_or_else(|| "level1".to_string()) })
-        .insert_resource(LevelRequest::default())
-        .insert_resource(Lives { current: 3, max: 3 })
-        .insert_resource(GameState::Running)
-        .add_event::<systems::particles::JumpBurstEvent>()
-        .add_event::<systems::particles::DirtKickEvent>()
-        .add_systems(Startup, (
-            systems::startup::setup,
-            systems::background::setup_parallax_background,
-        ))
-        .add_systems(Update, (
-            systems::player::player_input_system,
-            systems::player::physics_and_collision_system,
-            systems::levels::level_transition_system,
-            systems::camera::camera_follow_system,
-            systems::background::update_parallax_background,
-            systems::player::death_check_system,
-            systems::ui::update_lives_ui_system,
-            systems::particles::spawn_burst_on_event,
-            systems::particles::spawn_dirt_on_event,
-            systems::particles::update_particles,
-            systems::ui::game_over_restart_system,
-            systems::levels::exit_detection_system,
-            systems::player::apply_pending_start_system,
-        ));
-
-    // On web, poll the async level fetch task each frame.
-    #[cfg(target_arch = "wasm32")]
-    {
-        app.add_systems(Update, (poll_wasm_level_task,));
+        );
     }
-    app.run();
+    app.add_plugins(GamePlugin {
+        config: cfg,
+        first_level: cli.level.unwrap_or_else(|| "level1".to_string()),
+        seed: cli.seed.unwrap_or_else(fresh_seed),
+        headless,
+        replay,
+        record: cli.record.map(Into::into),
+        ghost,
+    });
+    cfg_report.log();
+    app.run()
 }
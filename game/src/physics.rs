@@ -0,0 +1,257 @@
+// Player movement and collision over plain data, shared by the fixed-tick
+// systems and by tools that simulate a level without an ECS world (the level
+// editor's play-test).
+
+// Re-exported so code using this module doesn't need its own bevy dependency
+pub use bevy::math::Vec2;
+
+use crate::components::JumpState;
+use crate::config::Movement;
+use crate::resources::{GameRng, PLAYER_SIZE};
+use crate::systems::input::{InputFrame, PlayerInput};
+
+// Bevy's default fixed timestep, which the game runs its physics at unless a
+// replay asks for another
+pub const TICK_HZ: f64 = 64.0;
+
+// Falling below this height loses a life
+pub const DEATH_Y: f32 = -600.0;
+
+// Extra downward reach used to keep a walking player attached to descending slopes
+const SLOPE_SNAP_PX: f32 = 6.0;
+
+#[derive(Clone, Debug, Default)]
+pub struct Body {
+    pub pos: Vec2,
+    pub vel: Vec2,
+    pub jump: JumpState,
+}
+
+// Things that happened during a step, for effects
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StepEvents {
+    // Direction the player turned to while running on the ground
+    pub dirt_kick: Option<f32>,
+    // A jump in the air, and whether it was a refunded bonus jump
+    pub air_jump: bool,
+    pub bonus_jump: bool,
+}
+
+// Advance the player by one tick. `grounds` are (center, size) of solid
+// rectangles, one-way from below.
+pub fn step<'a>(
+    body: &mut Body,
+    input: &PlayerInput,
+    mv: &Movement,
+    dt: f32,
+    rng: &mut GameRng,
+    grounds: impl IntoIterator<Item = (Vec2, Vec2)>,
+    slopes: impl IntoIterator<Item = &'a level_format::Slope>,
+) -> StepEvents {
+    let mut events = StepEvents::default();
+    let Body { pos: t, vel: v, jump } = body;
+    let input_dir = input.dir();
+
+    let target_speed = input_dir * mv.max_speed;
+    let prev_vx = v.x;
+    if input_dir.abs() > 0.0 {
+        v.x = approach(v.x, target_speed, mv.acceleration * dt);
+    } else {
+        v.x = approach(v.x, 0.0, mv.deceleration * dt);
+    }
+
+    // Gravity
+    v.y -= mv.gravity * dt;
+
+    // Integrate
+    t.x += v.x * dt;
+    t.y += v.y * dt;
+
+    // Simple AABB collision with each ground
+    let player_half = PLAYER_SIZE / 2.0;
+    let mut grounded = false;
+    for (g, size) in grounds {
+        let ground_half = size / 2.0;
+
+        let dx = (t.x - g.x).abs();
+        let dy = (t.y - g.y).abs();
+        let pen_x = player_half.x + ground_half.x - dx;
+        let pen_y = player_half.y + ground_half.y - dy;
+
+        if pen_x > 0.0 && pen_y > 0.0 {
+            // One-way platforms: ignore collisions when coming from below while moving upward
+            let player_bottom = t.y - player_half.y;
+            let ground_top = g.y + ground_half.y;
+            if player_bottom < ground_top && v.y > 0.0 {
+                // Allow the player to pass through from below
+                continue;
+            }
+
+            if pen_y < pen_x {
+                if t.y > g.y {
+                    // Land on top
+                    t.y = g.y + ground_half.y + player_half.y;
+                    v.y = 0.0;
+                    grounded = true;
+                } else {
+                    // Coming from below: do not push the player down (already handled by pass-through logic)
+                    // If we ever reach here (e.g., v.y <= 0), avoid bumping head
+                    continue;
+                }
+            } else {
+                // Horizontal resolution
+                if t.x > g.x {
+                    t.x = g.x + ground_half.x + player_half.x;
+                } else {
+                    t.x = g.x - ground_half.x - player_half.x;
+                }
+                v.x = 0.0;
+            }
+        }
+    }
+
+    // Sloped ground: solid from above only. While walking (grounded last step and
+    // not jumping) the player is pulled down onto the surface so running downhill
+    // doesn't turn into a series of tiny falls.
+    let snap_dist = if jump.grounded && !jump.jumping && v.y <= 0.0 {
+        SLOPE_SNAP_PX + v.x.abs() * dt
+    } else {
+        0.0
+    };
+    for slope in slopes {
+        if let Some(surface_y) = slope.surface_y_at(t.x) {
+            let player_bottom = t.y - player_half.y;
+            if v.y <= 0.0
+                && player_bottom <= surface_y + snap_dist
+                && player_bottom >= surface_y - slope.depth
+            {
+                t.y = surface_y + player_half.y;
+                v.y = 0.0;
+                grounded = true;
+            }
+        }
+    }
+    jump.grounded = grounded;
+
+    // Dirt kick: emit when reversing direction on ground
+    if grounded && input_dir.abs() > 0.0 {
+        let new_dir = input_dir.signum();
+        let old_dir = prev_vx.signum();
+        if old_dir != 0.0 && new_dir != 0.0 && old_dir != new_dir && prev_vx.abs() > 30.0 {
+            events.dirt_kick = Some(new_dir);
+        }
+    }
+
+    // Jumping: allow up to max_jumps
+    if input.jump_just_pressed() {
+        let (can_jump, bonus_triggered) = if grounded {
+            (true, false)
+        } else {
+            // 10% chance to grant an extra jump: refund one usage
+            let r01 = rng.next_f32();
+            let bonus_triggered = if r01 < 0.10 && jump.jumps_used > 1 {
+                jump.jumps_used -= 1; // refund one, effectively adding an extra jump
+                true
+            } else {
+                false
+            };
+            (jump.jumps_used < mv.jump.max_jumps, bonus_triggered)
+        };
+        if can_jump {
+            v.y = mv.jump.velocity;
+            jump.jumping = true;
+            jump.hold_ms = 0.0;
+            if grounded {
+                jump.jumps_used = 1;
+            } else {
+                jump.jumps_used = (jump.jumps_used + 1).min(mv.jump.max_jumps);
+            }
+            events.bonus_jump = bonus_triggered;
+            events.air_jump = jump.jumps_used >= 2;
+        }
+    }
+    // Track hold time while rising
+    if jump.jumping && input.jump_held() && v.y > 0.0 {
+        jump.hold_ms += dt * 1000.0;
+    }
+    // Early release jump cut
+    if input.jump_just_released() {
+        if jump.hold_ms < mv.jump.max_hold_ms && v.y > 0.0 {
+            v.y *= mv.jump.cut_factor;
+        }
+        jump.jumping = false;
+    }
+    // Reset jump state if landed
+    if grounded && v.y.abs() < f32::EPSILON {
+        jump.jumping = false;
+        jump.hold_ms = 0.0;
+        jump.jumps_used = 0;
+    }
+    events
+}
+
+#[inline]
+fn approach(current: f32, target: f32, max_delta: f32) -> f32 {
+    let delta = target - current;
+    let step = max_delta.clamp(0.0, delta.abs());
+    current + step * delta.signum()
+}
+
+// Player overlapping an exit rectangle
+pub fn touches_exit(pos: Vec2, exit_pos: Vec2, exit_size: Vec2) -> bool {
+    let p_half = PLAYER_SIZE / 2.0;
+    let half = exit_size / 2.0;
+    (pos.x - exit_pos.x).abs() < p_half.x + half.x && (pos.y - exit_pos.y).abs() < p_half.y + half.y
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimEvent {
+    Fell,
+    // Next level of the exit reached
+    Exit(String),
+}
+
+// One player in a level, stepped one fixed tick at a time like PhysicsPlugin does
+pub struct Sim {
+    pub level: level_format::Level,
+    pub movement: Movement,
+    pub body: Body,
+    pub input: PlayerInput,
+    pub rng: GameRng,
+    pub ticks: u64,
+    // What happened during the last tick
+    pub events: StepEvents,
+    // (center, size) of the level's solid rectangles, tiles merged
+    pub grounds: Vec<(Vec2, Vec2)>,
+}
+
+impl Sim {
+    pub fn new(level: level_format::Level, movement: Movement, seed: u64, start: Vec2) -> Self {
+        let grounds = level.solid_rects().iter().map(|r| (Vec2::new(r.x, r.y), Vec2::new(r.w, r.h))).collect();
+        Self {
+            level,
+            movement,
+            body: Body { pos: start, ..Default::default() },
+            input: PlayerInput::default(),
+            rng: GameRng::new(seed),
+            ticks: 0,
+            events: StepEvents::default(),
+            grounds,
+        }
+    }
+
+    pub fn tick(&mut self, frame: InputFrame, dt: f32) -> Option<SimEvent> {
+        self.input.push(frame);
+        self.ticks += 1;
+        self.events = step(&mut self.body, &self.input, &self.movement, dt, &mut self.rng, self.grounds.iter().copied(), &self.level.slopes);
+
+        if self.body.pos.y < DEATH_Y {
+            return Some(SimEvent::Fell);
+        }
+        self.level
+            .exits
+            .iter()
+            .find(|e| touches_exit(self.body.pos, Vec2::new(e.x, e.y), Vec2::new(e.w, e.h)))
+            .map(|e| SimEvent::Exit(e.next.clone()))
+    }
+}
@@ -0,0 +1,250 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::{GameConfig, Movement};
+use crate::resources::{GameRng, GameState, LevelManager, LevelProfile, LevelRequest, LevelTimer, Lives, PendingStart, ProfileChoice};
+use crate::save;
+use crate::systems;
+use crate::systems::ghost::{GhostFile, ImportedGhost};
+use crate::systems::replay::{Replay, ReplayPlayback, ReplayRecorder};
+
+// The whole game on top of DefaultPlugins (or MinimalPlugins when headless)
+pub struct GamePlugin {
+    pub config: GameConfig,
+    // Level loaded by New game; a replay always starts on its own level
+    pub first_level: String,
+    pub seed: u64,
+    // Simulation only: no window, renderer, UI or save file
+    pub headless: bool,
+    // Play these inputs instead of reading the keyboard
+    pub replay: Option<Replay>,
+    // Write each level attempt here when it ends (--record)
+    pub record: Option<PathBuf>,
+    // Race this ghost instead of the saved best run on its level
+    pub ghost: Option<GhostFile>,
+}
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        let first_level = self.replay.as_ref().map(|r| r.level.clone()).unwrap_or_else(|| self.first_level.clone());
+        let seed = self.replay.as_ref().map(|r| r.seed).unwrap_or(self.seed);
+        app
+            .insert_resource(self.config.clone())
+            .insert_resource(LevelManager { first: first_level.clone(), current: first_level, last_loaded: None })
+            .insert_resource(GameRng::new(seed))
+            .insert_resource(ReplayRecorder { out_path: self.record.clone(), ..default() })
+            .add_plugins(PhysicsPlugin);
+
+        if self.headless {
+            app
+                .add_systems(OnEnter(GameState::LevelComplete), systems::replay::finish_replay_on_stop)
+                .add_systems(OnEnter(GameState::GameOver), systems::replay::finish_replay_on_stop)
+                .add_systems(OnEnter(GameState::LoadError), systems::state::exit_on_load_error)
+                .add_systems(Last, systems::replay::write_recording_on_exit);
+        } else {
+            app
+                .add_plugins((CameraPlugin, SpritesPlugin, ParticlesPlugin, BackgroundPlugin, ProgressPlugin, UiPlugin))
+                .add_systems(OnEnter(GameState::LevelComplete), (
+                    systems::replay::write_recording_on_stop,
+                    systems::replay::finish_replay_on_stop,
+                ).chain())
+                .add_systems(OnEnter(GameState::GameOver), (
+                    systems::replay::write_recording_on_stop,
+                    systems::replay::finish_replay_on_stop,
+                ).chain())
+                .add_systems(Update, systems::levels::build_slope_meshes);
+            #[cfg(any(debug_assertions, feature = "dev_tools"))]
+            app.add_plugins(crate::devtools::DevToolsPlugin);
+        }
+
+        if let Some(g) = &self.ghost {
+            app.insert_resource(ImportedGhost(g.clone()));
+        }
+        if let Some(r) = &self.replay {
+            app.insert_resource(Time::<Fixed>::from_hz(r.tick_hz));
+            if self.headless {
+                // Advance exactly one fixed tick per update instead of waiting on the wall clock
+                app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / r.tick_hz)));
+            }
+            if let Some(profile) = &r.profile {
+                app.insert_resource(ProfileChoice(Some(profile.clone())));
+            }
+            app.insert_resource(ReplayPlayback::new(r.clone()));
+        }
+    }
+}
+
+// Fixed-tick simulation: level loading, player physics, deaths and exits, driven
+// by PlayerInput frames. Expects GameConfig, LevelManager and GameRng to be
+// inserted by the app (GamePlugin does).
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<PendingStart>()
+            .init_resource::<LevelRequest>()
+            .insert_resource(Lives { current: 3, max: 3 })
+            .init_resource::<systems::levels::LevelLoadFailure>()
+            .init_resource::<LevelTimer>()
+            .init_resource::<systems::input::PlayerInput>()
+            .init_resource::<systems::input::KeyboardLatch>()
+            .init_resource::<ReplayRecorder>()
+            .init_resource::<LevelProfile>()
+            .init_resource::<ProfileChoice>()
+            .init_resource::<Movement>()
+            .init_state::<GameState>()
+            .enable_state_scoped_entities::<GameState>()
+            .add_event::<systems::particles::JumpBurstEvent>()
+            .add_event::<systems::particles::DirtKickEvent>()
+            .add_event::<systems::levels::LevelLoadEvent>()
+            .add_systems(Startup, systems::startup::spawn_player)
+            .add_systems(OnEnter(GameState::Loading), systems::levels::load_requested_level)
+            .add_systems(Update, (
+                systems::input::player_input_system,
+                systems::tuning::cycle_profile_hotkey,
+            ).run_if(in_state(GameState::Playing)))
+            .add_systems(Update, (
+                systems::state::finish_boot.run_if(in_state(GameState::Boot)),
+                systems::levels::level_load_result_system.run_if(in_state(GameState::Loading)),
+                systems::replay::begin_attempt_on_load,
+                systems::player::apply_pending_start_system,
+            ))
+            // Simulation runs at a fixed rate from PlayerInput frames so it can be
+            // recorded and replayed; it stops as soon as the level ends mid-frame.
+            .add_systems(FixedUpdate, (
+                systems::tuning::update_movement,
+                systems::replay::advance_input_tick,
+                systems::player::physics_and_collision_system,
+                systems::player::death_check_system,
+                systems::levels::exit_detection_system,
+                systems::progress::tick_level_timer,
+                // Respawn after a death within the same tick
+                systems::player::apply_pending_start_system,
+                systems::ghost::record_ghost_frame,
+                systems::ghost::advance_ghost,
+            ).chain().run_if(systems::state::simulation_running));
+
+        // On web, poll the async level fetch task each frame.
+        #[cfg(target_arch = "wasm32")]
+        {
+            app.init_resource::<systems::levels::WasmLevelChan>();
+            app.add_systems(Update, systems::levels::poll_wasm_level_task);
+        }
+    }
+}
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, systems::camera::spawn_camera)
+            .add_systems(Update, (systems::camera::fit_viewport_to_window, systems::camera::toggle_fullscreen))
+            .add_systems(Update, systems::camera::camera_follow_system.run_if(in_state(GameState::Playing)));
+    }
+}
+
+// Textures on level sprites, tile layer meshes and the player's animated sprite
+// sheet, with a placeholder for images that are missing
+pub struct SpritesPlugin;
+
+impl Plugin for SpritesPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_asset::<crate::animation::PlayerAnimations>()
+            .register_asset_loader(systems::animation::PlayerAnimationsLoader)
+            .add_systems(Startup, (systems::sprites::setup_placeholder_texture, systems::animation::load_player_animations))
+            .add_systems(Update, (
+                systems::sprites::setup_player_sprite,
+                systems::sprites::load_textures,
+                systems::sprites::replace_missing_textures,
+                systems::sprites::face_player_sprite,
+                systems::tilemap::build_tile_meshes,
+                systems::tilemap::replace_missing_tilesets,
+            ).chain())
+            // Animations freeze with the world behind overlays
+            .add_systems(Update, systems::animation::animate_player
+                .after(systems::sprites::setup_player_sprite)
+                .run_if(in_state(GameState::Playing)));
+    }
+}
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        // Particles freeze with the world behind overlays
+        app.add_systems(Update, (
+            systems::particles::spawn_burst_on_event,
+            systems::particles::spawn_dirt_on_event,
+            systems::particles::update_particles,
+        ).run_if(in_state(GameState::Playing)));
+    }
+}
+
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, systems::background::setup_parallax_background)
+            .add_systems(Update, (systems::background::update_parallax_background, systems::background::update_decoration_parallax));
+    }
+}
+
+// Save data, speedrun splits, ghosts and the replay/export hotkeys
+pub struct ProgressPlugin;
+
+impl Plugin for ProgressPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<systems::speedrun::SpeedrunTimer>()
+            .insert_resource(save::load_save())
+            .add_systems(Startup, systems::ghost::spawn_ghost)
+            .add_systems(OnEnter(GameState::LevelComplete), (
+                systems::progress::save_on_level_complete,
+                systems::speedrun::record_split,
+            ).chain())
+            .add_systems(Update, (
+                systems::progress::save_on_level_loaded,
+                systems::speedrun::reset_run_on_new_game,
+                systems::speedrun::reset_run_after_finish,
+                systems::speedrun::export_splits_hotkey,
+                systems::speedrun::toggle_splits_ui,
+                systems::speedrun::update_splits_ui,
+                systems::replay::save_replay_hotkey,
+                systems::ghost::reset_ghost_on_load,
+                systems::ghost::ghost_hotkeys,
+            ));
+    }
+}
+
+// HUD, menu overlays and the keyboard/mouse menu actions. Reads SaveData, so
+// it goes together with ProgressPlugin.
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<systems::ui::MenuActionEvent>()
+            .add_systems(Startup, systems::ui::setup_ui)
+            .add_systems(OnEnter(GameState::Title), systems::ui::spawn_title_ui)
+            .add_systems(OnEnter(GameState::Paused), (systems::ui::spawn_pause_ui, systems::state::pause_time))
+            .add_systems(OnExit(GameState::Paused), systems::state::resume_time)
+            // Shows the best time, so after the save has been updated
+            .add_systems(OnEnter(GameState::LevelComplete), systems::ui::spawn_level_complete_ui
+                .after(systems::progress::save_on_level_complete)
+                .after(systems::speedrun::record_split))
+            .add_systems(OnEnter(GameState::GameOver), systems::ui::spawn_game_over_ui)
+            .add_systems(OnEnter(GameState::LoadError), systems::ui::spawn_load_error_ui)
+            .add_systems(Update, (
+                systems::ui::update_lives_ui_system,
+                systems::ui::menu_button_system,
+                systems::ui::menu_keyboard_system,
+                systems::state::apply_menu_actions,
+            ));
+    }
+}
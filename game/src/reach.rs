@@ -0,0 +1,271 @@
+// Where the player can get to in a level, found by simulating jumps with the
+// real physics from standing spots on every surface. Used by the level editor
+// to draw jump arcs and flag platforms and exits that can't be reached.
+
+use std::collections::{BTreeSet, VecDeque};
+
+use bevy::math::Vec2;
+use level_format::Level;
+
+use crate::config::Movement;
+use crate::physics::{self, Body, DEATH_Y, TICK_HZ};
+use crate::resources::{GameRng, PLAYER_SIZE};
+use crate::systems::input::{InputFrame, PlayerInput};
+
+// Something the player can stand on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Surface {
+    Platform(usize),
+    Slope(usize),
+}
+
+// One simulated jump (or walk off an edge)
+#[derive(Clone, Debug)]
+pub struct Arc {
+    // None for the drop from the start point
+    pub from: Option<Surface>,
+    // Where it landed; None when the player fell out of the level
+    pub to: Option<Surface>,
+    // Player positions along the way, every few ticks
+    pub points: Vec<Vec2>,
+    // Exits touched on the way
+    pub exits: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Reachability {
+    // Where the player lands after spawning
+    pub start: Option<Surface>,
+    pub reachable: BTreeSet<Surface>,
+    pub reachable_exits: BTreeSet<usize>,
+    // Every jump tried from the reachable surfaces
+    pub arcs: Vec<Arc>,
+}
+
+impl Reachability {
+    pub fn unreachable_platforms(&self, level: &Level) -> Vec<usize> {
+        (0..level.platforms.len()).filter(|i| !self.reachable.contains(&Surface::Platform(*i))).collect()
+    }
+
+    pub fn unreachable_slopes(&self, level: &Level) -> Vec<usize> {
+        (0..level.slopes.len()).filter(|i| !self.reachable.contains(&Surface::Slope(*i))).collect()
+    }
+
+    pub fn unreachable_exits(&self, level: &Level) -> Vec<usize> {
+        (0..level.exits.len()).filter(|i| !self.reachable_exits.contains(i)).collect()
+    }
+}
+
+// Give up on a jump that hasn't landed after this long
+const MAX_AIR_S: f32 = 5.0;
+// Standing spots tried per surface
+const MAX_SAMPLES: usize = 9;
+// Keep every n-th position of an arc
+const POINT_EVERY: u32 = 3;
+
+// Surfaces reachable from the start point, walking, jumping and falling
+pub fn analyze(level: &Level, mv: &Movement) -> Reachability {
+    let level = &tiles_as_platforms(level);
+    let mut out = Reachability::default();
+    let start = Vec2::new(level.start.x, level.start.y);
+    let drop = fly(level, mv, None, Body { pos: start, ..Default::default() }, Plan::default());
+    out.start = drop.to;
+    out.reachable_exits.extend(drop.exits.iter().copied());
+    out.arcs.push(drop);
+
+    let mut queue: VecDeque<Surface> = out.start.into_iter().collect();
+    out.reachable.extend(out.start);
+    while let Some(surface) = queue.pop_front() {
+        out.reachable_exits.extend(exits_along(level, surface));
+        for arc in jumps(level, mv, surface) {
+            out.reachable_exits.extend(arc.exits.iter().copied());
+            if let Some(to) = arc.to {
+                if out.reachable.insert(to) {
+                    queue.push_back(to);
+                }
+            }
+            out.arcs.push(arc);
+        }
+    }
+    out
+}
+
+// Every jump tried from standing spots on `surface`
+pub fn jumps_from(level: &Level, mv: &Movement, surface: Surface) -> Vec<Arc> {
+    jumps(&tiles_as_platforms(level), mv, surface)
+}
+
+fn jumps(level: &Level, mv: &Movement, surface: Surface) -> Vec<Arc> {
+    let Some((left, right)) = span(level, surface) else { return Vec::new(); };
+    // Distance needed to get up to full speed
+    let run_up = mv.max_speed * mv.max_speed / (2.0 * mv.acceleration.max(1.0));
+    let extra_jumps = mv.jump.max_jumps.saturating_sub(1);
+
+    let mut arcs = Vec::new();
+    for x in samples(left, right) {
+        let Some(y) = surface_y(level, surface, x) else { continue; };
+        let pos = Vec2::new(x, y + PLAYER_SIZE.y / 2.0);
+        for dir in [-1.0f32, 0.0, 1.0] {
+            // Run up from the far side of the surface, as far as it allows
+            let room = if dir > 0.0 { x - left } else { right - x };
+            let speed = if room >= run_up { mv.max_speed } else { (2.0 * mv.acceleration * room).sqrt() };
+            let vel = Vec2::new(dir * speed, 0.0);
+            let body = Body { pos, vel, jump: crate::components::JumpState { grounded: true, ..Default::default() } };
+            let mut plans = Vec::new();
+            if dir != 0.0 {
+                plans.push(Plan { dir, jump: false, full_hold: false, air_jumps: 0 });
+            }
+            for full_hold in [true, false] {
+                for air_jumps in [0, extra_jumps] {
+                    plans.push(Plan { dir, jump: true, full_hold, air_jumps });
+                }
+            }
+            plans.dedup();
+            arcs.extend(plans.into_iter().map(|plan| fly(level, mv, Some(surface), body.clone(), plan)));
+        }
+    }
+    arcs
+}
+
+// The level with its solid tiles turned into platforms after its own, so merged
+// tile rectangles are surfaces too; Platform indices of the level stay valid
+fn tiles_as_platforms(level: &Level) -> Level {
+    Level { platforms: level.solid_rects(), tile_layers: Vec::new(), ..level.clone() }
+}
+
+// How the player is steered through one jump
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Plan {
+    // Held the whole time
+    dir: f32,
+    jump: bool,
+    // Hold jump as long as it helps, or tap it
+    full_hold: bool,
+    // Further jumps pressed at the top of each arc
+    air_jumps: u8,
+}
+
+fn fly(level: &Level, mv: &Movement, from: Option<Surface>, mut body: Body, plan: Plan) -> Arc {
+    let dt = (1.0 / TICK_HZ) as f32;
+    let hold_ticks = (mv.jump.max_hold_ms / 1000.0 / dt).ceil() as u32 + 1;
+    let mut input = PlayerInput::default();
+    let mut rng = GameRng::new(0);
+    let mut arc = Arc { from, to: None, points: vec![body.pos], exits: Vec::new() };
+
+    let mut jumps_left = plan.air_jumps;
+    // Ticks since the current jump was pressed; None while not pressing
+    let mut pressing: Option<u32> = None;
+    let mut airborne = false;
+    // Gone up since the last jump was pressed
+    let mut rising = false;
+    for tick in 0..(MAX_AIR_S / dt) as u32 {
+        let mut jump = false;
+        if plan.jump && tick == 0 {
+            pressing = Some(0);
+        } else if jumps_left > 0 && rising && body.vel.y <= 0.0 && pressing.is_none() {
+            // At the top of the arc, jump again
+            jumps_left -= 1;
+            rising = false;
+            pressing = Some(0);
+        }
+        if let Some(held) = pressing {
+            let limit = if plan.full_hold { hold_ticks } else { 1 };
+            if held < limit {
+                jump = true;
+                pressing = Some(held + 1);
+            } else {
+                pressing = None;
+            }
+        }
+        input.push(InputFrame { left: plan.dir < 0.0, right: plan.dir > 0.0, jump });
+
+        let grounds = level.platforms.iter().map(|r| (Vec2::new(r.x, r.y), Vec2::new(r.w, r.h)));
+        physics::step(&mut body, &input, mv, dt, &mut rng, grounds, &level.slopes);
+        rising |= body.vel.y > 0.0;
+        if tick % POINT_EVERY == 0 {
+            arc.points.push(body.pos);
+        }
+        for (i, e) in level.exits.iter().enumerate() {
+            if !arc.exits.contains(&i) && physics::touches_exit(body.pos, Vec2::new(e.x, e.y), Vec2::new(e.w, e.h)) {
+                arc.exits.push(i);
+            }
+        }
+
+        if body.pos.y < DEATH_Y {
+            break;
+        }
+        if body.jump.grounded {
+            let under = surface_under(level, body.pos);
+            if airborne || (under.is_some() && under != from) {
+                arc.to = under;
+                break;
+            }
+        } else {
+            airborne = true;
+        }
+    }
+    arc.points.push(body.pos);
+    arc
+}
+
+// Exits the player touches just by walking along a surface
+fn exits_along(level: &Level, surface: Surface) -> Vec<usize> {
+    let Some((left, right)) = span(level, surface) else { return Vec::new(); };
+    let steps = ((right - left) / (PLAYER_SIZE.x / 2.0)).ceil().max(1.0) as usize;
+    let mut out = Vec::new();
+    for s in 0..=steps {
+        let x = left + (right - left) * s as f32 / steps as f32;
+        let Some(y) = surface_y(level, surface, x) else { continue; };
+        let pos = Vec2::new(x, y + PLAYER_SIZE.y / 2.0);
+        for (i, e) in level.exits.iter().enumerate() {
+            if !out.contains(&i) && physics::touches_exit(pos, Vec2::new(e.x, e.y), Vec2::new(e.w, e.h)) {
+                out.push(i);
+            }
+        }
+    }
+    out
+}
+
+// Spots spread evenly across [left, right], edges included
+fn samples(left: f32, right: f32) -> Vec<f32> {
+    let n = (((right - left) / 48.0).ceil() as usize + 1).clamp(1, MAX_SAMPLES);
+    if n == 1 {
+        return vec![(left + right) * 0.5];
+    }
+    (0..n).map(|i| left + (right - left) * i as f32 / (n - 1) as f32).collect()
+}
+
+// Range of x the player's center can stand at
+fn span(level: &Level, surface: Surface) -> Option<(f32, f32)> {
+    match surface {
+        Surface::Platform(i) => level.platforms.get(i).map(|r| (r.x - r.w * 0.5, r.x + r.w * 0.5)),
+        Surface::Slope(i) => {
+            let s = level.slopes.get(i)?;
+            Some((s.points.first()?.x, s.points.last()?.x))
+        }
+    }
+}
+
+fn surface_y(level: &Level, surface: Surface, x: f32) -> Option<f32> {
+    match surface {
+        Surface::Platform(i) => level.platforms.get(i).map(|r| r.y + r.h * 0.5),
+        Surface::Slope(i) => level.slopes.get(i)?.surface_y_at(x),
+    }
+}
+
+// The surface a grounded player at `pos` stands on
+pub fn surface_under(level: &Level, pos: Vec2) -> Option<Surface> {
+    const EPS: f32 = 0.5;
+    let half = PLAYER_SIZE / 2.0;
+    let bottom = pos.y - half.y;
+    let platform = level.platforms.iter().position(|r| {
+        (bottom - (r.y + r.h * 0.5)).abs() < EPS && (pos.x - r.x).abs() < half.x + r.w * 0.5
+    });
+    platform.map(Surface::Platform).or_else(|| {
+        level
+            .slopes
+            .iter()
+            .position(|s| s.surface_y_at(pos.x).is_some_and(|y| (bottom - y).abs() < EPS))
+            .map(Surface::Slope)
+    })
+}
@@ -8,17 +8,80 @@ pub const PLAYER_SIZE: Vec2 = Vec2::new(32.0, 32.0);
 #[derive(Resource, Clone, Copy)]
 pub struct Lives { pub current: u8, pub max: u8 }
 
-#[derive(Resource, Clone, Copy, PartialEq, Eq)]
-pub enum GameState { Running, GameOver }
+// Falling out of the level respawns without costing a life (dev console `god`)
+#[derive(Resource, Default)]
+pub struct GodMode(pub bool);
+
+// Top-level game flow; gameplay systems only run while Playing
+#[derive(States, Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameState {
+    #[default]
+    Boot,
+    Title,
+    Loading,
+    Playing,
+    Paused,
+    LevelComplete,
+    GameOver,
+    LoadError,
+}
 
 #[derive(Resource, Default)]
 pub struct LevelStart(pub Vec2);
 
+// Movement profile requested by the current level's meta.profile
+#[derive(Resource, Default)]
+pub struct LevelProfile(pub Option<String>);
+
+// Movement profile picked at runtime (F8); wins over the level and config choice
+#[derive(Resource, Default)]
+pub struct ProfileChoice(pub Option<String>);
+
 #[derive(Resource, Default)]
 pub struct PendingStart(pub Option<Vec2>);
 
 #[derive(Resource)]
-pub struct LevelManager { pub current: String }
+pub struct LevelManager {
+    // Level a new game starts from (--level or level1)
+    pub first: String,
+    pub current: String,
+    // Last level that loaded successfully; the error screen can go back to it
+    pub last_loaded: Option<String>,
+}
 
 #[derive(Resource, Default)]
 pub struct LevelRequest(pub Option<String>);
+
+// Seeded RNG for gameplay randomness (bonus jumps). Reset to `seed` whenever a
+// level loads so replays of a level attempt draw the same numbers.
+#[derive(Resource)]
+pub struct GameRng {
+    pub seed: u64,
+    state: u64,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self { seed, state: 0 };
+        rng.reset();
+        rng
+    }
+
+    pub fn reset(&mut self) {
+        // xorshift must not start from zero
+        self.state = self.seed.max(1);
+    }
+
+    pub fn next_f32(&mut self) -> f32 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+// Time spent playing the current level in seconds; reset whenever a level loads
+#[derive(Resource, Default)]
+pub struct LevelTimer {
+    pub elapsed: f32,
+}
@@ -0,0 +1,291 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Bump when the layout changes and add a step to `migrate`
+pub const SAVE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Resource, Clone, Debug, PartialEq)]
+pub struct SaveData {
+    pub version: u32,
+    // Level to resume with Continue; None until a level has been reached
+    #[serde(default)]
+    pub current_level: Option<String>,
+    pub lives: u8,
+    #[serde(default)]
+    pub unlocked: Vec<String>,
+    #[serde(default)]
+    pub levels: BTreeMap<String, LevelRecord>,
+    // Splits of the fastest complete run through the world
+    #[serde(default)]
+    pub best_run: Vec<Split>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Split {
+    pub level: String,
+    // Time spent in this level alone
+    pub time_s: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct LevelRecord {
+    #[serde(default)]
+    pub completions: u32,
+    // Fastest completion in seconds
+    #[serde(default)]
+    pub best_time_s: Option<f32>,
+    // Player position every `ghost_step` fixed ticks of the best-time run,
+    // drawn as a ghost that moves in a straight line between them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ghost: Vec<[f32; 2]>,
+    // Saves from before ghosts were thinned out have a position every tick
+    #[serde(default = "every_tick")]
+    pub ghost_step: u32,
+}
+
+pub(crate) fn every_tick() -> u32 {
+    1
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            current_level: None,
+            lives: 3,
+            unlocked: Vec::new(),
+            levels: BTreeMap::new(),
+            best_run: Vec::new(),
+        }
+    }
+}
+
+impl SaveData {
+    pub fn has_progress(&self) -> bool {
+        self.current_level.is_some()
+    }
+
+    pub fn unlock(&mut self, level: &str) {
+        if !self.unlocked.iter().any(|l| l == level) {
+            self.unlocked.push(level.to_string());
+        }
+    }
+
+    // Record a finished run; returns true when it beat the stored best time
+    pub fn record_completion(&mut self, level: &str, time_s: f32) -> bool {
+        let rec = self.levels.entry(level.to_string()).or_default();
+        rec.completions += 1;
+        let is_best = rec.best_time_s.is_none_or(|best| time_s < best);
+        if is_best {
+            rec.best_time_s = Some(time_s);
+        }
+        is_best
+    }
+}
+
+pub fn run_total(splits: &[Split]) -> f32 {
+    splits.iter().map(|s| s.time_s).sum()
+}
+
+#[derive(Deserialize)]
+struct VersionProbe {
+    version: u32,
+}
+
+fn parse_save(text: &str) -> Result<SaveData, String> {
+    let probe: VersionProbe = toml::from_str(text).map_err(|e| e.message().to_string())?;
+    if probe.version > SAVE_VERSION {
+        return Err(format!("save version {} is newer than supported {}", probe.version, SAVE_VERSION));
+    }
+    migrate(probe.version, text)
+}
+
+// Upgrade older save layouts to the current one. Version 1 is the first format.
+fn migrate(version: u32, text: &str) -> Result<SaveData, String> {
+    match version {
+        SAVE_VERSION => toml::from_str::<SaveData>(text).map_err(|e| e.message().to_string()),
+        v => Err(format!("unknown save version {}", v)),
+    }
+}
+
+// Load the save, falling back to a fresh one when it is missing or unreadable.
+// Unreadable saves are kept aside (save.toml.corrupt / a separate storage key)
+// instead of being overwritten by the next write.
+pub fn load_save() -> SaveData {
+    let Some(text) = storage::read() else { return SaveData::default(); };
+    match parse_save(&text) {
+        Ok(data) => data,
+        Err(e) => {
+            bevy::log::warn!("Ignoring unreadable save data ({}); starting fresh", e);
+            storage::backup_corrupt(&text);
+            SaveData::default()
+        }
+    }
+}
+
+// Export a finished (or in-progress) run as plain text and JSON for comparing runs
+pub fn export_splits(splits: &[Split]) {
+    let stamp = storage::export_stamp();
+    let text = splits_to_text(splits);
+    let json = splits_to_json(splits);
+    for (name, body) in [(format!("run-{}.txt", stamp), text), (format!("run-{}.json", stamp), json)] {
+        match storage::write_export("splits", &name, &body) {
+            Ok(dest) => bevy::log::info!("Exported splits to {:?}", dest),
+            Err(e) => bevy::log::error!("Failed to export splits: {}", e),
+        }
+    }
+}
+
+// Store a replay of the current attempt next to the save (or in localStorage)
+pub fn export_replay(level: &str, text: &str) {
+    let name = format!("{}-{}.toml", level.replace(['/', '\\'], "_"), storage::export_stamp());
+    match storage::write_export("replays", &name, text) {
+        Ok(dest) => bevy::log::info!("Saved replay to {:?}", dest),
+        Err(e) => bevy::log::error!("Failed to save replay: {}", e),
+    }
+}
+
+// Share a ghost as a standalone file that others can race with --ghost
+pub fn export_ghost(level: &str, text: &str) {
+    let name = format!("{}-{}.toml", level.replace(['/', '\\'], "_"), storage::export_stamp());
+    match storage::write_export("ghosts", &name, text) {
+        Ok(dest) => bevy::log::info!("Exported ghost to {:?}", dest),
+        Err(e) => bevy::log::error!("Failed to export ghost: {}", e),
+    }
+}
+
+pub fn splits_to_text(splits: &[Split]) -> String {
+    let mut out = String::from("# level split_s cumulative_s\n");
+    let mut cumulative = 0.0;
+    for s in splits {
+        cumulative += s.time_s;
+        out.push_str(&format!("{} {:.3} {:.3}\n", s.level, s.time_s, cumulative));
+    }
+    out.push_str(&format!("total {:.3}\n", cumulative));
+    out
+}
+
+#[derive(Serialize)]
+struct SplitsExport<'a> {
+    version: u32,
+    total_s: f32,
+    splits: Vec<SplitExport<'a>>,
+}
+
+#[derive(Serialize)]
+struct SplitExport<'a> {
+    level: &'a str,
+    time_s: f32,
+    cumulative_s: f32,
+}
+
+pub fn splits_to_json(splits: &[Split]) -> String {
+    let mut cumulative = 0.0;
+    let rows = splits
+        .iter()
+        .map(|s| {
+            cumulative += s.time_s;
+            SplitExport { level: &s.level, time_s: s.time_s, cumulative_s: cumulative }
+        })
+        .collect();
+    let export = SplitsExport { version: 1, total_s: run_total(splits), splits: rows };
+    serde_json::to_string_pretty(&export).unwrap_or_default()
+}
+
+pub fn write_save(data: &SaveData) {
+    match toml::to_string_pretty(data) {
+        Ok(text) => {
+            if let Err(e) = storage::write(&text) {
+                bevy::log::error!("Failed to write save data: {}", e);
+            }
+        }
+        Err(e) => bevy::log::error!("Failed to serialize save data: {}", e),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod storage {
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn save_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|d| d.join("rust-side-scroller").join("save.toml"))
+    }
+
+    pub fn read() -> Option<String> {
+        fs::read_to_string(save_path()?).ok()
+    }
+
+    pub fn write(text: &str) -> Result<(), String> {
+        let path = save_path().ok_or("no user data directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        // Write then rename so a crash mid-write can't truncate the previous save
+        let tmp = path.with_extension("toml.tmp");
+        fs::write(&tmp, text).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, &path).map_err(|e| e.to_string())
+    }
+
+    pub fn backup_corrupt(text: &str) {
+        if let Some(path) = save_path() {
+            let _ = fs::write(path.with_extension("toml.corrupt"), text);
+        }
+    }
+
+    pub fn export_stamp() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    // Write an export file into a folder next to the save; returns the written path
+    pub fn write_export(folder: &str, file_name: &str, text: &str) -> Result<PathBuf, String> {
+        let dir = save_path().and_then(|p| p.parent().map(|d| d.join(folder))).ok_or("no user data directory")?;
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join(file_name);
+        fs::write(&path, text).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod storage {
+    const KEY: &str = "rust-side-scroller.save";
+    const CORRUPT_KEY: &str = "rust-side-scroller.save.corrupt";
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn read() -> Option<String> {
+        local_storage()?.get_item(KEY).ok()?
+    }
+
+    pub fn write(text: &str) -> Result<(), String> {
+        let storage = local_storage().ok_or("localStorage unavailable")?;
+        storage.set_item(KEY, text).map_err(|e| format!("{:?}", e))
+    }
+
+    pub fn backup_corrupt(text: &str) {
+        if let Some(storage) = local_storage() {
+            let _ = storage.set_item(CORRUPT_KEY, text);
+        }
+    }
+
+    // Only used to name files on native; the web keeps one export per kind
+    pub fn export_stamp() -> u64 {
+        0
+    }
+
+    // No filesystem on the web: keep the latest export of each kind under its own key
+    pub fn write_export(folder: &str, file_name: &str, text: &str) -> Result<String, String> {
+        let ext = file_name.rsplit('.').next().unwrap_or("txt");
+        let key = format!("rust-side-scroller.{}.{}", folder, ext);
+        let storage = local_storage().ok_or("localStorage unavailable")?;
+        storage.set_item(&key, text).map_err(|e| format!("{:?}", e))?;
+        Ok(key)
+    }
+}
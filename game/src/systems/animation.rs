@@ -0,0 +1,68 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+
+use crate::animation::{feet_anchor, AnimationFileError, Animator, Motion, PlayerAnimations};
+use crate::components::{JumpState, Player, Velocity};
+use crate::config::GameConfig;
+use crate::systems::particles::{BurstKind, DirtKickEvent, JumpBurstEvent};
+
+// Reads *.anim.toml files as PlayerAnimations
+#[derive(Default)]
+pub struct PlayerAnimationsLoader;
+
+impl AssetLoader for PlayerAnimationsLoader {
+    type Asset = PlayerAnimations;
+    type Settings = ();
+    type Error = AnimationFileError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<PlayerAnimations, AnimationFileError> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text).await.map_err(|e| AnimationFileError { message: e.to_string() })?;
+        PlayerAnimations::from_toml_str(&text)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anim.toml"]
+    }
+}
+
+#[derive(Resource)]
+pub struct PlayerAnimationsHandle(pub Handle<PlayerAnimations>);
+
+pub fn load_player_animations(mut commands: Commands, cfg: Res<GameConfig>, asset_server: Res<AssetServer>) {
+    commands.insert_resource(PlayerAnimationsHandle(asset_server.load(cfg.player.animations.clone())));
+}
+
+// Pick the player's clip and frame from its motion and squash or stretch it.
+// Until the file has loaded (or when it failed to) the player keeps frame 0.
+pub fn animate_player(
+    time: Res<Time>,
+    handle: Res<PlayerAnimationsHandle>,
+    animations: Res<Assets<PlayerAnimations>>,
+    mut kicks: EventReader<DirtKickEvent>,
+    mut bursts: EventReader<JumpBurstEvent>,
+    mut q_motion: Query<(&Velocity, &JumpState, &mut Animator)>,
+    mut q_sprite: Query<(&mut Transform, &mut Sprite, Option<&mut TextureAtlas>), With<Player>>,
+) {
+    let skid = kicks.read().count() > 0;
+    // Every air jump bursts as Normal, bonus ones burst as Bonus too
+    let air_jump = bursts.read().any(|b| matches!(b.kind, BurstKind::Normal));
+    let Some(anims) = animations.get(&handle.0) else { return; };
+    let (Ok((vel, jump, mut animator)), Ok((mut transform, mut sprite, atlas))) = (q_motion.get_single_mut(), q_sprite.get_single_mut()) else {
+        return;
+    };
+    animator.update(anims, Motion { vel: vel.0, grounded: jump.grounded, skid, air_jump }, time.delta_seconds() * 1000.0);
+    if let Some(mut atlas) = atlas {
+        atlas.index = animator.frame(anims);
+    }
+    let scale = animator.scale(anims);
+    transform.scale = scale.extend(1.0);
+    sprite.anchor = Anchor::Custom(Vec2::new(0.0, feet_anchor(scale.y)));
+}
@@ -4,6 +4,9 @@ use bevy::render::render_asset::RenderAssetUsages;
 use bevy::render::render_resource::PrimitiveTopology;
 use bevy::sprite::MaterialMesh2dBundle;
 
+use crate::components::Decoration;
+use crate::config::GameConfig;
+
 // Marker for background entities
 #[derive(Component)]
 pub struct Background;
@@ -18,12 +21,13 @@ pub struct ParallaxLayer {
 
 pub fn setup_parallax_background(
     mut commands: Commands,
-    windows: Query<&Window>,
+    cfg: Res<GameConfig>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    let window = windows.single();
-    let width = window.width().max(1280.0);
+    // Sized from the virtual resolution, which is all the camera ever shows
+    let view = Vec2::new(cfg.display.width as f32, cfg.display.height as f32);
+    let width = view.x.max(1280.0);
 
     // Make layers very wide to avoid tiling for now
     let very_wide = width * 6.0;
@@ -31,7 +35,7 @@ pub fn setup_parallax_background(
     // Sky backdrop as a big rectangle behind everything
     commands.spawn((
         SpriteBundle {
-            sprite: Sprite { color: Color::srgb(0.65, 0.85, 1.0), custom_size: Some(Vec2::new(very_wide, window.height() * 3.0)), ..default() },
+            sprite: Sprite { color: Color::srgb(0.65, 0.85, 1.0), custom_size: Some(Vec2::new(very_wide, view.y * 3.0)), ..default() },
             transform: Transform::from_xyz(0.0, 0.0, -20.0),
             ..default()
         },
@@ -143,3 +147,18 @@ pub fn update_parallax_background(
         t.translation.y = layer.base_y; // keep vertical anchor stable
     }
 }
+
+// Decorations follow the camera by (1 - parallax) of its movement, so a layer
+// with parallax 1 stays put in the world and 0 stays put on screen
+pub fn update_decoration_parallax(
+    cam_q: Query<&Transform, (With<Camera>, Without<Decoration>)>,
+    mut q: Query<(&Decoration, &mut Transform)>,
+) {
+    let Ok(cam_t) = cam_q.get_single() else { return; };
+    let cam = cam_t.translation.truncate();
+    for (d, mut t) in q.iter_mut() {
+        let pos = d.base + cam * (1.0 - d.parallax);
+        t.translation.x = pos.x;
+        t.translation.y = pos.y;
+    }
+}
@@ -1,13 +1,72 @@
 use bevy::prelude::*;
+use bevy::render::camera::{ScalingMode, Viewport};
+use bevy::window::{PrimaryWindow, WindowMode};
 
-use crate::config::GameConfig;
+use crate::config::{GameConfig, Scaling};
 use crate::resources::GROUND_Y;
 use crate::components::{Player, Velocity};
 
+// The camera always shows the virtual resolution's worth of world; the black
+// clear color shows as bars around the viewport when the aspect differs
+pub fn spawn_camera(mut commands: Commands, cfg: Res<GameConfig>) {
+    let mut camera = Camera2dBundle::default();
+    camera.projection.scaling_mode = ScalingMode::Fixed {
+        width: cfg.display.width as f32,
+        height: cfg.display.height as f32,
+    };
+    camera.camera.clear_color = ClearColorConfig::Custom(Color::BLACK);
+    commands.spawn(camera);
+}
+
+// Keep the viewport the largest (whole, with integer scaling) multiple of the
+// virtual resolution that fits the window, centered, and scale the UI with it
+pub fn fit_viewport_to_window(
+    cfg: Res<GameConfig>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut q_camera: Query<&mut Camera>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let (Ok(window), Ok(mut camera)) = (windows.get_single(), q_camera.get_single_mut()) else { return; };
+    let physical = UVec2::new(window.physical_width(), window.physical_height());
+    // Minimized
+    if physical.x == 0 || physical.y == 0 { return; }
+
+    let virtual_size = Vec2::new(cfg.display.width as f32, cfg.display.height as f32);
+    let fit = (physical.as_vec2() / virtual_size).min_element();
+    let scale = match cfg.display.scaling {
+        // Smaller than the virtual resolution can only shrink smoothly
+        Scaling::Integer if fit >= 1.0 => fit.floor(),
+        _ => fit,
+    };
+    let size = (virtual_size * scale).round().as_uvec2().clamp(UVec2::ONE, physical);
+    let viewport = Viewport { physical_position: (physical - size) / 2, physical_size: size, ..default() };
+
+    let changed = camera.viewport.as_ref().is_none_or(|v| {
+        v.physical_position != viewport.physical_position || v.physical_size != viewport.physical_size
+    });
+    if changed {
+        camera.viewport = Some(viewport);
+    }
+    let ui = scale / window.scale_factor();
+    if ui_scale.0 != ui {
+        ui_scale.0 = ui;
+    }
+}
+
+// F11 switches between window and borderless fullscreen
+pub fn toggle_fullscreen(keyboard: Res<ButtonInput<KeyCode>>, mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    if !keyboard.just_pressed(KeyCode::F11) { return; }
+    if let Ok(mut window) = windows.get_single_mut() {
+        window.mode = match window.mode {
+            WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+            _ => WindowMode::Windowed,
+        };
+    }
+}
+
 pub fn camera_follow_system(
     time: Res<Time>,
     cfg: Res<GameConfig>,
-    windows: Query<&Window>,
     mut q_camera: Query<&mut Transform, (With<Camera>, Without<Player>)>,
     q_player: Query<(&Transform, &Velocity), With<Player>>,
 ) {
@@ -26,8 +85,8 @@ pub fn camera_follow_system(
         let amp_x = cfg.camera.noise_amp_x.unwrap_or(cfg.camera.noise_amp);
         let amp_y = cfg.camera.noise_amp_y.unwrap_or(cfg.camera.noise_amp);
 
-        let window_h = windows.get_single().map(|w| w.height()).unwrap_or(540.0);
-        let half_h = window_h * 0.5;
+        // Virtual height: the visible world doesn't depend on the window
+        let half_h = cfg.display.height as f32 * 0.5;
         let margin = 40.0;
         let max_cam_y = GROUND_Y + half_h - margin;
         let allowed_max = max_cam_y - amp_y;
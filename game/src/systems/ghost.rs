@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::components::{Ghost, Player};
+use crate::resources::{LevelManager, PLAYER_SIZE};
+use crate::save::{every_tick, export_ghost, SaveData};
+use crate::systems::levels::LevelLoadEvent;
+use crate::systems::replay::ReplayRecorder;
+
+pub const GHOST_VERSION: u32 = 1;
+// Ticks between the stored positions of a best run, 16 a second at 64 Hz
+pub const GHOST_STEP: u32 = 4;
+
+// Standalone ghost for racing someone else's run (--ghost <file>, exported with F7)
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GhostFile {
+    pub version: u32,
+    pub level: String,
+    #[serde(default)]
+    pub time_s: Option<f32>,
+    pub positions: Vec<[f32; 2]>,
+    // Ticks between positions; files from before ghosts were thinned out have
+    // one per tick
+    #[serde(default = "every_tick")]
+    pub step: u32,
+}
+
+impl GhostFile {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let ghost: GhostFile = toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if ghost.version != GHOST_VERSION {
+            return Err(format!("{}: unsupported ghost version {}", path.display(), ghost.version));
+        }
+        Ok(ghost)
+    }
+}
+
+// Every `step`-th of a run's per-tick positions, rounded to a tenth of a unit
+// to keep the save small. The last position is kept too, one step after the
+// one before it, so the ghost still ends where the run did.
+pub fn thin_out(positions: &[[f32; 2]], step: u32) -> Vec<[f32; 2]> {
+    let round = |[x, y]: [f32; 2]| [(x * 10.0).round() / 10.0, (y * 10.0).round() / 10.0];
+    let mut out: Vec<[f32; 2]> = positions.iter().step_by(step.max(1) as usize).copied().map(round).collect();
+    if let Some(last) = positions.last() {
+        if !(positions.len() - 1).is_multiple_of(step.max(1) as usize) {
+            out.push(round(*last));
+        }
+    }
+    out
+}
+
+// Where a ghost with a position every `step` ticks is `tick` ticks in: on the
+// line between the two positions around it, or the last one once it's done
+pub fn ghost_position(frames: &[Vec2], step: u32, tick: usize) -> Option<Vec2> {
+    let step = step.max(1) as usize;
+    let last = frames.len().checked_sub(1)?;
+    let i = tick / step;
+    if i >= last {
+        return Some(frames[last]);
+    }
+    Some(frames[i].lerp(frames[i + 1], (tick % step) as f32 / step as f32))
+}
+
+// Imported ghost; replaces the saved best-run ghost on its level
+#[derive(Resource)]
+pub struct ImportedGhost(pub GhostFile);
+
+// Ghost of the best run, drawn behind the player; shown once a level has one
+pub fn spawn_ghost(mut commands: Commands) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgba(1.0, 1.0, 1.0, 0.35),
+                custom_size: Some(PLAYER_SIZE),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 0.9),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        Ghost::default(),
+    ));
+}
+
+// Runs on every level load: pick the imported ghost for this level if there is
+// one, else the saved best run, and restart it from the first frame
+pub fn reset_ghost_on_load(
+    mut reader: EventReader<LevelLoadEvent>,
+    save: Res<SaveData>,
+    imported: Option<Res<ImportedGhost>>,
+    mut q_ghost: Query<(&mut Ghost, &mut Visibility)>,
+) {
+    for ev in reader.read() {
+        let LevelLoadEvent::Loaded { name } = ev else { continue; };
+        let Ok((mut ghost, mut vis)) = q_ghost.get_single_mut() else { return; };
+        let (frames, step) = match imported.as_ref().filter(|g| &g.0.level == name) {
+            Some(g) => (g.0.positions.clone(), g.0.step),
+            None => save.levels.get(name).map_or((Vec::new(), 1), |r| (r.ghost.clone(), r.ghost_step)),
+        };
+        ghost.frames = frames.into_iter().map(Vec2::from).collect();
+        ghost.step = step;
+        ghost.tick = 0;
+        *vis = if ghost.frames.is_empty() || ghost.hidden { Visibility::Hidden } else { Visibility::Visible };
+    }
+}
+
+// Fixed-tick step, in lockstep with the player, holding the final position
+// once the ghost has finished
+pub fn advance_ghost(mut q_ghost: Query<(&mut Ghost, &mut Transform), Without<Player>>) {
+    for (mut ghost, mut t) in q_ghost.iter_mut() {
+        let Some(pos) = ghost_position(&ghost.frames, ghost.step, ghost.tick) else { continue; };
+        t.translation.x = pos.x;
+        t.translation.y = pos.y;
+        ghost.tick += 1;
+    }
+}
+
+pub fn record_ghost_frame(mut recorder: ResMut<ReplayRecorder>, q_player: Query<&Transform, With<Player>>) {
+    if let Ok(t) = q_player.get_single() {
+        recorder.positions.push([t.translation.x, t.translation.y]);
+    }
+}
+
+// G toggles the ghost, F7 exports this level's saved best ghost for sharing
+pub fn ghost_hotkeys(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    level_mgr: Res<LevelManager>,
+    save: Res<SaveData>,
+    mut q_ghost: Query<(&mut Ghost, &mut Visibility)>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyG) {
+        for (mut ghost, mut vis) in q_ghost.iter_mut() {
+            ghost.hidden = !ghost.hidden;
+            *vis = if !ghost.hidden && !ghost.frames.is_empty() { Visibility::Visible } else { Visibility::Hidden };
+        }
+    }
+    if keyboard.just_pressed(KeyCode::F7) {
+        let Some(rec) = save.levels.get(&level_mgr.current).filter(|r| !r.ghost.is_empty()) else { return; };
+        let file = GhostFile {
+            version: GHOST_VERSION,
+            level: level_mgr.current.clone(),
+            time_s: rec.best_time_s,
+            positions: rec.ghost.clone(),
+            step: rec.ghost_step,
+        };
+        match toml::to_string(&file) {
+            Ok(text) => export_ghost(&level_mgr.current, &text),
+            Err(e) => bevy::log::error!("Failed to serialize ghost: {}", e),
+        }
+    }
+}
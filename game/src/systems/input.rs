@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+
+// Player actions for one fixed physics tick. Physics reads these instead of the
+// keyboard so a recorded sequence of frames replays identically.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InputFrame {
+    pub left: bool,
+    pub right: bool,
+    pub jump: bool,
+}
+
+impl InputFrame {
+    pub fn to_bits(self) -> u8 {
+        (self.left as u8) | ((self.right as u8) << 1) | ((self.jump as u8) << 2)
+    }
+
+    pub fn from_bits(bits: u8) -> Self {
+        Self { left: bits & 1 != 0, right: bits & 2 != 0, jump: bits & 4 != 0 }
+    }
+}
+
+// Actions for the current tick plus the previous one, for edge detection
+#[derive(Resource, Default)]
+pub struct PlayerInput {
+    pub current: InputFrame,
+    pub previous: InputFrame,
+}
+
+impl PlayerInput {
+    pub fn push(&mut self, frame: InputFrame) {
+        self.previous = self.current;
+        self.current = frame;
+    }
+
+    pub fn dir(&self) -> f32 {
+        self.current.right as i8 as f32 - self.current.left as i8 as f32
+    }
+
+    pub fn jump_held(&self) -> bool {
+        self.current.jump
+    }
+
+    pub fn jump_just_pressed(&self) -> bool {
+        self.current.jump && !self.previous.jump
+    }
+
+    pub fn jump_just_released(&self) -> bool {
+        !self.current.jump && self.previous.jump
+    }
+}
+
+// Jump taps noticed between fixed ticks, so a press and release inside one
+// render frame still reaches physics
+#[derive(Resource, Default)]
+pub struct KeyboardLatch {
+    jump_tapped: bool,
+}
+
+pub fn player_input_system(keyboard: Res<ButtonInput<KeyCode>>, mut latch: ResMut<KeyboardLatch>) {
+    if keyboard.just_pressed(KeyCode::Space) {
+        latch.jump_tapped = true;
+    }
+}
+
+// Read the live keyboard for one fixed tick, consuming any latched tap
+pub fn live_input_frame(keyboard: &ButtonInput<KeyCode>, latch: &mut KeyboardLatch) -> InputFrame {
+    let frame = InputFrame {
+        left: keyboard.any_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]),
+        right: keyboard.any_pressed([KeyCode::KeyD, KeyCode::ArrowRight]),
+        jump: keyboard.pressed(KeyCode::Space) || latch.jump_tapped,
+    };
+    latch.jump_tapped = false;
+    frame
+}
@@ -1,6 +1,11 @@
 use bevy::prelude::*;
-use serde::Deserialize;
+use bevy::render::mesh::Indices;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy::sprite::MaterialMesh2dBundle;
+#[cfg(not(target_arch = "wasm32"))]
 use std::fs;
+#[cfg(not(target_arch = "wasm32"))]
 use std::path::Path;
 
 #[cfg(target_arch = "wasm32")]
@@ -10,18 +15,25 @@ use gloo_net::http::Request;
 #[cfg(target_arch = "wasm32")]
 use crossbeam_channel::{unbounded, Receiver};
 
-use crate::components::{Exit, Ground, LevelEntity};
-use crate::resources::{LevelManager, LevelRequest, LevelStart, PendingStart};
+use level_format::Level;
 
-#[derive(Deserialize)]
-struct LevelMeta { name: String }
+use crate::components::{Decoration, Exit, Ground, LevelEntity, Slope, Textured, TileMap};
+use crate::resources::{GameState, LevelManager, LevelProfile, LevelRequest, LevelStart, PendingStart};
 
-fn spawn_loaded_level(commands: &mut Commands, pending: &mut ResMut<PendingStart>, def: LevelDef) {
+fn spawn_loaded_level(commands: &mut Commands, pending: &mut ResMut<PendingStart>, def: Level) {
     for p in def.platforms {
-        commands.spawn((
+        let texture = p.texture.as_deref().and_then(|name| {
+            let found = def.textures.iter().find(|t| t.name == name);
+            if found.is_none() {
+                warn!("Platform at ({}, {}) uses unknown texture '{}'", p.x, p.y, name);
+            }
+            found
+        });
+        let mut platform = commands.spawn((
             SpriteBundle {
                 sprite: Sprite {
-                    color: Color::srgb(0.20, 0.8, 0.25),
+                    // Textures are drawn untinted
+                    color: if texture.is_some() { Color::WHITE } else { Color::srgb(0.20, 0.8, 0.25) },
                     custom_size: Some(Vec2::new(p.w, p.h)),
                     ..default()
                 },
@@ -31,6 +43,40 @@ fn spawn_loaded_level(commands: &mut Commands, pending: &mut ResMut<PendingStart
             Ground,
             LevelEntity,
         ));
+        if let Some(t) = texture {
+            platform.insert(Textured(t.clone()));
+        }
+    }
+    for layer in def.tile_layers {
+        // Solid tiles collide as merged rectangles, which aren't drawn
+        for r in layer.solid_rects() {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite { custom_size: Some(Vec2::new(r.w, r.h)), ..default() },
+                    transform: Transform::from_xyz(r.x, r.y, 0.0),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                Ground,
+                LevelEntity,
+            ));
+        }
+        match def.tilesets.iter().find(|t| t.name == layer.tileset) {
+            Some(tileset) => {
+                let tileset = tileset.clone();
+                commands.spawn((SpatialBundle::default(), TileMap { layer, tileset }, LevelEntity));
+            }
+            None => warn!("Tile layer '{}' uses unknown tileset '{}'", layer.name, layer.tileset),
+        }
+    }
+    for s in def.slopes {
+        if s.points.len() < 2 { continue; }
+        // Mesh is attached by build_slope_meshes once the entity exists
+        commands.spawn((
+            SpatialBundle::default(),
+            Slope(s),
+            LevelEntity,
+        ));
     }
     for e in def.exits {
         commands.spawn((
@@ -47,49 +93,137 @@ fn spawn_loaded_level(commands: &mut Commands, pending: &mut ResMut<PendingStart
             LevelEntity,
         ));
     }
+    for (i, d) in def.decorations.into_iter().enumerate() {
+        // Decorations on a missing layer are drawn just behind the level
+        let (z, parallax) = def.layers.iter().find(|l| l.name == d.layer).map_or((-1.0, 1.0), |l| (l.z, l.parallax));
+        let [r, g, b, a] = level_format::parse_color(&d.color).unwrap_or([255; 4]);
+        let mut decoration = commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgba_u8(r, g, b, a),
+                    custom_size: Some(Vec2::new(d.w, d.h)),
+                    ..default()
+                },
+                // Later ones in the file on top within a layer
+                transform: Transform::from_xyz(d.x, d.y, z + i as f32 * 1e-4),
+                ..default()
+            },
+            Decoration { base: Vec2::new(d.x, d.y), parallax },
+            LevelEntity,
+        ));
+        if let Some(path) = d.sprite {
+            decoration.insert(Textured(level_format::Texture {
+                name: String::new(),
+                path,
+                mode: level_format::TextureMode::Stretch,
+                border: 0.0,
+                scale: 1.0,
+            }));
+        }
+    }
     let start = Vec2::new(def.start.x, def.start.y);
     pending.0 = Some(start);
     commands.insert_resource(LevelStart(start));
+    info!("Spawned level '{}'", def.meta.name);
+    commands.insert_resource(LevelProfile(def.meta.profile));
+}
+
+// Outcome of a level load, sent once the level is spawned or has failed.
+// On native this is immediate; on web it arrives when the fetch completes.
+#[derive(Event, Debug, Clone)]
+pub enum LevelLoadEvent {
+    Loaded { name: String },
+    Failed { name: String, error: LevelLoadError },
+}
+
+#[derive(Debug, Clone)]
+pub struct LevelLoadError {
+    // File path or URL the level was read from
+    pub source: String,
+    // 1-based position of a parse error, when known
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl LevelLoadError {
+    fn io(source: &str, message: String) -> Self {
+        Self { source: source.to_string(), line: None, column: None, message }
+    }
+
+    fn parse(source: &str, content: &str, err: &toml::de::Error) -> Self {
+        let (line, column) = crate::config::error_position(content, err);
+        Self { source: source.to_string(), line, column, message: err.message().to_string() }
+    }
+}
+
+impl std::fmt::Display for LevelLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.line, self.column) {
+            (Some(l), Some(c)) => write!(f, "{}:{}:{}: {}", self.source, l, c, self.message),
+            _ => write!(f, "{}: {}", self.source, self.message),
+        }
+    }
+}
+
+// Most recent load failure, shown on the error screen
+#[derive(Resource, Default)]
+pub struct LevelLoadFailure(pub Option<LevelLoadError>);
+
+// Accept either a bare name (resolved under levels/<name>.toml)
+// or a direct file path to a .toml level file.
+fn level_path(name: &str) -> String {
+    if name.ends_with(".toml") || name.contains('/') || name.contains('\\') {
+        name.to_string()
+    } else {
+        format!("levels/{}.toml", name)
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
-fn spawn_or_queue_load(commands: &mut Commands, pending: &mut ResMut<PendingStart>, level_name: &str) {
-    if let Some(def) = read_level(level_name) {
-        spawn_loaded_level(commands, pending, def);
+fn spawn_or_queue_load(
+    commands: &mut Commands,
+    pending: &mut ResMut<PendingStart>,
+    ev_load: &mut EventWriter<LevelLoadEvent>,
+    level_name: &str,
+) {
+    match read_level(level_name) {
+        Ok(def) => {
+            spawn_loaded_level(commands, pending, def);
+            ev_load.send(LevelLoadEvent::Loaded { name: level_name.to_string() });
+        }
+        Err(error) => {
+            ev_load.send(LevelLoadEvent::Failed { name: level_name.to_string(), error });
+        }
     }
 }
 
 #[cfg(target_arch = "wasm32")]
 #[derive(Resource, Default)]
-pub struct WasmLevelChan(Option<Receiver<LevelDef>>);
+pub struct WasmLevelChan(Option<Receiver<(String, Result<Level, LevelLoadError>)>>);
 
 #[cfg(target_arch = "wasm32")]
-fn spawn_or_queue_load(commands: &mut Commands, _pending: &mut ResMut<PendingStart>, level_name: &str) {
+fn spawn_or_queue_load(
+    commands: &mut Commands,
+    _pending: &mut ResMut<PendingStart>,
+    _ev_load: &mut EventWriter<LevelLoadEvent>,
+    level_name: &str,
+) {
     // Queue an async fetch task; actual spawning happens in poller system.
-    let url = if level_name.ends_with(".toml") || level_name.contains('/') || level_name.contains('\\') {
-        level_name.to_string()
-    } else {
-        format!("levels/{}.toml", level_name)
-    };
-    let (tx, rx) = unbounded::<LevelDef>();
+    let url = level_path(level_name);
+    let name = level_name.to_string();
+    let (tx, rx) = unbounded::<(String, Result<Level, LevelLoadError>)>();
     commands.insert_resource(WasmLevelChan(Some(rx)));
     spawn_local(async move {
-        match Request::get(&url).send().await {
-            Ok(resp) => {
-                if !resp.ok() {
-                    bevy::log::error!("HTTP error fetching level '{}': {}", url, resp.status());
-                    return;
-                }
-                match resp.text().await {
-                    Ok(text) => match toml::from_str::<LevelDef>(&text) {
-                        Ok(def) => { let _ = tx.send(def); }
-                        Err(e) => bevy::log::error!("Failed to parse level from '{}': {}", url, e),
-                    },
-                    Err(e) => bevy::log::error!("Failed to read body for '{}': {:?}", url, e),
-                }
-            }
-            Err(e) => bevy::log::error!("Fetch error for '{}': {:?}", url, e),
-        }
+        let result = match Request::get(&url).send().await {
+            Ok(resp) if !resp.ok() => Err(LevelLoadError::io(&url, format!("HTTP error {}", resp.status()))),
+            Ok(resp) => match resp.text().await {
+                Ok(text) => Level::from_toml_str(&text).map_err(|e| LevelLoadError::parse(&url, &text, &e)),
+                Err(e) => Err(LevelLoadError::io(&url, format!("failed to read body: {:?}", e))),
+            },
+            Err(e) => Err(LevelLoadError::io(&url, format!("fetch error: {:?}", e))),
+        };
+        let _ = tx.send((name, result));
     });
 }
 
@@ -98,93 +232,139 @@ pub fn poll_wasm_level_task(
     mut commands: Commands,
     mut pending: ResMut<PendingStart>,
     mut chan_res: ResMut<WasmLevelChan>,
+    mut ev_load: EventWriter<LevelLoadEvent>,
 ) {
     if let Some(rx) = chan_res.0.as_ref() {
-        if let Ok(def) = rx.try_recv() {
-            // got a level, spawn it and clear channel
-            spawn_loaded_level(&mut commands, &mut pending, def);
+        if let Ok((name, result)) = rx.try_recv() {
+            // got a result, spawn the level or report the failure and clear channel
+            match result {
+                Ok(def) => {
+                    spawn_loaded_level(&mut commands, &mut pending, def);
+                    ev_load.send(LevelLoadEvent::Loaded { name });
+                }
+                Err(error) => {
+                    ev_load.send(LevelLoadEvent::Failed { name, error });
+                }
+            }
             chan_res.0 = None;
         }
     }
 }
 
-#[derive(Deserialize)]
-struct StartDef { x: f32, y: f32 }
+// Build a filled mesh for each newly spawned slope: a quad strip from the surface
+// polyline down to the surface shifted by the slope depth.
+pub fn build_slope_meshes(
+    mut commands: Commands,
+    q_slopes: Query<(Entity, &Slope), Added<Slope>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (entity, slope) in q_slopes.iter() {
+        let n = slope.points.len();
+        let mut positions: Vec<[f32; 3]> = Vec::with_capacity(n * 2);
+        let mut uvs: Vec<[f32; 2]> = Vec::with_capacity(n * 2);
+        for (i, p) in slope.points.iter().enumerate() {
+            let u = i as f32 / (n - 1) as f32;
+            positions.push([p.x, p.y, 0.0]);
+            positions.push([p.x, p.y - slope.depth, 0.0]);
+            uvs.push([u, 1.0]);
+            uvs.push([u, 0.0]);
+        }
 
-#[derive(Deserialize)]
-struct PlatformDef { x: f32, y: f32, w: f32, h: f32 }
+        let mut indices: Vec<u32> = Vec::with_capacity((n - 1) * 6);
+        for i in 0..(n - 1) as u32 {
+            let top_left = i * 2;
+            let bottom_left = top_left + 1;
+            let top_right = top_left + 2;
+            let bottom_right = top_left + 3;
+            indices.extend_from_slice(&[top_left, bottom_left, top_right, bottom_left, bottom_right, top_right]);
+        }
 
-#[derive(Deserialize)]
-struct ExitDef { x: f32, y: f32, w: f32, h: f32, next: String }
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.insert_indices(Indices::U32(indices));
 
-#[derive(Deserialize)]
-struct LevelDef {
-    meta: LevelMeta,
-    start: StartDef,
-    #[serde(default)]
-    platforms: Vec<PlatformDef>,
-    #[serde(default)]
-    exits: Vec<ExitDef>,
+        commands.entity(entity).insert(MaterialMesh2dBundle {
+            mesh: meshes.add(mesh).into(),
+            material: materials.add(ColorMaterial::from(Color::srgb(0.20, 0.8, 0.25))),
+            ..default()
+        });
+    }
 }
 
-pub fn do_load_level(commands: &mut Commands, pending: &mut ResMut<PendingStart>, level_name: &str) {
-    spawn_or_queue_load(commands, pending, level_name);
+pub fn do_load_level(
+    commands: &mut Commands,
+    pending: &mut ResMut<PendingStart>,
+    ev_load: &mut EventWriter<LevelLoadEvent>,
+    level_name: &str,
+) {
+    spawn_or_queue_load(commands, pending, ev_load, level_name);
 }
 
-fn read_level(name: &str) -> Option<LevelDef> {
-    // Accept either a bare name (resolved under levels/<name>.toml)
-    // or a direct file path to a .toml level file.
-    let content = if name.ends_with(".toml") || name.contains('/') || name.contains('\\') {
-        fs::read_to_string(name).ok()?
-    } else {
-        let path = format!("levels/{}.toml", name);
-        fs::read_to_string(Path::new(&path)).ok()?
-    };
-    match toml::from_str::<LevelDef>(&content) {
-        Ok(def) => Some(def),
-        Err(e) => {
-            bevy::log::error!("Failed to parse level '{}': {}", name, e);
-            None
-        }
-    }
+#[cfg(not(target_arch = "wasm32"))]
+fn read_level(name: &str) -> Result<Level, LevelLoadError> {
+    let path = level_path(name);
+    let content = fs::read_to_string(Path::new(&path))
+        .map_err(|e| LevelLoadError::io(&path, e.to_string()))?;
+    Level::from_toml_str(&content).map_err(|e| LevelLoadError::parse(&path, &content, &e))
 }
 
 pub fn exit_detection_system(
     mut level_req: ResMut<LevelRequest>,
+    mut next_state: ResMut<NextState<GameState>>,
     q_player: Query<&Transform, With<crate::components::Player>>,
     q_exits: Query<(&Transform, &Exit)>,
 ) {
     if level_req.0.is_some() { return; }
     if let Ok(pt) = q_player.get_single() {
-        let p_half = crate::resources::PLAYER_SIZE / 2.0;
-        let px = pt.translation.x;
-        let py = pt.translation.y;
         for (et, exit) in q_exits.iter() {
-            let gx = et.translation.x;
-            let gy = et.translation.y;
-            let half = exit.size / 2.0;
-            let dx = (px - gx).abs();
-            let dy = (py - gy).abs();
-            if dx < (p_half.x + half.x) && dy < (p_half.y + half.y) {
+            if crate::physics::touches_exit(pt.translation.truncate(), et.translation.truncate(), exit.size) {
                 level_req.0 = Some(exit.next.clone());
+                next_state.set(GameState::LevelComplete);
                 break;
             }
         }
     }
 }
 
-pub fn level_transition_system(
+// Runs on entering Loading: replace the current level with the requested one,
+// or reload the current level when nothing was requested (restart from title).
+pub fn load_requested_level(
     mut commands: Commands,
     mut req: ResMut<LevelRequest>,
     mut pending: ResMut<PendingStart>,
     mut level_mgr: ResMut<LevelManager>,
+    mut ev_load: EventWriter<LevelLoadEvent>,
     q_level_entities: Query<Entity, With<LevelEntity>>,
 ) {
-    if let Some(next) = req.0.take() {
-        for e in q_level_entities.iter() {
-            commands.entity(e).despawn_recursive();
+    let next = req.0.take().unwrap_or_else(|| level_mgr.current.clone());
+    for e in q_level_entities.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+    level_mgr.current = next.clone();
+    do_load_level(&mut commands, &mut pending, &mut ev_load, &next);
+}
+
+// Stay in Loading until the level reports back, then play it or show the error screen
+pub fn level_load_result_system(
+    mut reader: EventReader<LevelLoadEvent>,
+    mut level_mgr: ResMut<LevelManager>,
+    mut failure: ResMut<LevelLoadFailure>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for ev in reader.read() {
+        match ev {
+            LevelLoadEvent::Loaded { name } => {
+                level_mgr.last_loaded = Some(name.clone());
+                failure.0 = None;
+                next_state.set(GameState::Playing);
+            }
+            LevelLoadEvent::Failed { name, error } => {
+                bevy::log::error!("Failed to load level '{}': {}", name, error);
+                failure.0 = Some(error.clone());
+                next_state.set(GameState::LoadError);
+            }
         }
-        level_mgr.current = next.clone();
-        do_load_level(&mut commands, &mut pending, &next);
     }
 }
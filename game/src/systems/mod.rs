@@ -5,3 +5,13 @@ pub mod ui;
 pub mod startup;
 pub mod particles;
 pub mod background;
+pub mod state;
+pub mod progress;
+pub mod speedrun;
+pub mod input;
+pub mod replay;
+pub mod ghost;
+pub mod tuning;
+pub mod sprites;
+pub mod tilemap;
+pub mod animation;
@@ -54,12 +54,12 @@ pub enum BurstKind { Normal, Bonus }
 
 // Components for transient VFX
 #[derive(Component)]
-pub(crate) struct ShatterPlatform {
+pub struct ShatterPlatform {
     life: f32,
 }
 
 #[derive(Component)]
-pub(crate) struct Particle {
+pub struct Particle {
     vel: Vec2,
     life: f32,
     max_life: f32,
@@ -162,7 +162,7 @@ pub fn spawn_burst_on_event(
 
 pub fn update_particles(
     time: Res<Time>,
-    cfg: Res<crate::config::GameConfig>,
+    mv: Res<crate::config::Movement>,
     mut q_pf: Query<(Entity, &mut ShatterPlatform)>,
     mut q_p: Query<(Entity, &mut Transform, &mut Particle, &mut Sprite)>,
     mut commands: Commands,
@@ -179,7 +179,7 @@ pub fn update_particles(
 
     // update particles: integrate velocity + gravity, fade out, despawn
     for (e, mut t, mut p, mut sprite) in q_p.iter_mut() {
-        p.vel.y -= cfg.gravity.value * dt * 0.8; // slightly less than player gravity for feel
+        p.vel.y -= mv.gravity * dt * 0.8; // slightly less than player gravity for feel
         t.translation.x += p.vel.x * dt;
         t.translation.y += p.vel.y * dt;
         p.life -= dt;
@@ -0,0 +1,53 @@
+use bevy::prelude::*;
+
+use crate::resources::{LevelManager, LevelRequest, LevelTimer, Lives};
+use crate::save::{write_save, SaveData};
+use crate::systems::ghost::{thin_out, GHOST_STEP};
+use crate::systems::levels::LevelLoadEvent;
+use crate::systems::replay::ReplayRecorder;
+
+pub fn tick_level_timer(time: Res<Time>, mut timer: ResMut<LevelTimer>) {
+    timer.elapsed += time.delta_seconds();
+}
+
+// Checkpoint progress whenever a level loads successfully
+pub fn save_on_level_loaded(
+    mut reader: EventReader<LevelLoadEvent>,
+    mut save: ResMut<SaveData>,
+    mut timer: ResMut<LevelTimer>,
+    lives: Res<Lives>,
+) {
+    for ev in reader.read() {
+        if let LevelLoadEvent::Loaded { name } = ev {
+            timer.elapsed = 0.0;
+            save.current_level = Some(name.clone());
+            save.lives = lives.current;
+            save.unlock(name);
+            write_save(&save);
+        }
+    }
+}
+
+// Runs on entering LevelComplete: store the run and unlock the next level so
+// Continue resumes there even if the player quits from the overlay.
+pub fn save_on_level_complete(
+    level_mgr: Res<LevelManager>,
+    level_req: Res<LevelRequest>,
+    timer: Res<LevelTimer>,
+    lives: Res<Lives>,
+    recorder: Res<ReplayRecorder>,
+    mut save: ResMut<SaveData>,
+) {
+    if save.record_completion(&level_mgr.current, timer.elapsed) {
+        if let Some(rec) = save.levels.get_mut(&level_mgr.current) {
+            rec.ghost = thin_out(&recorder.positions, GHOST_STEP);
+            rec.ghost_step = GHOST_STEP;
+        }
+    }
+    if let Some(next) = &level_req.0 {
+        save.unlock(next);
+        save.current_level = Some(next.clone());
+    }
+    save.lives = lives.current;
+    write_save(&save);
+}
@@ -0,0 +1,269 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::components::Player;
+use crate::config::{GameConfig, Movement};
+use crate::resources::GameRng;
+use crate::save::export_replay;
+use crate::systems::input::{live_input_frame, InputFrame, KeyboardLatch, PlayerInput};
+use crate::systems::levels::LevelLoadEvent;
+
+pub const REPLAY_VERSION: u32 = 1;
+// Final positions within this distance count as a match
+const POSITION_TOLERANCE: f32 = 0.01;
+
+// One level attempt: everything needed to re-run the fixed-tick simulation.
+// Inputs are run-length encoded as [InputFrame bits, tick count] pairs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub version: u32,
+    pub level: String,
+    pub seed: u64,
+    // GameConfig::hash as hex; playback warns when the current config differs
+    pub config_hash: String,
+    pub tick_hz: f64,
+    // Player position after the last tick, asserted on playback
+    #[serde(default)]
+    pub final_position: Option<[f32; 2]>,
+    // Movement profile in use when the attempt was saved; playback selects it.
+    // Switching profiles mid-attempt (F8) is not recorded.
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub inputs: Vec<[u32; 2]>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let replay: Replay = toml::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        if replay.version != REPLAY_VERSION {
+            return Err(format!("{}: unsupported replay version {}", path.display(), replay.version));
+        }
+        Ok(replay)
+    }
+
+    pub fn frames(&self) -> Vec<InputFrame> {
+        self.inputs
+            .iter()
+            .flat_map(|[bits, n]| std::iter::repeat_n(InputFrame::from_bits(*bits as u8), *n as usize))
+            .collect()
+    }
+
+    fn encode(frames: &[InputFrame]) -> Vec<[u32; 2]> {
+        let mut runs: Vec<[u32; 2]> = Vec::new();
+        for f in frames {
+            let bits = f.to_bits() as u32;
+            match runs.last_mut() {
+                Some(run) if run[0] == bits => run[1] += 1,
+                _ => runs.push([bits, 1]),
+            }
+        }
+        runs
+    }
+}
+
+pub fn config_hash_hex(cfg: &GameConfig) -> String {
+    format!("{:016x}", cfg.hash())
+}
+
+// Seed for a normal (non-replay) session. Kept below 2^63 so it fits a TOML integer.
+pub fn fresh_seed() -> u64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    let entropy = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    #[cfg(target_arch = "wasm32")]
+    let entropy = web_sys::window()
+        .and_then(|w| w.performance())
+        .map(|p| (p.now() * 1000.0) as u64)
+        .unwrap_or(0);
+    (entropy ^ 0x9e37_79b9_7f4a_7c15) & (i64::MAX as u64)
+}
+
+// Inputs of the current level attempt, restarted on every level load
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    pub level: String,
+    pub frames: Vec<InputFrame>,
+    // Player position after each tick, kept as a ghost if the attempt is a best time
+    pub positions: Vec<[f32; 2]>,
+    // Set by --record: the attempt is written here when the level ends
+    pub out_path: Option<PathBuf>,
+}
+
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    frames: Vec<InputFrame>,
+    cursor: usize,
+    done: bool,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        let frames = replay.frames();
+        Self { replay, frames, cursor: 0, done: false }
+    }
+}
+
+pub fn begin_attempt_on_load(
+    mut reader: EventReader<LevelLoadEvent>,
+    mut rng: ResMut<GameRng>,
+    mut input: ResMut<PlayerInput>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    for ev in reader.read() {
+        if let LevelLoadEvent::Loaded { name } = ev {
+            rng.reset();
+            *input = PlayerInput::default();
+            recorder.level = name.clone();
+            recorder.frames.clear();
+            recorder.positions.clear();
+        }
+    }
+}
+
+// First step of every fixed tick: take the next frame from the replay being
+// played, or from the keyboard, and record it.
+pub fn advance_input_tick(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut latch: ResMut<KeyboardLatch>,
+    mut input: ResMut<PlayerInput>,
+    mut recorder: ResMut<ReplayRecorder>,
+    playback: Option<ResMut<ReplayPlayback>>,
+    q_player: Query<&Transform, With<Player>>,
+    mut ev_exit: EventWriter<AppExit>,
+) {
+    let frame = match playback {
+        Some(mut pb) => {
+            if pb.done { return; }
+            match pb.frames.get(pb.cursor).copied() {
+                Some(f) => {
+                    pb.cursor += 1;
+                    f
+                }
+                None => {
+                    finish_playback(&mut pb, q_player.get_single().ok(), &mut ev_exit);
+                    return;
+                }
+            }
+        }
+        None => live_input_frame(&keyboard, &mut latch),
+    };
+    input.push(frame);
+    recorder.frames.push(frame);
+}
+
+// Reaching the exit or game over also ends playback, since no more ticks run
+pub fn finish_replay_on_stop(
+    playback: Option<ResMut<ReplayPlayback>>,
+    q_player: Query<&Transform, With<Player>>,
+    mut ev_exit: EventWriter<AppExit>,
+) {
+    if let Some(mut pb) = playback {
+        if !pb.done {
+            finish_playback(&mut pb, q_player.get_single().ok(), &mut ev_exit);
+        }
+    }
+}
+
+fn finish_playback(pb: &mut ReplayPlayback, player: Option<&Transform>, ev_exit: &mut EventWriter<AppExit>) {
+    pb.done = true;
+    let pos = player.map(|t| t.translation.truncate()).unwrap_or(Vec2::NAN);
+    let exit = match pb.replay.final_position {
+        Some([x, y]) if pos.distance(Vec2::new(x, y)) <= POSITION_TOLERANCE => {
+            bevy::log::info!("Replay finished at {:?}, matching the recording", pos);
+            AppExit::Success
+        }
+        Some([x, y]) => {
+            bevy::log::error!("Replay diverged: finished at {:?}, recording ended at ({}, {})", pos, x, y);
+            AppExit::error()
+        }
+        None => {
+            bevy::log::info!("Replay finished at {:?}", pos);
+            AppExit::Success
+        }
+    };
+    ev_exit.send(exit);
+}
+
+fn recorded_replay(
+    recorder: &ReplayRecorder,
+    rng: &GameRng,
+    cfg: &GameConfig,
+    movement: &Movement,
+    fixed: &Time<Fixed>,
+    player: Option<&Transform>,
+) -> Replay {
+    Replay {
+        version: REPLAY_VERSION,
+        level: recorder.level.clone(),
+        seed: rng.seed,
+        config_hash: config_hash_hex(cfg),
+        tick_hz: 1.0 / fixed.timestep().as_secs_f64(),
+        final_position: player.map(|t| [t.translation.x, t.translation.y]),
+        profile: Some(movement.profile.clone()).filter(|p| !p.is_empty()),
+        inputs: Replay::encode(&recorder.frames),
+    }
+}
+
+// Runs when a level ends (exit or game over) and writes the attempt for --record
+pub fn write_recording_on_stop(
+    recorder: Res<ReplayRecorder>,
+    rng: Res<GameRng>,
+    cfg: Res<GameConfig>,
+    movement: Res<Movement>,
+    fixed: Res<Time<Fixed>>,
+    q_player: Query<&Transform, With<Player>>,
+) {
+    let Some(path) = &recorder.out_path else { return; };
+    write_recording(path, &recorded_replay(&recorder, &rng, &cfg, &movement, &fixed, q_player.get_single().ok()));
+}
+
+// Headless runs also stop when the inputs run out; recording then captures the
+// final position, which turns a hand-written input script into a checked replay
+pub fn write_recording_on_exit(
+    mut reader: EventReader<AppExit>,
+    recorder: Res<ReplayRecorder>,
+    rng: Res<GameRng>,
+    cfg: Res<GameConfig>,
+    movement: Res<Movement>,
+    fixed: Res<Time<Fixed>>,
+    q_player: Query<&Transform, With<Player>>,
+) {
+    if reader.read().last().is_none() { return; }
+    let Some(path) = &recorder.out_path else { return; };
+    write_recording(path, &recorded_replay(&recorder, &rng, &cfg, &movement, &fixed, q_player.get_single().ok()));
+}
+
+fn write_recording(path: &Path, replay: &Replay) {
+    match toml::to_string(replay) {
+        Ok(text) => match std::fs::write(path, text) {
+            Ok(()) => bevy::log::info!("Recorded replay to {}", path.display()),
+            Err(e) => bevy::log::error!("Failed to write replay {}: {}", path.display(), e),
+        },
+        Err(e) => bevy::log::error!("Failed to serialize replay: {}", e),
+    }
+}
+
+// F9 saves the attempt so far, e.g. right after hitting a bug
+pub fn save_replay_hotkey(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    recorder: Res<ReplayRecorder>,
+    rng: Res<GameRng>,
+    cfg: Res<GameConfig>,
+    movement: Res<Movement>,
+    fixed: Res<Time<Fixed>>,
+    q_player: Query<&Transform, With<Player>>,
+) {
+    if !keyboard.just_pressed(KeyCode::F9) || recorder.frames.is_empty() { return; }
+    let replay = recorded_replay(&recorder, &rng, &cfg, &movement, &fixed, q_player.get_single().ok());
+    match toml::to_string(&replay) {
+        Ok(text) => export_replay(&recorder.level, &text),
+        Err(e) => bevy::log::error!("Failed to serialize replay: {}", e),
+    }
+}
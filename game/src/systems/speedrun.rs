@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+
+use crate::components::{MenuAction, SplitsUi};
+use crate::resources::{LevelManager, LevelRequest, LevelTimer};
+use crate::save::{export_splits, run_total, write_save, SaveData, Split};
+use crate::systems::levels::LevelLoadEvent;
+use crate::systems::ui::MenuActionEvent;
+
+// Splits of the run in progress. A run starts with a new game (or Continue) and
+// finishes when an exit leads back to a level already completed in the run,
+// i.e. after one full loop through the world.
+#[derive(Resource, Default)]
+pub struct SpeedrunTimer {
+    pub splits: Vec<Split>,
+    pub finished: bool,
+}
+
+pub fn reset_run_on_new_game(mut reader: EventReader<MenuActionEvent>, mut run: ResMut<SpeedrunTimer>) {
+    for MenuActionEvent(action) in reader.read() {
+        if matches!(action, MenuAction::Start | MenuAction::Restart | MenuAction::LoadSave) {
+            *run = SpeedrunTimer::default();
+        }
+    }
+}
+
+// Playing on after a finished run starts the next one from the level just loaded
+pub fn reset_run_after_finish(mut reader: EventReader<LevelLoadEvent>, mut run: ResMut<SpeedrunTimer>) {
+    for ev in reader.read() {
+        if matches!(ev, LevelLoadEvent::Loaded { .. }) && run.finished {
+            *run = SpeedrunTimer::default();
+        }
+    }
+}
+
+// Runs on entering LevelComplete, after the level timer stopped at exit contact
+pub fn record_split(
+    level_mgr: Res<LevelManager>,
+    level_req: Res<LevelRequest>,
+    timer: Res<LevelTimer>,
+    mut run: ResMut<SpeedrunTimer>,
+    mut save: ResMut<SaveData>,
+) {
+    if run.finished { return; }
+    run.splits.push(Split { level: level_mgr.current.clone(), time_s: timer.elapsed });
+    let loops_back = level_req.0.as_ref().is_some_and(|next| run.splits.iter().any(|s| &s.level == next));
+    if !loops_back { return; }
+
+    run.finished = true;
+    let same_route = save.best_run.len() == run.splits.len()
+        && save.best_run.iter().zip(&run.splits).all(|(a, b)| a.level == b.level);
+    if save.best_run.is_empty() || (same_route && run_total(&run.splits) < run_total(&save.best_run)) {
+        save.best_run = run.splits.clone();
+        write_save(&save);
+    }
+    export_splits(&run.splits);
+}
+
+// F6 exports the splits recorded so far without waiting for the run to finish
+pub fn export_splits_hotkey(keyboard: Res<ButtonInput<KeyCode>>, run: Res<SpeedrunTimer>) {
+    if keyboard.just_pressed(KeyCode::F6) && !run.splits.is_empty() {
+        export_splits(&run.splits);
+    }
+}
+
+pub fn toggle_splits_ui(keyboard: Res<ButtonInput<KeyCode>>, mut q_ui: Query<&mut Visibility, With<SplitsUi>>) {
+    if keyboard.just_pressed(KeyCode::Tab) {
+        for mut vis in q_ui.iter_mut() {
+            *vis = match *vis {
+                Visibility::Hidden => Visibility::Inherited,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+// One line per completed level with cumulative time and the difference to the
+// personal best at the same point, then the level in progress.
+pub fn update_splits_ui(
+    run: Res<SpeedrunTimer>,
+    timer: Res<LevelTimer>,
+    level_mgr: Res<LevelManager>,
+    save: Res<SaveData>,
+    mut q_text: Query<&mut Text, With<SplitsUi>>,
+) {
+    let Ok(mut text) = q_text.get_single_mut() else { return; };
+    let mut lines = Vec::with_capacity(run.splits.len() + 2);
+    let mut cumulative = 0.0;
+    let mut pb_cumulative = 0.0;
+    for (i, split) in run.splits.iter().enumerate() {
+        cumulative += split.time_s;
+        let line = match save.best_run.get(i).filter(|pb| pb.level == split.level) {
+            Some(pb) => {
+                pb_cumulative += pb.time_s;
+                format!("{:<10} {:>7.2} {:>+6.2}", split.level, cumulative, cumulative - pb_cumulative)
+            }
+            None => format!("{:<10} {:>7.2}", split.level, cumulative),
+        };
+        lines.push(line);
+    }
+    if !run.finished {
+        lines.push(format!("> {:<8} {:>7.2}", level_mgr.current, cumulative + timer.elapsed));
+    }
+    if !save.best_run.is_empty() {
+        lines.push(format!("{:<10} {:>7.2}", "PB", run_total(&save.best_run)));
+    }
+    text.sections[0].value = lines.join("\n");
+}
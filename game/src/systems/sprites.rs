@@ -0,0 +1,117 @@
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::sprite::{BorderRect, ImageScaleMode, SliceScaleMode, TextureSlicer};
+use level_format::{Texture, TextureMode};
+
+use crate::animation::Animator;
+use crate::components::{Player, Textured, Velocity};
+use crate::config::GameConfig;
+
+// Magenta and black checkerboard drawn instead of an image that failed to load
+#[derive(Resource)]
+pub struct PlaceholderTexture(pub Handle<Image>);
+
+// Size of one checker square in pixels
+const CHECKER: u32 = 4;
+
+pub fn setup_placeholder_texture(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let size = CHECKER * 2;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let on = (x / CHECKER + y / CHECKER).is_multiple_of(2);
+            data.extend_from_slice(if on { &[255, 0, 255, 255] } else { &[0, 0, 0, 255] });
+        }
+    }
+    let image = Image::new(
+        Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    commands.insert_resource(PlaceholderTexture(images.add(image)));
+}
+
+// Put the configured sprite sheet and an animator on the player once it has
+// been spawned
+pub fn setup_player_sprite(
+    mut commands: Commands,
+    cfg: Res<GameConfig>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    q: Query<Entity, Added<Player>>,
+) {
+    for entity in q.iter() {
+        let p = &cfg.player;
+        let layout = TextureAtlasLayout::from_grid(UVec2::new(p.frame_width, p.frame_height), p.columns, p.rows, None, None);
+        let sheet = Texture { name: "player".to_string(), path: p.sheet.clone(), mode: TextureMode::Stretch, border: 0.0, scale: 1.0 };
+        commands.entity(entity).insert((Textured(sheet), TextureAtlas { layout: layouts.add(layout), index: 0 }, Animator::default()));
+    }
+}
+
+// Start loading the image of each newly textured sprite and set how it covers
+// the sprite's size
+pub fn load_textures(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut q: Query<(Entity, &Textured, &mut Handle<Image>), Added<Textured>>,
+) {
+    for (entity, Textured(texture), mut image) in q.iter_mut() {
+        *image = asset_server.load(texture.path.clone());
+        let scale = texture.scale.max(0.01);
+        match texture.mode {
+            TextureMode::Stretch => {}
+            TextureMode::Tile => {
+                commands.entity(entity).insert(ImageScaleMode::Tiled { tile_x: true, tile_y: true, stretch_value: scale });
+            }
+            TextureMode::Slice => {
+                commands.entity(entity).insert(ImageScaleMode::Sliced(TextureSlicer {
+                    border: BorderRect::square(texture.border),
+                    center_scale_mode: SliceScaleMode::Tile { stretch_value: scale },
+                    sides_scale_mode: SliceScaleMode::Tile { stretch_value: scale },
+                    max_corner_scale: scale,
+                }));
+            }
+        }
+    }
+}
+
+// Once a texture has loaded it needs no more watching; one that failed is
+// replaced by the tiled placeholder so the sprite stays visible
+pub fn replace_missing_textures(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    placeholder: Res<PlaceholderTexture>,
+    mut q: Query<(Entity, &Textured, &mut Handle<Image>, &mut Sprite)>,
+) {
+    for (entity, Textured(texture), mut image, mut sprite) in q.iter_mut() {
+        match asset_server.get_load_state(&*image) {
+            Some(LoadState::Loaded) => {
+                commands.entity(entity).remove::<Textured>();
+            }
+            Some(LoadState::Failed(_)) => {
+                warn!("Missing texture '{}', drawing a placeholder instead", texture.path);
+                *image = placeholder.0.clone();
+                sprite.color = Color::WHITE;
+                commands
+                    .entity(entity)
+                    .remove::<(Textured, TextureAtlas)>()
+                    .insert(ImageScaleMode::Tiled { tile_x: true, tile_y: true, stretch_value: 1.0 });
+            }
+            _ => {}
+        }
+    }
+}
+
+// The sheet's frames face right; mirror them while moving left
+pub fn face_player_sprite(mut q: Query<(&Velocity, &mut Sprite), With<Player>>) {
+    for (vel, mut sprite) in q.iter_mut() {
+        if vel.x < -1.0 {
+            sprite.flip_x = true;
+        } else if vel.x > 1.0 {
+            sprite.flip_x = false;
+        }
+    }
+}
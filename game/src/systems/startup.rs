@@ -1,23 +1,10 @@
 use bevy::prelude::*;
 
 use crate::components::{JumpState, Player, Velocity};
-use crate::resources::{LevelManager, PendingStart, PLAYER_SIZE};
-use crate::systems::levels::do_load_level;
-use crate::systems::ui::setup_ui;
+use crate::resources::PLAYER_SIZE;
 
-pub fn setup(
-    mut commands: Commands,
-    level_mgr: Res<LevelManager>,
-    mut pending: ResMut<PendingStart>,
-    asset_server: Res<AssetServer>,
-) {
-    // Camera
-    commands.spawn(Camera2dBundle::default());
-
-    // UI
-    setup_ui(commands.reborrow(), asset_server);
-
-    // Player
+// The only entity the simulation needs besides the level; also spawned in headless runs
+pub fn spawn_player(mut commands: Commands) {
     commands.spawn((
         SpriteBundle {
             sprite: Sprite {
@@ -32,7 +19,4 @@ pub fn setup(
         Velocity::default(),
         JumpState::default(),
     ));
-
-    // Load initial level
-    do_load_level(&mut commands, &mut pending, &level_mgr.current);
 }
@@ -0,0 +1,89 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+use crate::components::MenuAction;
+use crate::resources::{GameState, LevelManager, LevelRequest, Lives};
+use crate::save::SaveData;
+use crate::systems::replay::ReplayPlayback;
+use crate::systems::ui::MenuActionEvent;
+
+// Nothing to wait for yet; go straight to the title screen, or into the level
+// when playing back a replay
+pub fn finish_boot(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut level_req: ResMut<LevelRequest>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    match playback {
+        Some(pb) => {
+            level_req.0 = Some(pb.replay.level.clone());
+            next_state.set(GameState::Loading);
+        }
+        None => next_state.set(GameState::Title),
+    }
+}
+
+// Run condition for the fixed-tick simulation: playing, and no transition
+// (exit, game over, pause) queued by an earlier tick of this frame
+pub fn simulation_running(state: Res<State<GameState>>, next_state: Res<NextState<GameState>>) -> bool {
+    *state.get() == GameState::Playing && matches!(*next_state, NextState::Unchanged)
+}
+
+pub fn pause_time(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+pub fn resume_time(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+pub fn apply_menu_actions(
+    mut reader: EventReader<MenuActionEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut lives: ResMut<Lives>,
+    mut level_req: ResMut<LevelRequest>,
+    level_mgr: Res<LevelManager>,
+    save: Res<SaveData>,
+    mut ev_exit: EventWriter<AppExit>,
+) {
+    for MenuActionEvent(action) in reader.read() {
+        match action {
+            MenuAction::Start => {
+                lives.current = lives.max;
+                level_req.0 = Some(level_mgr.first.clone());
+                next_state.set(GameState::Loading);
+            }
+            MenuAction::LoadSave => {
+                // A save taken right before a game over has no lives left
+                lives.current = if save.lives == 0 { lives.max } else { save.lives.min(lives.max) };
+                level_req.0 = save.current_level.clone();
+                next_state.set(GameState::Loading);
+            }
+            MenuAction::Pause => next_state.set(GameState::Paused),
+            MenuAction::Resume => next_state.set(GameState::Playing),
+            // LevelRequest was filled in by exit detection
+            MenuAction::Continue => next_state.set(GameState::Loading),
+            MenuAction::Restart => {
+                lives.current = lives.max;
+                level_req.0 = Some(level_mgr.first.clone());
+                next_state.set(GameState::Loading);
+            }
+            // Reload whatever failed (LevelManager::current)
+            MenuAction::Retry => next_state.set(GameState::Loading),
+            MenuAction::PreviousLevel => {
+                level_req.0 = level_mgr.last_loaded.clone();
+                next_state.set(GameState::Loading);
+            }
+            MenuAction::QuitToTitle => {
+                level_req.0 = None;
+                next_state.set(GameState::Title);
+            }
+            MenuAction::Quit => { ev_exit.send(AppExit::Success); }
+        }
+    }
+}
+
+// Headless runs have nobody to press Retry; a level that fails to load fails the run
+pub fn exit_on_load_error(mut ev_exit: EventWriter<AppExit>) {
+    ev_exit.send(AppExit::error());
+}
@@ -0,0 +1,84 @@
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::PrimitiveTopology;
+use bevy::sprite::MaterialMesh2dBundle;
+
+use crate::components::TileMap;
+use crate::systems::sprites::PlaceholderTexture;
+
+// Tileset image of a tile layer that is still loading
+#[derive(Component)]
+pub struct TilesetImage(Handle<Image>);
+
+// Build one mesh for each newly spawned tile layer, a quad per painted cell
+// showing its part of the tileset image, so a layer is a single draw
+pub fn build_tile_meshes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    q: Query<(Entity, &TileMap), Added<TileMap>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (entity, TileMap { layer, tileset }) in q.iter() {
+        let mut positions: Vec<[f32; 3]> = Vec::new();
+        let mut uvs: Vec<[f32; 2]> = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+        let (cols, rows) = (tileset.columns.max(1) as f32, tileset.rows.max(1) as f32);
+        for row in 0..layer.rows {
+            for col in 0..layer.columns {
+                // Empty cells and tiles the tileset doesn't have are left out
+                let Some((tc, tr)) = tileset.tile_cell(layer.get(col, row)) else { continue; };
+                let r = layer.cells_rect(col, row, 1, 1);
+                let (left, right, top, bottom) = (r.x - r.w * 0.5, r.x + r.w * 0.5, r.y + r.h * 0.5, r.y - r.h * 0.5);
+                let (u0, u1) = (tc as f32 / cols, (tc + 1) as f32 / cols);
+                let (v0, v1) = (tr as f32 / rows, (tr + 1) as f32 / rows);
+                let i = positions.len() as u32;
+                positions.extend_from_slice(&[[left, top, 0.0], [left, bottom, 0.0], [right, top, 0.0], [right, bottom, 0.0]]);
+                uvs.extend_from_slice(&[[u0, v0], [u0, v1], [u1, v0], [u1, v1]]);
+                indices.extend_from_slice(&[i, i + 1, i + 2, i + 1, i + 3, i + 2]);
+            }
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default());
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.insert_indices(Indices::U32(indices));
+
+        let image: Handle<Image> = asset_server.load(tileset.path.clone());
+        commands.entity(entity).insert((
+            MaterialMesh2dBundle {
+                mesh: meshes.add(mesh).into(),
+                material: materials.add(ColorMaterial { color: Color::WHITE, texture: Some(image.clone()) }),
+                ..default()
+            },
+            TilesetImage(image),
+        ));
+    }
+}
+
+// Like replace_missing_textures, for the tileset image of a tile layer mesh
+pub fn replace_missing_tilesets(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    placeholder: Res<PlaceholderTexture>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    q: Query<(Entity, &TilesetImage, &Handle<ColorMaterial>, &TileMap)>,
+) {
+    for (entity, TilesetImage(image), material, map) in q.iter() {
+        match asset_server.get_load_state(image) {
+            Some(LoadState::Loaded) => {
+                commands.entity(entity).remove::<TilesetImage>();
+            }
+            Some(LoadState::Failed(_)) => {
+                warn!("Missing tileset image '{}', drawing a placeholder instead", map.tileset.path);
+                if let Some(m) = materials.get_mut(material) {
+                    m.texture = Some(placeholder.0.clone());
+                }
+                commands.entity(entity).remove::<TilesetImage>();
+            }
+            _ => {}
+        }
+    }
+}
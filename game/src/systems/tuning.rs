@@ -0,0 +1,49 @@
+use bevy::prelude::*;
+
+use crate::config::{GameConfig, Movement};
+use crate::resources::{LevelProfile, ProfileChoice};
+
+// Recompute the movement values when the config, the level or the runtime pick
+// changes. Runtime pick beats the level's profile, which beats config `profile`;
+// names that don't exist are skipped with a warning.
+pub fn update_movement(
+    cfg: Res<GameConfig>,
+    level: Res<LevelProfile>,
+    choice: Res<ProfileChoice>,
+    mut movement: ResMut<Movement>,
+) {
+    if !(cfg.is_changed() || level.is_changed() || choice.is_changed()) { return; }
+    let candidates = [choice.0.as_deref(), level.0.as_deref(), Some(cfg.profile.as_str())];
+    let mut name = None;
+    for candidate in candidates.into_iter().flatten() {
+        if cfg.has_profile(candidate) {
+            name = Some(candidate);
+            break;
+        }
+        bevy::log::warn!("Unknown movement profile '{}'", candidate);
+    }
+    let next = cfg.movement(name.unwrap_or_default());
+    if *movement != next {
+        if movement.profile != next.profile {
+            bevy::log::info!("Movement profile: {}", name.unwrap_or("(base values)"));
+        }
+        *movement = next;
+    }
+}
+
+// F8 cycles through the configured profiles, overriding the level's choice
+pub fn cycle_profile_hotkey(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    cfg: Res<GameConfig>,
+    movement: Res<Movement>,
+    mut choice: ResMut<ProfileChoice>,
+) {
+    if !keyboard.just_pressed(KeyCode::F8) || cfg.profiles.is_empty() { return; }
+    let names: Vec<&String> = cfg.profiles.keys().collect();
+    let next = names
+        .iter()
+        .position(|n| **n == movement.profile)
+        .map(|i| names[(i + 1) % names.len()])
+        .unwrap_or(names[0]);
+    choice.0 = Some(next.clone());
+}
@@ -1,7 +1,9 @@
 use bevy::prelude::*;
 
-use crate::components::{GameOverUi, HeartSlot, LivesUi};
-use crate::resources::{GameState, LevelRequest, LevelStart, Lives, PendingStart};
+use crate::components::{GameOverUi, HeartSlot, LevelCompleteUi, LivesUi, LoadErrorUi, MenuAction, PauseUi, SplitsUi, TitleUi};
+use crate::resources::{GameState, LevelManager, LevelTimer, Lives};
+use crate::save::SaveData;
+use crate::systems::levels::LevelLoadFailure;
 
 pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
     // Lives hearts container
@@ -35,8 +37,45 @@ pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
         });
     }
 
-    // Game Over overlay (visible even if font missing)
-    let overlay = commands
+    // Speedrun splits (Tab toggles); text is filled in by update_splits_ui
+    let font: Handle<Font> = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section("", TextStyle { font, font_size: 16.0, color: Color::WHITE }),
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                right: Val::Px(10.0),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.35)),
+            ..default()
+        },
+        SplitsUi,
+    ));
+}
+
+// Menu event raised by buttons and keyboard shortcuts; applied by systems::state
+#[derive(Event, Debug, Clone, Copy)]
+pub struct MenuActionEvent(pub MenuAction);
+
+const BUTTON_COLOR: Color = Color::srgb(0.25, 0.25, 0.3);
+const BUTTON_HOVER_COLOR: Color = Color::srgb(0.35, 0.35, 0.45);
+
+// Full-screen dimmed overlay with a centered panel, a heading and a column of
+// buttons. The overlay is scoped to `state` so it is despawned on exit.
+fn spawn_overlay(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    state: GameState,
+    marker: impl Component,
+    panel_color: Color,
+    heading: &str,
+    buttons: &[(&str, MenuAction)],
+) {
+    // Attempt to load a font from assets; if not present, the panel colors still indicate the screen
+    let font: Handle<Font> = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands
         .spawn((
             NodeBundle {
                 style: Style {
@@ -50,38 +89,185 @@ pub fn setup_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                     ..default()
                 },
                 background_color: BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.55)),
-                visibility: Visibility::Hidden,
                 ..default()
             },
-            GameOverUi,
+            marker,
+            StateScoped(state),
         ))
-        .id();
-
-    // Centered panel
-    commands.entity(overlay).with_children(|parent| {
-        parent.spawn((
-            NodeBundle {
-                style: Style {
-                    padding: UiRect::all(Val::Px(20.0)),
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(20.0)),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(panel_color),
                     ..default()
-                },
-                background_color: BackgroundColor(Color::srgb(0.2, 0.0, 0.0)),
-                ..default()
-            },
-        ))
-        .with_children(|panel| {
-            // Attempt to load a font from assets; if not present, the overlay still indicates Game Over
-            let font: Handle<Font> = asset_server.load("fonts/FiraSans-Bold.ttf");
-            panel.spawn(TextBundle {
-                text: Text::from_section(
-                    "GAME OVER\nPress SPACE to restart",
-                    TextStyle { font, font_size: 42.0, color: Color::WHITE },
-                )
-                .with_justify(JustifyText::Center),
-                ..default()
-            });
+                })
+                .with_children(|panel| {
+                    panel.spawn(TextBundle {
+                        text: Text::from_section(
+                            heading,
+                            TextStyle { font: font.clone(), font_size: 42.0, color: Color::WHITE },
+                        )
+                        .with_justify(JustifyText::Center),
+                        ..default()
+                    });
+                    for (label, action) in buttons {
+                        panel
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(220.0),
+                                        height: Val::Px(40.0),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    background_color: BackgroundColor(BUTTON_COLOR),
+                                    ..default()
+                                },
+                                *action,
+                            ))
+                            .with_children(|b| {
+                                b.spawn(TextBundle::from_section(
+                                    *label,
+                                    TextStyle { font: font.clone(), font_size: 24.0, color: Color::WHITE },
+                                ));
+                            });
+                    }
+                });
         });
-    });
+}
+
+pub fn spawn_title_ui(mut commands: Commands, asset_server: Res<AssetServer>, save: Res<SaveData>) {
+    let mut buttons = Vec::new();
+    if save.has_progress() {
+        buttons.push(("Continue", MenuAction::LoadSave));
+    }
+    buttons.push(("New game", MenuAction::Start));
+    buttons.push(("Quit", MenuAction::Quit));
+    spawn_overlay(
+        &mut commands,
+        &asset_server,
+        GameState::Title,
+        TitleUi,
+        Color::srgb(0.1, 0.15, 0.3),
+        "RUST SIDE SCROLLER\nPress ENTER to start",
+        &buttons,
+    );
+}
+
+pub fn spawn_pause_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_overlay(
+        &mut commands,
+        &asset_server,
+        GameState::Paused,
+        PauseUi,
+        Color::srgb(0.15, 0.15, 0.2),
+        "PAUSED\nPress ESC to resume",
+        &[("Resume", MenuAction::Resume), ("Quit to title", MenuAction::QuitToTitle)],
+    );
+}
+
+pub fn spawn_level_complete_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    timer: Res<LevelTimer>,
+    level_mgr: Res<LevelManager>,
+    save: Res<SaveData>,
+) {
+    let best = save.levels.get(&level_mgr.current).and_then(|r| r.best_time_s);
+    let time_line = match best {
+        Some(b) if b >= timer.elapsed => format!("Time {:.2}s  NEW BEST", timer.elapsed),
+        Some(b) => format!("Time {:.2}s  (best {:.2}s)", timer.elapsed, b),
+        None => format!("Time {:.2}s", timer.elapsed),
+    };
+    spawn_overlay(
+        &mut commands,
+        &asset_server,
+        GameState::LevelComplete,
+        LevelCompleteUi,
+        Color::srgb(0.0, 0.25, 0.1),
+        &format!("LEVEL COMPLETE\n{}\nPress SPACE to continue", time_line),
+        &[("Continue", MenuAction::Continue)],
+    );
+}
+
+pub fn spawn_game_over_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    spawn_overlay(
+        &mut commands,
+        &asset_server,
+        GameState::GameOver,
+        GameOverUi,
+        Color::srgb(0.2, 0.0, 0.0),
+        "GAME OVER\nPress SPACE to restart",
+        &[("Restart", MenuAction::Restart), ("Quit to title", MenuAction::QuitToTitle)],
+    );
+}
+
+pub fn spawn_load_error_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    failure: Res<LevelLoadFailure>,
+    level_mgr: Res<LevelManager>,
+) {
+    let detail = failure.0.as_ref().map(|e| e.to_string()).unwrap_or_default();
+    let heading = format!("FAILED TO LOAD '{}'\n{}\nPress R to retry", level_mgr.current, detail);
+    let mut buttons = vec![("Retry", MenuAction::Retry)];
+    if level_mgr.last_loaded.is_some() {
+        buttons.push(("Previous level", MenuAction::PreviousLevel));
+    }
+    buttons.push(("Quit to title", MenuAction::QuitToTitle));
+    spawn_overlay(
+        &mut commands,
+        &asset_server,
+        GameState::LoadError,
+        LoadErrorUi,
+        Color::srgb(0.3, 0.15, 0.0),
+        &heading,
+        &buttons,
+    );
+}
+
+pub fn menu_button_system(
+    mut q_buttons: Query<(&Interaction, &MenuAction, &mut BackgroundColor), Changed<Interaction>>,
+    mut ev_menu: EventWriter<MenuActionEvent>,
+) {
+    for (interaction, action, mut bg) in q_buttons.iter_mut() {
+        match interaction {
+            Interaction::Pressed => { ev_menu.send(MenuActionEvent(*action)); }
+            Interaction::Hovered => bg.0 = BUTTON_HOVER_COLOR,
+            Interaction::None => bg.0 = BUTTON_COLOR,
+        }
+    }
+}
+
+// Keyboard shortcuts for the menus, mirroring the on-screen buttons
+pub fn menu_keyboard_system(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    state: Res<State<GameState>>,
+    save: Res<SaveData>,
+    mut ev_menu: EventWriter<MenuActionEvent>,
+) {
+    let confirm = keyboard.any_just_pressed([KeyCode::Enter, KeyCode::Space]);
+    let pause = keyboard.any_just_pressed([KeyCode::Escape, KeyCode::KeyP]);
+    let action = match state.get() {
+        GameState::Title if confirm && save.has_progress() => Some(MenuAction::LoadSave),
+        GameState::Title if confirm => Some(MenuAction::Start),
+        GameState::Playing if pause => Some(MenuAction::Pause),
+        GameState::Paused if pause => Some(MenuAction::Resume),
+        GameState::LevelComplete if confirm => Some(MenuAction::Continue),
+        GameState::GameOver if keyboard.just_pressed(KeyCode::Space) => Some(MenuAction::Restart),
+        GameState::LoadError if keyboard.just_pressed(KeyCode::KeyR) => Some(MenuAction::Retry),
+        _ => None,
+    };
+    if let Some(action) = action {
+        ev_menu.send(MenuActionEvent(action));
+    }
 }
 
 pub fn update_lives_ui_system(
@@ -97,29 +283,3 @@ pub fn update_lives_ui_system(
         }
     }
 }
-
-pub fn game_over_restart_system(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut lives: ResMut<Lives>,
-    mut state: ResMut<GameState>,
-    mut q_over: Query<&mut Visibility, With<GameOverUi>>,
-    mut pending: ResMut<PendingStart>,
-    level_start: Option<Res<LevelStart>>,
-    mut level_req: ResMut<LevelRequest>,
-    mut commands: Commands,
-    q_level_entities: Query<Entity, With<crate::components::LevelEntity>>,
-) {
-    if *state != GameState::GameOver { return; }
-    if keyboard.just_pressed(KeyCode::Space) {
-        lives.current = lives.max;
-        *state = GameState::Running;
-        if let Ok(mut vis) = q_over.get_single_mut() { *vis = Visibility::Hidden; }
-        // Proactively clear existing level entities to ensure a visible reset
-        for e in q_level_entities.iter() {
-            commands.entity(e).despawn_recursive();
-        }
-        level_req.0 = Some("level1".to_string());
-        let start = level_start.as_ref().map(|s| s.0).unwrap_or(Vec2::ZERO);
-        pending.0 = Some(start);
-    }
-}
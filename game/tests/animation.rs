@@ -0,0 +1,173 @@
+use game::animation::{feet_anchor, AnimState, Animator, Clip, FrameMs, Motion, PlayerAnimations};
+use game::config::GameConfig;
+use game::physics::{Sim, Vec2, TICK_HZ};
+use game::systems::input::InputFrame;
+use level_format::{Level, Meta, Rect, Start};
+
+const TICK_MS: f32 = 1000.0 / TICK_HZ as f32;
+
+fn shipped() -> PlayerAnimations {
+    let text = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/sprites/player.anim.toml")).unwrap();
+    PlayerAnimations::from_toml_str(&text).unwrap()
+}
+
+fn on_ground(vx: f32) -> Motion {
+    Motion { vel: Vec2::new(vx, 0.0), grounded: true, ..Default::default() }
+}
+
+fn in_air(vx: f32, vy: f32) -> Motion {
+    Motion { vel: Vec2::new(vx, vy), grounded: false, ..Default::default() }
+}
+
+#[test]
+fn the_shipped_animations_fit_the_shipped_sheet() {
+    let anims = shipped();
+    let sheet = GameConfig::default().player;
+    let frames = (sheet.columns * sheet.rows) as usize;
+    for (name, clip) in anims.clips() {
+        assert!(clip.frames.iter().all(|f| *f < frames), "{} uses a frame outside the sheet", name);
+    }
+}
+
+#[test]
+fn clips_play_their_frame_times_and_repeat_or_hold() {
+    let mut clip = Clip { frames: vec![3, 4, 5], frame_ms: FrameMs::Each(vec![100.0, 50.0, 50.0]), repeat: true };
+    assert_eq!(clip.duration_ms(), 200.0);
+    let at = |clip: &Clip, ms: f32| clip.frame_at(ms);
+    assert_eq!([at(&clip, 0.0), at(&clip, 99.0), at(&clip, 120.0), at(&clip, 160.0), at(&clip, 210.0)], [3, 3, 4, 5, 3]);
+    assert!(!clip.finished(500.0));
+    clip.repeat = false;
+    assert_eq!(at(&clip, 500.0), 5);
+    assert!(clip.finished(200.0));
+}
+
+#[test]
+fn bad_animation_files_are_errors() {
+    let text = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/sprites/player.anim.toml")).unwrap();
+    let empty = text.replacen("frames = [2, 3, 4, 5]", "frames = []", 1);
+    let e = PlayerAnimations::from_toml_str(&empty).unwrap_err();
+    assert!(e.to_string().contains("run"), "{}", e);
+    let short = text.replacen("frame_ms = [700, 400]", "frame_ms = [700]", 1);
+    assert!(PlayerAnimations::from_toml_str(&short).is_err());
+}
+
+#[test]
+fn jumping_stretches_and_landing_squashes() {
+    let anims = shipped();
+    let mut a = Animator::default();
+    // Settle after the first touch down
+    for _ in 0..20 {
+        a.update(&anims, on_ground(0.0), TICK_MS);
+    }
+    assert_eq!(a.state, AnimState::Idle);
+    a.update(&anims, on_ground(200.0), TICK_MS);
+    assert_eq!(a.state, AnimState::Run);
+
+    a.update(&anims, in_air(200.0, 600.0), TICK_MS);
+    assert_eq!(a.state, AnimState::Rise);
+    let scale = a.scale(&anims);
+    assert!(scale.y > 1.0 && scale.x < 1.0, "{:?}", scale);
+
+    a.update(&anims, in_air(200.0, -800.0), TICK_MS);
+    assert_eq!(a.state, AnimState::Fall);
+    a.update(&anims, on_ground(200.0), TICK_MS);
+    assert_eq!(a.state, AnimState::Land);
+    let scale = a.scale(&anims);
+    assert!(scale.y < 1.0 && scale.x > 1.0, "{:?}", scale);
+
+    // The squash eases out and running takes over once the landing has played
+    for _ in 0..20 {
+        a.update(&anims, on_ground(200.0), TICK_MS);
+    }
+    assert_eq!(a.state, AnimState::Run);
+    assert_eq!(a.scale(&anims), game::physics::Vec2::ONE);
+}
+
+#[test]
+fn turning_around_skids_until_the_clip_ends() {
+    let anims = shipped();
+    let mut a = Animator::default();
+    a.update(&anims, on_ground(250.0), TICK_MS);
+    a.update(&anims, Motion { skid: true, ..on_ground(200.0) }, TICK_MS);
+    assert_eq!(a.state, AnimState::Skid);
+    a.update(&anims, on_ground(0.0), TICK_MS);
+    assert_eq!(a.state, AnimState::Skid);
+    assert_eq!(a.frame(&anims), anims.skid.frames[0]);
+    for _ in 0..(anims.skid.duration_ms() / TICK_MS) as usize {
+        a.update(&anims, on_ground(-200.0), TICK_MS);
+    }
+    assert_eq!(a.state, AnimState::Run);
+}
+
+#[test]
+fn a_double_jump_in_the_simulation_flips() {
+    let level = Level {
+        meta: Meta { name: "flip".into(), profile: None },
+        start: Start { x: 0.0, y: 16.0 },
+        platforms: vec![Rect::new(0.0, -10.0, 4000.0, 20.0)],
+        ..Default::default()
+    };
+    let cfg = GameConfig::default();
+    let mut sim = Sim::new(level, cfg.movement(&cfg.profile), 1, Vec2::new(0.0, 16.0));
+    let anims = shipped();
+    let mut a = Animator::default();
+    let mut states = Vec::new();
+    for tick in 0..200 {
+        // Jump from the ground, and again near the top of that jump
+        let jump = (10..15).contains(&tick) || (35..40).contains(&tick);
+        sim.tick(InputFrame { left: false, right: true, jump }, TICK_MS / 1000.0);
+        let motion = Motion { vel: sim.body.vel, grounded: sim.body.jump.grounded, skid: false, air_jump: sim.events.air_jump };
+        a.update(&anims, motion, TICK_MS);
+        if states.last() != Some(&a.state) {
+            states.push(a.state);
+        }
+    }
+    let flip = states.iter().position(|s| *s == AnimState::Flip).expect("no flip");
+    let rise = states.iter().position(|s| *s == AnimState::Rise).expect("no rise");
+    let land = states.iter().rposition(|s| *s == AnimState::Land).expect("no landing");
+    assert!(rise < flip && flip < land, "{:?}", states);
+    assert_eq!(states.last(), Some(&AnimState::Run), "{:?}", states);
+}
+
+#[test]
+fn the_first_jump_after_walking_off_a_ledge_does_not_flip() {
+    // A ledge ending at x = 100 and nothing after it
+    let level = Level {
+        meta: Meta { name: "ledge".into(), profile: None },
+        start: Start { x: 60.0, y: 16.0 },
+        platforms: vec![Rect::new(50.0, -10.0, 100.0, 20.0)],
+        ..Default::default()
+    };
+    let cfg = GameConfig::default();
+    let mut sim = Sim::new(level, cfg.movement(&cfg.profile), 1, Vec2::new(60.0, 16.0));
+    let anims = shipped();
+    let mut a = Animator::default();
+    let mut states = Vec::new();
+    let mut jumped = None;
+    for tick in 0..120 {
+        // Jump once, a few ticks after leaving the ledge
+        let off = !sim.body.jump.grounded && sim.body.pos.x > 120.0;
+        let jump = off && jumped.is_none_or(|t| tick - t < 5);
+        if jump && jumped.is_none() {
+            jumped = Some(tick);
+        }
+        sim.tick(InputFrame { left: false, right: true, jump }, TICK_MS / 1000.0);
+        let motion = Motion { vel: sim.body.vel, grounded: sim.body.jump.grounded, skid: false, air_jump: sim.events.air_jump };
+        a.update(&anims, motion, TICK_MS);
+        if states.last() != Some(&a.state) {
+            states.push(a.state);
+        }
+    }
+    assert!(jumped.is_some(), "never left the ledge");
+    assert_eq!(sim.body.jump.jumps_used, 1);
+    assert!(!states.contains(&AnimState::Flip), "{:?}", states);
+    assert!(states.ends_with(&[AnimState::Fall, AnimState::Rise, AnimState::Fall]), "{:?}", states);
+}
+
+#[test]
+fn the_feet_stay_put_when_squashed() {
+    for scale in [0.7, 1.0, 1.25] {
+        let bottom = (-0.5 - feet_anchor(scale)) * scale;
+        assert!((bottom + 0.5).abs() < 1e-6, "scale {}: bottom at {}", scale, bottom);
+    }
+}
@@ -0,0 +1,191 @@
+// Test harness: a windowless App with the simulation plugin, stepped one fixed
+// tick per update, with keyboard input injected as InputFrames.
+#![allow(dead_code)]
+
+use bevy::input::InputPlugin;
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use game::components::{JumpState, Player, Velocity};
+use game::config::{GameConfig, Movement};
+use game::physics::TICK_HZ;
+use game::resources::{GameRng, GameState, LevelManager, LevelRequest, Lives};
+use game::systems::input::InputFrame;
+use game::PhysicsPlugin;
+use level_format::{Exit, Level, Meta, Rect, Start};
+
+pub const DT: f32 = 1.0 / TICK_HZ as f32;
+const TEST_SEED: u64 = 1;
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+pub struct TestApp {
+    pub app: App,
+    // Levels are written here so they load through the normal file path
+    dir: PathBuf,
+}
+
+impl TestApp {
+    pub fn new() -> Self {
+        Self::with_config(GameConfig::default())
+    }
+
+    pub fn with_config(cfg: GameConfig) -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "game-tests-{}-{}",
+            std::process::id(),
+            NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir).expect("create level dir");
+
+        let mut app = App::new();
+        app
+            .add_plugins((MinimalPlugins, StatesPlugin, InputPlugin))
+            .insert_resource(cfg)
+            .insert_resource(LevelManager { first: String::new(), current: String::new(), last_loaded: None })
+            .insert_resource(GameRng::new(TEST_SEED))
+            .insert_resource(Time::<Fixed>::from_hz(TICK_HZ))
+            // Every update advances time by exactly one fixed tick
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / TICK_HZ)))
+            .add_plugins(PhysicsPlugin);
+        // Startup, then Boot moves on to Title
+        app.update();
+        app.update();
+        Self { app, dir }
+    }
+
+    // Write the level next to the others of this test and return its path, usable
+    // as a level name (and as an exit's `next`)
+    pub fn write_level(&self, level: &Level) -> String {
+        let path = self.dir.join(format!("{}.toml", level.meta.name));
+        std::fs::write(&path, level.to_toml_string_pretty().expect("serialize level")).expect("write level");
+        path.to_string_lossy().into_owned()
+    }
+
+    // Load a level the way the game does (Loading state) and step until playing
+    pub fn load_level(&mut self, level: &Level) -> String {
+        let path = self.write_level(level);
+        self.request_level(&path);
+        assert!(self.step_until(10, |t| t.state() == GameState::Playing), "level did not load");
+        path
+    }
+
+    pub fn request_level(&mut self, name: &str) {
+        self.app.world_mut().resource_mut::<LevelRequest>().0 = Some(name.to_string());
+        self.app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::Loading);
+    }
+
+    // Hold exactly the keys of `frame` from now on
+    pub fn set_input(&mut self, frame: InputFrame) {
+        let mut keys = self.app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.release_all();
+        for (held, key) in [(frame.left, KeyCode::KeyA), (frame.right, KeyCode::KeyD), (frame.jump, KeyCode::Space)] {
+            if held {
+                keys.press(key);
+            }
+        }
+    }
+
+    // Step until the player stands on something
+    pub fn land(&mut self) {
+        assert!(self.step_until(240, |t| t.grounded()), "player never landed");
+    }
+
+    pub fn step(&mut self, ticks: usize) {
+        for _ in 0..ticks {
+            self.app.update();
+        }
+    }
+
+    // Step until `done` holds, at most `max_ticks`; returns whether it did
+    pub fn step_until(&mut self, max_ticks: usize, mut done: impl FnMut(&mut TestApp) -> bool) -> bool {
+        for _ in 0..max_ticks {
+            self.app.update();
+            if done(self) {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn player_pos(&mut self) -> Vec2 {
+        let world = self.app.world_mut();
+        world.query_filtered::<&Transform, With<Player>>().single(world).translation.truncate()
+    }
+
+    pub fn player_velocity(&mut self) -> Vec2 {
+        let world = self.app.world_mut();
+        world.query_filtered::<&Velocity, With<Player>>().single(world).0
+    }
+
+    pub fn jump_state(&mut self) -> JumpState {
+        let world = self.app.world_mut();
+        world.query_filtered::<&JumpState, With<Player>>().single(world).clone()
+    }
+
+    pub fn grounded(&mut self) -> bool {
+        self.jump_state().grounded
+    }
+
+    pub fn lives(&self) -> Lives {
+        *self.app.world().resource::<Lives>()
+    }
+
+    pub fn set_lives(&mut self, current: u8) {
+        self.app.world_mut().resource_mut::<Lives>().current = current;
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.app.world().resource::<State<GameState>>().get()
+    }
+
+    pub fn level_manager(&self) -> &LevelManager {
+        self.app.world().resource::<LevelManager>()
+    }
+
+    pub fn level_request(&self) -> Option<String> {
+        self.app.world().resource::<LevelRequest>().0.clone()
+    }
+
+    pub fn config(&self) -> &GameConfig {
+        self.app.world().resource::<GameConfig>()
+    }
+
+    // Movement values in effect (config with the active profile applied)
+    pub fn movement(&self) -> &Movement {
+        self.app.world().resource::<Movement>()
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+pub const IDLE: InputFrame = InputFrame { left: false, right: false, jump: false };
+pub const RIGHT: InputFrame = InputFrame { left: false, right: true, jump: false };
+pub const JUMP: InputFrame = InputFrame { left: false, right: false, jump: true };
+
+// Top of the floor in `floor_level`
+pub const FLOOR_TOP: f32 = 0.0;
+
+// A wide floor with its top at y = 0 and the start just above it
+pub fn floor_level(name: &str) -> Level {
+    Level {
+        meta: Meta { name: name.to_string(), profile: None },
+        start: Start { x: 0.0, y: 40.0 },
+        platforms: vec![Rect::new(0.0, FLOOR_TOP - 20.0, 2000.0, 40.0)],
+        exits: vec![],
+        slopes: vec![],
+        ..Default::default()
+    }
+}
+
+pub fn exit_at(x: f32, next: &str) -> Exit {
+    Exit { x, y: FLOOR_TOP + 40.0, w: 40.0, h: 80.0, next: next.to_string() }
+}
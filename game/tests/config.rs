@@ -0,0 +1,99 @@
+use game::config::{parse_config, ConfigOverride, GameConfig, Scaling};
+
+#[test]
+fn shipped_config_loads_without_warnings() {
+    let content = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/../config.toml")).unwrap();
+    let (cfg, report) = parse_config("config.toml", &content, &[]).expect("shipped config is valid");
+    assert!(report.unknown.is_empty(), "{:?}", report.unknown);
+    assert_eq!(cfg.jump, GameConfig::default().jump);
+}
+
+#[test]
+fn missing_sections_and_values_fall_back_individually() {
+    let (cfg, report) = parse_config("partial.toml", "[jump]\nvelocity = 700.0\n", &[]).unwrap();
+    let defaults = GameConfig::default();
+    assert_eq!(cfg.jump.velocity, 700.0);
+    assert_eq!(cfg.jump.max_jumps, defaults.jump.max_jumps);
+    assert_eq!(cfg.gravity.value, defaults.gravity.value);
+    assert!(cfg.has_profile("floaty"));
+    assert_eq!(report.from_file, vec!["jump.velocity = 700".to_string()]);
+}
+
+#[test]
+fn syntax_and_type_errors_point_at_the_value() {
+    let errors = parse_config("bad.toml", "[jump]\nvelocity = 700.0\ncut_factor = \"half\"\n", &[]).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!((errors[0].line, errors[0].column), (Some(3), Some(14)));
+
+    let errors = parse_config("bad.toml", "[gravity]\nvalue = = 3\n", &[]).unwrap_err();
+    assert_eq!(errors[0].line, Some(2));
+}
+
+#[test]
+fn out_of_range_values_are_all_reported() {
+    let content = "[gravity]\nvalue = -10.0\n\n[jump]\ncut_factor = 1.5\nmax_jumps = 0\n\n[profiles.floaty]\ngravity = 0.0\n";
+    let errors = parse_config("range.toml", content, &[]).unwrap_err();
+    let found: Vec<(Option<usize>, bool)> = errors.iter().map(|e| (e.line, e.message.contains("must"))).collect();
+    assert_eq!(found, vec![(Some(2), true), (Some(5), true), (Some(6), true), (Some(9), true)]);
+    assert!(errors[3].to_string().starts_with("range.toml:9: profiles.floaty.gravity"), "{}", errors[3]);
+}
+
+#[test]
+fn unknown_keys_are_reported_with_their_line() {
+    let content = "gravty = 3\n\n[jump]\nvelocty = 700.0\n\n[profiles.mine]\ngravity = 900.0\nwings = true\n";
+    let (cfg, report) = parse_config("typo.toml", content, &[]).unwrap();
+    assert!(cfg.has_profile("mine"));
+    assert_eq!(report.unknown, vec!["typo.toml:1: gravty", "typo.toml:4: jump.velocty", "typo.toml:8: profiles.mine.wings"]);
+}
+
+fn set(arg: &str) -> ConfigOverride {
+    ConfigOverride::parse(arg, &format!("--set {}", arg)).unwrap()
+}
+
+#[test]
+fn overrides_win_over_the_file_and_keep_the_rest() {
+    let content = "[jump]\nvelocity = 650.0\n\n[profiles.floaty]\ngravity = 800.0\n";
+    let overrides = [set("jump.velocity=700"), set("gravity=1000"), set("profiles.tight.gravity=1900"), set("profile=tight")];
+    let (cfg, report) = parse_config("cfg.toml", content, &overrides).unwrap();
+    assert_eq!(cfg.jump.velocity, 700.0);
+    assert_eq!(cfg.gravity.value, 1000.0);
+    assert_eq!(cfg.profile, "tight");
+    // The rest of the shipped profile is still there
+    let tight = cfg.movement("tight");
+    assert_eq!((tight.gravity, tight.max_speed), (1900.0, 340.0));
+    assert_eq!(cfg.movement("floaty").gravity, 800.0);
+    assert!(report.overridden.contains(&"jump.velocity = 700 (--set jump.velocity=700)".to_string()));
+    assert!(!report.from_file.iter().any(|e| e.starts_with("jump.velocity")));
+}
+
+#[test]
+fn bad_overrides_are_blamed_on_their_origin() {
+    let overrides = [set("jump.velocty=700"), set("jump.velocity=fast"), set("jump.cut_factor=3"), set("jump=1")];
+    let errors = parse_config("cfg.toml", "", &overrides).unwrap_err();
+    let sources: Vec<&str> = errors.iter().map(|e| e.source.as_str()).collect();
+    assert_eq!(sources, vec!["--set jump.velocty=700", "--set jump.velocity=fast", "--set jump=1", "--set jump.cut_factor=3"]);
+    assert!(ConfigOverride::parse("jump.velocity", "--set").is_err());
+}
+
+#[test]
+fn display_settings_parse_and_leave_the_replay_hash_alone() {
+    let content = "[display]\nscaling = \"integer\"\nwindow_width = 1920\nwindow_height = 1080\n";
+    let (cfg, _) = parse_config("display.toml", content, &[set("display.fullscreen=true")]).unwrap();
+    assert_eq!(cfg.display.scaling, Scaling::Integer);
+    assert_eq!((cfg.display.width, cfg.display.height, cfg.display.window_width), (960, 540, 1920));
+    assert!(cfg.display.fullscreen);
+    assert_eq!(cfg.hash(), GameConfig::default().hash());
+
+    let errors = parse_config("display.toml", "[display]\nscaling = \"stretch\"\n", &[]).unwrap_err();
+    assert_eq!(errors[0].line, Some(2));
+}
+
+#[test]
+fn only_movement_values_change_the_replay_hash() {
+    let defaults = GameConfig::default().hash();
+    let hash = |s: &str| parse_config("test.toml", "", &[set(s)]).unwrap().0.hash();
+    assert_eq!(hash("player.columns=4"), defaults);
+    assert_eq!(hash("camera.lag_s=2"), defaults);
+    assert_ne!(hash("jump.velocity=700"), defaults);
+    assert_ne!(hash("profiles.floaty.gravity=800"), defaults);
+}
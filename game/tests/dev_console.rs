@@ -0,0 +1,63 @@
+// The developer console only exists in debug builds (or with `dev_tools`)
+#![cfg(any(debug_assertions, feature = "dev_tools"))]
+
+mod common;
+
+use common::*;
+use game::devtools::{parse_command, run_command, ConsoleCommand};
+use game::resources::{GodMode, ProfileChoice};
+
+fn run(t: &mut TestApp, line: &str) -> Result<String, String> {
+    let cmd = parse_command(line)?;
+    run_command(t.app.world_mut(), cmd)
+}
+
+#[test]
+fn commands_parse_and_reject_bad_input() {
+    assert_eq!(parse_command("tp 10 -5"), Ok(ConsoleCommand::Teleport(bevy::math::Vec2::new(10.0, -5.0))));
+    assert_eq!(parse_command("set jump.velocity 700"), Ok(ConsoleCommand::Set("jump.velocity".into(), "700".into())));
+    assert!(parse_command("tp 10").is_err());
+    assert!(parse_command("lives many").is_err());
+    assert!(parse_command("fly").is_err());
+}
+
+#[test]
+fn set_changes_the_active_profile_and_god_keeps_lives() {
+    let mut t = TestApp::new();
+    t.load_level(&floor_level("console"));
+
+    run(&mut t, "set jump.velocity 700").unwrap();
+    t.step(1);
+    assert_eq!(t.movement().jump.velocity, 700.0);
+    assert!(run(&mut t, "set wings 1").is_err());
+
+    run(&mut t, "set profile floaty").unwrap();
+    assert_eq!(t.app.world().resource::<ProfileChoice>().0.as_deref(), Some("floaty"));
+    assert!(run(&mut t, "set profile nope").is_err());
+
+    // Teleport below the level: without god mode that would cost a life
+    t.app.world_mut().insert_resource(GodMode::default());
+    run(&mut t, "god").unwrap();
+    assert!(t.app.world().resource::<GodMode>().0);
+    let lives = t.lives().current;
+    run(&mut t, "tp 0 -5000").unwrap();
+    t.step(5);
+    assert_eq!(t.lives().current, lives);
+}
+
+#[test]
+fn set_rejects_values_config_toml_would() {
+    let mut t = TestApp::new();
+    t.load_level(&floor_level("console"));
+    let before = format!("{:?}", t.config());
+
+    for line in ["set gravity 0", "set jump.cut_factor 5", "set max_jumps 0", "set max_jumps -1", "set max_jumps 1.5"] {
+        let e = run(&mut t, line).unwrap_err();
+        assert!(e.contains("must be"), "{}: {}", line, e);
+    }
+    assert_eq!(format!("{:?}", t.config()), before);
+
+    run(&mut t, "set max_jumps 3").unwrap();
+    t.step(1);
+    assert_eq!(t.movement().jump.max_jumps, 3);
+}
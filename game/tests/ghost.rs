@@ -0,0 +1,27 @@
+use bevy::math::Vec2;
+use game::systems::ghost::{ghost_position, thin_out, GHOST_STEP};
+
+#[test]
+fn a_thinned_out_run_plays_back_close_to_the_original() {
+    // A second of running along a jump arc, one position per tick
+    let run: Vec<[f32; 2]> = (0..65).map(|t| t as f32).map(|t| [t * 4.7, 300.0 * t / 64.0 - 200.0 * (t / 64.0).powi(2)]).collect();
+    let kept = thin_out(&run, GHOST_STEP);
+    assert_eq!(kept.len(), 17);
+    let frames: Vec<Vec2> = kept.into_iter().map(Vec2::from).collect();
+    for (tick, pos) in run.iter().enumerate() {
+        let at = ghost_position(&frames, GHOST_STEP, tick).unwrap();
+        assert!(at.distance(Vec2::from(*pos)) < 0.5, "tick {}: {} vs {:?}", tick, at, pos);
+    }
+    // Done: it stays at the end
+    assert_eq!(ghost_position(&frames, GHOST_STEP, 500), Some(Vec2::from(run[64])));
+}
+
+#[test]
+fn the_last_position_is_kept_when_the_run_ends_between_steps() {
+    let run: Vec<[f32; 2]> = (0..7).map(|t| [t as f32, 0.0]).collect();
+    assert_eq!(thin_out(&run, 4), vec![[0.0, 0.0], [4.0, 0.0], [6.0, 0.0]]);
+    // Ghosts saved before thinning have a position every tick
+    let frames: Vec<Vec2> = run.iter().copied().map(Vec2::from).collect();
+    assert_eq!(ghost_position(&frames, 1, 3), Some(Vec2::new(3.0, 0.0)));
+    assert_eq!(ghost_position(&[], 4, 0), None);
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use common::*;
+use game::resources::{GameState, PLAYER_SIZE};
+use game::systems::levels::LevelLoadFailure;
+use bevy::prelude::{Sprite, Transform, With};
+use game::components::{Decoration, Ground, Textured, TileMap};
+use level_format::{Decoration as DecorationDef, Layer, Level, Meta, Rect, Start, Texture, TextureMode, TileLayer, Tileset};
+
+fn pit_level(name: &str) -> Level {
+    Level { meta: Meta { name: name.to_string(), profile: None }, start: Start { x: 50.0, y: 100.0 }, ..Default::default() }
+}
+
+#[test]
+fn falling_out_of_the_level_costs_a_life_and_respawns() {
+    let mut t = TestApp::new();
+    t.load_level(&pit_level("pit"));
+    let lives = t.lives();
+
+    assert!(t.step_until(240, |t| t.lives().current < lives.current), "never died");
+    assert_eq!(t.lives().current, lives.current - 1);
+    assert_eq!(t.state(), GameState::Playing);
+    // Respawned within the same tick
+    assert_eq!(t.player_pos(), bevy::math::Vec2::new(50.0, 100.0));
+    assert_eq!(t.player_velocity(), bevy::math::Vec2::ZERO);
+}
+
+#[test]
+fn losing_the_last_life_ends_the_game() {
+    let mut t = TestApp::new();
+    t.load_level(&pit_level("last_life"));
+    t.set_lives(1);
+
+    assert!(t.step_until(240, |t| t.state() == GameState::GameOver), "no game over");
+    assert_eq!(t.lives().current, 0);
+    // The simulation stops with the game
+    let pos = t.player_pos();
+    t.step(10);
+    assert_eq!(t.player_pos(), pos);
+}
+
+#[test]
+fn reaching_an_exit_completes_the_level_and_loads_the_next() {
+    let mut t = TestApp::new();
+    let mut second = floor_level("second");
+    second.start = Start { x: -100.0, y: 40.0 };
+    let second_path = t.write_level(&second);
+    let mut first = floor_level("first");
+    first.exits.push(exit_at(200.0, &second_path));
+    let first_path = t.load_level(&first);
+    assert_eq!(t.level_manager().current, first_path);
+
+    t.land();
+    t.set_input(RIGHT);
+    assert!(t.step_until(240, |t| t.state() == GameState::LevelComplete), "exit not reached");
+    assert_eq!(t.level_request(), Some(second_path.clone()));
+    assert!(t.player_pos().x + PLAYER_SIZE.x / 2.0 > 200.0 - 20.0);
+
+    // Continue: what the level complete screen does
+    t.set_input(IDLE);
+    t.app.world_mut().resource_mut::<bevy::prelude::NextState<GameState>>().set(GameState::Loading);
+    assert!(t.step_until(10, |t| t.state() == GameState::Playing), "next level did not load");
+    assert_eq!(t.level_manager().current, second_path);
+    assert_eq!(t.level_manager().last_loaded, Some(second_path));
+    assert_eq!(t.level_request(), None);
+    assert!((t.player_pos().x - -100.0).abs() < 1e-3);
+}
+
+#[test]
+fn exit_to_a_missing_level_shows_the_load_error() {
+    let mut t = TestApp::new();
+    let mut level = floor_level("broken_exit");
+    level.exits.push(exit_at(0.0, "does/not/exist.toml"));
+    let path = t.load_level(&level);
+    assert!(t.step_until(10, |t| t.state() == GameState::LevelComplete));
+
+    t.app.world_mut().resource_mut::<bevy::prelude::NextState<GameState>>().set(GameState::Loading);
+    assert!(t.step_until(10, |t| t.state() == GameState::LoadError), "no load error");
+    assert!(t.app.world().resource::<LevelLoadFailure>().0.is_some());
+    // The last good level is still known, for "Previous level"
+    assert_eq!(t.level_manager().last_loaded, Some(path));
+}
+
+#[test]
+fn decorations_are_drawn_on_their_layer_and_do_not_collide() {
+    let mut level = floor_level("decorated");
+    level.layers.push(Layer { name: "far".into(), z: -5.0, parallax: 0.5 });
+    // A wide rectangle between the start and the floor
+    let deco = |layer: &str| DecorationDef {
+        layer: layer.into(),
+        x: 0.0,
+        y: 15.0,
+        w: 400.0,
+        h: 10.0,
+        color: "#336699".into(),
+        sprite: None,
+    };
+    level.decorations = vec![deco("far"), deco("missing")];
+    let mut t = TestApp::new();
+    t.load_level(&level);
+
+    let world = t.app.world_mut();
+    let mut placed: Vec<(f32, f32)> = world
+        .query::<(&Decoration, &Transform)>()
+        .iter(world)
+        .map(|(d, tf)| (tf.translation.z, d.parallax))
+        .collect();
+    placed.sort_by(|a, b| a.0.total_cmp(&b.0));
+    // A missing layer draws just behind the level, without parallax
+    assert_eq!(placed.len(), 2);
+    assert_eq!(placed[0], (-5.0, 0.5));
+    assert!((placed[1].0 - -1.0).abs() < 0.01 && placed[1].1 == 1.0, "{:?}", placed[1]);
+
+    // Falls straight through onto the floor
+    t.land();
+    assert!((t.player_pos().y - (FLOOR_TOP + PLAYER_SIZE.y / 2.0)).abs() < 1e-3);
+}
+
+#[test]
+fn platform_textures_are_looked_up_by_name_and_keep_the_collision_size() {
+    let mut level = floor_level("textured");
+    level.textures.push(Texture {
+        name: "grass".into(),
+        path: "textures/grass.png".into(),
+        mode: TextureMode::Slice,
+        border: 8.0,
+        scale: 2.0,
+    });
+    level.platforms[0].texture = Some("grass".into());
+    level.platforms.push(Rect { texture: Some("nope".into()), ..Rect::new(500.0, 200.0, 100.0, 20.0) });
+    let mut t = TestApp::new();
+    t.load_level(&level);
+
+    let world = t.app.world_mut();
+    let mut grounds: Vec<(f32, Option<Texture>)> = world
+        .query_filtered::<(&Transform, Option<&Textured>), With<Ground>>()
+        .iter(world)
+        .map(|(tf, tex)| (tf.translation.x, tex.map(|t| t.0.clone())))
+        .collect();
+    grounds.sort_by(|a, b| a.0.total_cmp(&b.0));
+    assert_eq!(grounds[0].1.as_ref(), level.texture("grass"));
+    // An unknown name leaves the platform a plain color
+    assert_eq!(grounds[1].1, None);
+    let sizes: Vec<_> = world.query_filtered::<&Sprite, With<Ground>>().iter(world).map(|s| s.custom_size).collect();
+    assert!(sizes.contains(&Some(bevy::math::Vec2::new(2000.0, 40.0))), "{:?}", sizes);
+
+    t.land();
+    assert!((t.player_pos().y - (FLOOR_TOP + PLAYER_SIZE.y / 2.0)).abs() < 1e-3);
+}
+
+#[test]
+fn solid_tiles_collide_as_merged_rectangles() {
+    let mut level = pit_level("tiled");
+    level.tilesets.push(Tileset { name: "ground".into(), path: "tiles/ground.png".into(), columns: 4, rows: 1 });
+    // Two full rows under the start with a one-cell notch in the top row
+    let mut layer = TileLayer::new("floor", "ground", -200.0, 0.0, 20, 2, 20.0);
+    layer.tiles.fill(1);
+    layer.set(15, 0, 0);
+    let mut drawn_only = TileLayer::new("back", "ground", -200.0, 300.0, 4, 4, 20.0);
+    drawn_only.solid = false;
+    drawn_only.tiles.fill(3);
+    level.tile_layers = vec![layer, drawn_only];
+    let mut t = TestApp::new();
+    t.load_level(&level);
+
+    let world = t.app.world_mut();
+    let mut rects: Vec<(bevy::math::Vec2, bevy::math::Vec2)> = world
+        .query_filtered::<(&Transform, &Sprite), With<Ground>>()
+        .iter(world)
+        .map(|(tf, s)| (tf.translation.truncate(), s.custom_size.unwrap()))
+        .collect();
+    rects.sort_by(|a, b| a.0.x.total_cmp(&b.0.x).then(a.0.y.total_cmp(&b.0.y)));
+    // Left and right of the notch, then the whole bottom row
+    assert_eq!(rects.len(), 3, "{:?}", rects);
+    assert_eq!(rects[0], (bevy::math::Vec2::new(-50.0, -10.0), bevy::math::Vec2::new(300.0, 20.0)));
+    assert_eq!(rects[1], (bevy::math::Vec2::new(0.0, -30.0), bevy::math::Vec2::new(400.0, 20.0)));
+    assert_eq!(rects[2], (bevy::math::Vec2::new(160.0, -10.0), bevy::math::Vec2::new(80.0, 20.0)));
+    assert_eq!(world.query::<&TileMap>().iter(world).count(), 2);
+
+    t.land();
+    assert!((t.player_pos().y - PLAYER_SIZE.y / 2.0).abs() < 1e-3, "{}", t.player_pos());
+}
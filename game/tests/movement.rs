@@ -0,0 +1,184 @@
+mod common;
+
+use common::*;
+use game::config::GameConfig;
+use game::physics::{Body, Sim, SimEvent, Vec2};
+use game::resources::{ProfileChoice, PLAYER_SIZE};
+use game::systems::input::InputFrame;
+use level_format::Rect;
+
+const EPS: f32 = 1e-3;
+
+#[test]
+fn falling_player_lands_on_the_floor() {
+    let mut t = TestApp::new();
+    t.load_level(&floor_level("landing"));
+    let start = t.player_pos();
+    assert!(!t.grounded());
+
+    t.land();
+    let pos = t.player_pos();
+    assert!((pos.y - (FLOOR_TOP + PLAYER_SIZE.y / 2.0)).abs() < EPS, "standing at {}", pos.y);
+    assert!((pos.x - start.x).abs() < EPS);
+    assert_eq!(t.player_velocity().y, 0.0);
+    assert_eq!(t.jump_state().jumps_used, 0);
+
+    // Stays put while idle
+    t.step(30);
+    assert!(t.grounded());
+    assert!((t.player_pos().y - pos.y).abs() < EPS);
+}
+
+#[test]
+fn running_accelerates_to_max_speed() {
+    let mut t = TestApp::new();
+    t.load_level(&floor_level("running"));
+    t.land();
+
+    t.set_input(RIGHT);
+    let max_speed = t.movement().max_speed;
+    assert!(t.step_until(60, |t| t.player_velocity().x >= max_speed), "never reached max speed");
+    assert_eq!(t.player_velocity().x, max_speed);
+
+    t.set_input(IDLE);
+    assert!(t.step_until(60, |t| t.player_velocity().x == 0.0), "never stopped");
+}
+
+#[test]
+fn second_jump_in_the_air_resets_vertical_speed() {
+    let mut t = TestApp::new();
+    t.load_level(&floor_level("double_jump"));
+    t.land();
+    let jump_velocity = t.movement().jump.velocity;
+
+    t.set_input(JUMP);
+    t.step(1);
+    assert_eq!(t.jump_state().jumps_used, 1);
+    assert!((t.player_velocity().y - jump_velocity).abs() < EPS);
+
+    // Released right away (a cut jump), then pressed again once falling
+    t.set_input(IDLE);
+    assert!(t.step_until(120, |t| t.player_velocity().y < 0.0));
+    let height = t.player_pos().y;
+    t.set_input(JUMP);
+    t.step(1);
+    let jump = t.jump_state();
+    assert_eq!(jump.jumps_used, 2);
+    assert!(jump.jumping);
+    assert!((t.player_velocity().y - jump_velocity).abs() < EPS);
+
+    // Climbs above the height of the second jump before landing again
+    let mut peak = height;
+    t.step_until(240, |t| {
+        peak = peak.max(t.player_pos().y);
+        t.grounded()
+    });
+    assert!(peak > height + 50.0, "peak {} vs {}", peak, height);
+    assert_eq!(t.jump_state().jumps_used, 0);
+}
+
+#[test]
+fn air_jump_is_refused_with_a_single_jump() {
+    let mut cfg = GameConfig::default();
+    cfg.jump.max_jumps = 1;
+    let mut t = TestApp::with_config(cfg);
+    t.load_level(&floor_level("single_jump"));
+    t.land();
+
+    t.set_input(JUMP);
+    t.step(1);
+    t.set_input(IDLE);
+    t.step(10);
+    let vy = t.player_velocity().y;
+    t.set_input(JUMP);
+    t.step(1);
+    assert_eq!(t.jump_state().jumps_used, 1);
+    assert!(t.player_velocity().y < vy, "jumped again in the air");
+}
+
+#[test]
+fn platforms_are_passed_from_below_and_stood_on_from_above() {
+    let mut level = floor_level("one_way");
+    // Bottom within reach of the player's head, top below the jump apex
+    let platform = Rect::new(0.0, 80.0, 200.0, 10.0);
+    level.platforms.push(platform.clone());
+    let mut t = TestApp::new();
+    t.load_level(&level);
+    t.land();
+
+    // Full-height jump straight up: no bump on the underside
+    t.set_input(JUMP);
+    let mut lowest_vy_while_below = f32::MAX;
+    let platform_top = platform.y + platform.h / 2.0;
+    t.step_until(240, |t| {
+        let (pos, vel) = (t.player_pos(), t.player_velocity());
+        if pos.y < platform_top {
+            lowest_vy_while_below = lowest_vy_while_below.min(vel.y);
+        }
+        vel.y < 0.0
+    });
+    assert!(lowest_vy_while_below > 0.0, "stopped under the platform");
+    assert!(t.player_pos().y - PLAYER_SIZE.y / 2.0 > platform_top);
+
+    t.land();
+    let pos = t.player_pos();
+    assert!((pos.y - (platform_top + PLAYER_SIZE.y / 2.0)).abs() < EPS, "landed at {}", pos.y);
+}
+
+#[test]
+fn level_profile_applies_and_runtime_choice_wins() {
+    let mut t = TestApp::new();
+    let classic = t.config().movement("classic");
+    let floaty = t.config().movement("floaty");
+    let tight = t.config().movement("tight");
+    assert_eq!(*t.movement(), classic);
+
+    let mut level = floor_level("floaty_level");
+    level.meta.profile = Some("floaty".to_string());
+    t.load_level(&level);
+    assert_eq!(*t.movement(), floaty);
+
+    // Falls more slowly than with the classic gravity
+    t.step(1);
+    let vy = t.player_velocity().y;
+    t.step(1);
+    assert!((t.player_velocity().y - (vy - floaty.gravity * DT)).abs() < EPS);
+
+    t.app.world_mut().resource_mut::<ProfileChoice>().0 = Some("tight".to_string());
+    t.step(1);
+    assert_eq!(*t.movement(), tight);
+
+    // Unknown names fall back to the next choice
+    t.app.world_mut().resource_mut::<ProfileChoice>().0 = Some("nope".to_string());
+    t.step(1);
+    assert_eq!(*t.movement(), floaty);
+}
+
+#[test]
+fn plain_simulation_matches_the_game() {
+    let mut level = floor_level("plain_sim");
+    level.exits.push(exit_at(400.0, "next"));
+    let mut t = TestApp::new();
+    t.load_level(&level);
+    let body = Body { pos: t.player_pos(), vel: t.player_velocity(), jump: t.jump_state() };
+    let mut sim = Sim { body, ..Sim::new(level, t.movement().clone(), 1, Vec2::ZERO) };
+
+    // Run, jump while running, let go mid-air, and keep running into the exit
+    let run_jump = InputFrame { jump: true, ..RIGHT };
+    let script = [(IDLE, 30), (RIGHT, 20), (run_jump, 10), (RIGHT, 200)];
+    let mut reached = None;
+    'script: for (frame, ticks) in script {
+        for _ in 0..ticks {
+            t.set_input(frame);
+            t.step(1);
+            let event = sim.tick(frame, DT);
+            assert_eq!(sim.body.pos, t.player_pos(), "after {} ticks", sim.ticks);
+            if let Some(event) = event {
+                reached = Some(event);
+                break 'script;
+            }
+        }
+    }
+    assert_eq!(reached, Some(SimEvent::Exit("next".into())));
+    assert_eq!(t.level_request().as_deref(), Some("next"));
+}
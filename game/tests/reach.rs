@@ -0,0 +1,57 @@
+mod common;
+
+use common::*;
+use game::config::{GameConfig, Movement};
+use game::reach::{analyze, Surface};
+use level_format::{Level, Rect};
+
+fn movement(max_jumps: u8) -> Movement {
+    let mut cfg = GameConfig::default();
+    cfg.jump.max_jumps = max_jumps;
+    cfg.movement(&cfg.profile.clone())
+}
+
+// The floor of `floor_level` plus a ledge whose top is `height` above it
+fn ledge_level(height: f32) -> Level {
+    let mut level = floor_level("ledge");
+    level.platforms.push(Rect::new(200.0, FLOOR_TOP + height - 10.0, 120.0, 20.0));
+    level
+}
+
+#[test]
+fn ledges_within_jump_height_are_reachable() {
+    let level = ledge_level(100.0);
+    let reach = analyze(&level, &movement(1));
+    assert_eq!(reach.start, Some(Surface::Platform(0)));
+    assert!(reach.unreachable_platforms(&level).is_empty());
+    // Arcs from the start surface land on the ledge
+    assert!(reach.arcs.iter().any(|a| a.from == Some(Surface::Platform(0)) && a.to == Some(Surface::Platform(1))));
+}
+
+#[test]
+fn a_second_jump_reaches_higher_ledges() {
+    let level = ledge_level(200.0);
+    assert_eq!(analyze(&level, &movement(1)).unreachable_platforms(&level), vec![1]);
+    assert!(analyze(&level, &movement(2)).unreachable_platforms(&level).is_empty());
+}
+
+#[test]
+fn exits_past_a_wide_gap_are_flagged() {
+    let mut level = floor_level("gap");
+    // Floor ends at x = 1000; a far island with an exit, and an exit on the floor
+    level.platforms.push(Rect::new(3000.0, FLOOR_TOP - 20.0, 400.0, 40.0));
+    level.exits.push(exit_at(3000.0, "far"));
+    level.exits.push(exit_at(500.0, "near"));
+    let reach = analyze(&level, &movement(2));
+    assert_eq!(reach.unreachable_platforms(&level), vec![1]);
+    assert_eq!(reach.unreachable_exits(&level), vec![0]);
+}
+
+#[test]
+fn nothing_is_reachable_when_the_start_is_over_a_pit() {
+    let mut level = floor_level("pit");
+    level.start.x = 5000.0;
+    let reach = analyze(&level, &movement(2));
+    assert_eq!(reach.start, None);
+    assert_eq!(reach.unreachable_platforms(&level), vec![0]);
+}
@@ -0,0 +1,83 @@
+use game::config::GameConfig;
+use game::physics::{Sim, Vec2, TICK_HZ};
+use game::resources::PLAYER_SIZE;
+use game::systems::input::InputFrame;
+use level_format::{Level, Meta, Point, Slope, Start};
+
+const DT: f32 = 1.0 / TICK_HZ as f32;
+
+// Downhill to the right: from (0, 200) to (600, 0)
+fn hill() -> Slope {
+    Slope { points: vec![Point { x: 0.0, y: 200.0 }, Point { x: 600.0, y: 0.0 }], depth: 20.0 }
+}
+
+fn sim_on(slope: Slope, x: f32) -> Sim {
+    let y = slope.surface_y_at(x).unwrap() + PLAYER_SIZE.y / 2.0;
+    let level = Level {
+        meta: Meta { name: "slope".into(), profile: None },
+        start: Start { x, y },
+        slopes: vec![slope],
+        ..Default::default()
+    };
+    let cfg = GameConfig::default();
+    let mut sim = Sim::new(level, cfg.movement(&cfg.profile), 1, Vec2::new(x, y));
+    // Settle onto the surface
+    for _ in 0..4 {
+        sim.tick(InputFrame::default(), DT);
+    }
+    sim
+}
+
+fn bottom_above_surface(sim: &Sim) -> f32 {
+    let surface = sim.level.slopes[0].surface_y_at(sim.body.pos.x).unwrap();
+    sim.body.pos.y - PLAYER_SIZE.y / 2.0 - surface
+}
+
+#[test]
+fn surface_height_is_interpolated_within_the_span() {
+    let s = Slope { points: vec![Point { x: 0.0, y: 0.0 }, Point { x: 100.0, y: 50.0 }, Point { x: 200.0, y: 50.0 }], depth: 20.0 };
+    assert_eq!(s.surface_y_at(50.0), Some(25.0));
+    assert_eq!(s.surface_y_at(150.0), Some(50.0));
+    assert_eq!(s.surface_y_at(-1.0), None);
+    assert_eq!(s.surface_y_at(201.0), None);
+    // Segments that don't go left to right have no surface
+    let back = Slope { points: vec![Point { x: 100.0, y: 0.0 }, Point { x: 0.0, y: 50.0 }], depth: 20.0 };
+    assert_eq!(back.surface_y_at(50.0), None);
+}
+
+#[test]
+fn walking_down_a_slope_stays_on_it() {
+    let mut sim = sim_on(hill(), 40.0);
+    assert!(sim.body.jump.grounded);
+    while sim.body.pos.x < 560.0 {
+        sim.tick(InputFrame { right: true, ..Default::default() }, DT);
+        assert!(sim.body.jump.grounded, "left the slope at x = {}", sim.body.pos.x);
+        assert!(bottom_above_surface(&sim).abs() < 1e-3, "off the surface at x = {}", sim.body.pos.x);
+    }
+}
+
+#[test]
+fn jumping_off_a_slope_is_not_snapped_back() {
+    let mut sim = sim_on(hill(), 300.0);
+    assert!(sim.body.jump.grounded);
+    let jump = InputFrame { right: true, jump: true, ..Default::default() };
+    // Contact is resolved before the jump, so the player leaves on the next tick
+    sim.tick(jump, DT);
+    assert!(sim.body.vel.y > 0.0);
+    sim.tick(jump, DT);
+    assert!(!sim.body.jump.grounded);
+    let first = bottom_above_surface(&sim);
+    assert!(first > 0.0);
+    sim.tick(jump, DT);
+    assert!(!sim.body.jump.grounded, "snapped back onto the slope");
+    assert!(bottom_above_surface(&sim) > first);
+}
+
+#[test]
+fn levels_without_slopes_save_without_the_key() {
+    let level = Level { meta: Meta { name: "flat".into(), profile: None }, ..Default::default() };
+    let toml = level.to_toml_string_pretty().unwrap();
+    assert!(!toml.contains("slopes"), "{toml}");
+    let with = Level { slopes: vec![hill()], ..level };
+    assert_eq!(Level::from_toml_str(&with.to_toml_string_pretty().unwrap()).unwrap(), with);
+}
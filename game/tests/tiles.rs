@@ -0,0 +1,111 @@
+use game::config::GameConfig;
+use game::physics::{Sim, Vec2, TICK_HZ};
+use level_format::{Level, Meta, Rect, Start, TileEncoding, TileLayer, Tileset, MAX_GRID_SIDE};
+
+fn level_with(layer: TileLayer) -> Level {
+    Level {
+        meta: Meta { name: "tiles".into(), profile: None },
+        start: Start { x: 0.0, y: 100.0 },
+        tilesets: vec![Tileset { name: "ground".into(), path: "tiles/ground.png".into(), columns: 4, rows: 1 }],
+        tile_layers: vec![layer],
+        ..Default::default()
+    }
+}
+
+fn checkered(encoding: TileEncoding) -> TileLayer {
+    let mut layer = TileLayer::new("floor", "ground", 0.0, 0.0, 5, 3, 10.0);
+    layer.encoding = encoding;
+    for (i, t) in layer.tiles.iter_mut().enumerate() {
+        *t = [0, 0, 2, 2, 2, 4][i % 6];
+    }
+    layer
+}
+
+#[test]
+fn tile_data_round_trips_in_both_encodings() {
+    for encoding in [TileEncoding::Rle, TileEncoding::Base64] {
+        let level = level_with(checkered(encoding));
+        let toml = level.to_toml_string_pretty().unwrap();
+        assert_eq!(Level::from_toml_str(&toml).unwrap(), level, "{toml}");
+    }
+    let toml = level_with(checkered(TileEncoding::Rle)).to_toml_string_pretty().unwrap();
+    assert!(toml.contains(r#"data = "2*0,3*2,4,2*0,3*2,4,2*0,2""#), "{toml}");
+}
+
+#[test]
+fn tile_data_of_the_wrong_size_is_an_error() {
+    let toml = level_with(checkered(TileEncoding::Rle)).to_toml_string_pretty().unwrap();
+    let short = toml.replace(r#"data = "2*0,3*2,4,2*0,3*2,4,2*0,2""#, r#"data = "2*0,3*2""#);
+    let err = Level::from_toml_str(&short).unwrap_err().to_string();
+    assert!(err.contains("tile layer 'floor': 5 tiles for a 5x3 grid, expected 15"), "{err}");
+    let bad = toml.replace(r#"data = "2*0,3*2,4,2*0,3*2,4,2*0,2""#, r#"data = "2*x""#);
+    assert!(Level::from_toml_str(&bad).unwrap_err().to_string().contains("bad tile number in '2*x'"));
+}
+
+#[test]
+fn a_run_longer_than_the_grid_is_an_error() {
+    let toml = level_with(checkered(TileEncoding::Rle)).to_toml_string_pretty().unwrap();
+    let huge = toml.replace(r#"data = "2*0,3*2,4,2*0,3*2,4,2*0,2""#, r#"data = "2*0,99999999999*1""#);
+    let err = Level::from_toml_str(&huge).unwrap_err().to_string();
+    assert!(err.contains("tile layer 'floor': '99999999999*1' goes past the 15 tiles of the grid"), "{err}");
+    let one_over = toml.replace(r#"data = "2*0,3*2,4,2*0,3*2,4,2*0,2""#, r#"data = "15*0,1""#);
+    assert!(Level::from_toml_str(&one_over).unwrap_err().to_string().contains("'1' goes past"));
+}
+
+#[test]
+fn grids_over_the_size_limit_are_an_error() {
+    let toml = level_with(checkered(TileEncoding::Rle)).to_toml_string_pretty().unwrap();
+    let huge = toml.replace("columns = 5", "columns = 4000000000").replace("rows = 3", "rows = 4000000000");
+    let err = Level::from_toml_str(&huge).unwrap_err().to_string();
+    assert!(err.contains("tile layer 'floor': 4000000000x4000000000 grid is larger than the 1000x1000 allowed"), "{err}");
+
+    // A layer at the limit still works, and its cells are addressed without overflow
+    let mut layer = TileLayer::new("floor", "ground", 0.0, 0.0, MAX_GRID_SIDE, MAX_GRID_SIDE, 10.0);
+    assert!(layer.set(MAX_GRID_SIDE - 1, MAX_GRID_SIDE - 1, 3));
+    assert_eq!(layer.get(MAX_GRID_SIDE - 1, MAX_GRID_SIDE - 1), 3);
+    let big = Tileset { name: "big".into(), path: "big.png".into(), columns: 100_000, rows: 100_000 };
+    assert_eq!(big.tile_count(), u32::MAX);
+}
+
+#[test]
+fn solid_tiles_merge_into_few_rectangles() {
+    let mut layer = TileLayer::new("floor", "ground", 0.0, 0.0, 6, 4, 10.0);
+    // A 4x3 block with a 2x1 ledge on its top right
+    for row in 1..4 {
+        for col in 0..4 {
+            layer.set(col, row, 1);
+        }
+    }
+    layer.set(4, 0, 2);
+    layer.set(5, 0, 2);
+    let rects = layer.solid_rects();
+    assert_eq!(rects, vec![Rect::new(50.0, -5.0, 20.0, 10.0), Rect::new(20.0, -25.0, 40.0, 30.0)]);
+
+    layer.solid = false;
+    assert!(layer.solid_rects().is_empty());
+}
+
+#[test]
+fn fill_replaces_the_connected_area_only() {
+    let mut layer = checkered(TileEncoding::Rle);
+    // The empty cells form one diagonal band
+    assert!(layer.fill(0, 0, 3));
+    assert_eq!(layer.tiles, vec![3, 3, 2, 2, 2, 4, 3, 3, 2, 2, 2, 4, 3, 3, 2]);
+    // The 2 starting the last row doesn't touch the other 2s
+    assert!(layer.fill(0, 2, 1));
+    assert_eq!(layer.tiles, vec![3, 3, 2, 2, 2, 4, 3, 3, 2, 2, 1, 4, 3, 3, 2]);
+    assert!(!layer.fill(0, 2, 1));
+}
+
+#[test]
+fn the_simulation_stands_on_solid_tiles() {
+    let mut layer = TileLayer::new("floor", "ground", -100.0, 0.0, 20, 1, 10.0);
+    layer.tiles.fill(1);
+    let cfg = GameConfig::default();
+    let mut sim = Sim::new(level_with(layer), cfg.movement(&cfg.profile), 1, Vec2::new(0.0, 100.0));
+    for _ in 0..120 {
+        sim.tick(Default::default(), 1.0 / TICK_HZ as f32);
+    }
+    assert!(sim.body.jump.grounded);
+    assert!((sim.body.pos.y - game::resources::PLAYER_SIZE.y / 2.0).abs() < 1e-3);
+}
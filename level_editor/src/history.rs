@@ -0,0 +1,291 @@
+// Undo/redo: every change to the level goes through a Command, which knows how
+// to apply itself and how to reverse that.
+
+use eframe::egui::Vec2;
+use level_format::{Decoration, Exit, Layer, Level, Meta, Rect as LRect, Slope, Start, Texture, TileLayer, Tileset};
+
+use crate::{slope_bounds, ItemKind};
+
+// A copy of one level object
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    Platform(LRect),
+    Exit(Exit),
+    Slope(Slope),
+    Decoration(Decoration),
+}
+
+impl Item {
+    pub fn kind(&self) -> ItemKind {
+        match self {
+            Item::Platform(_) => ItemKind::Platform,
+            Item::Exit(_) => ItemKind::Exit,
+            Item::Slope(_) => ItemKind::Slope,
+            Item::Decoration(_) => ItemKind::Decoration,
+        }
+    }
+
+    pub fn get(level: &Level, kind: ItemKind, idx: usize) -> Option<Item> {
+        match kind {
+            ItemKind::Platform => level.platforms.get(idx).cloned().map(Item::Platform),
+            ItemKind::Exit => level.exits.get(idx).cloned().map(Item::Exit),
+            ItemKind::Slope => level.slopes.get(idx).cloned().map(Item::Slope),
+            ItemKind::Decoration => level.decorations.get(idx).cloned().map(Item::Decoration),
+        }
+    }
+
+    // Copy moved by `delta`
+    pub fn offset(mut self, delta: Vec2) -> Item {
+        match &mut self {
+            Item::Platform(r) => { r.x += delta.x; r.y += delta.y; }
+            Item::Exit(e) => { e.x += delta.x; e.y += delta.y; }
+            Item::Decoration(d) => { d.x += delta.x; d.y += delta.y; }
+            Item::Slope(s) => {
+                for p in s.points.iter_mut() {
+                    p.x += delta.x;
+                    p.y += delta.y;
+                }
+            }
+        }
+        self
+    }
+
+    // Center-based bounding box
+    pub fn bounds(&self) -> LRect {
+        match self {
+            Item::Platform(r) => r.clone(),
+            Item::Exit(e) => LRect::new(e.x, e.y, e.w, e.h),
+            Item::Slope(s) => slope_bounds(s),
+            Item::Decoration(d) => LRect::new(d.x, d.y, d.w, d.h),
+        }
+    }
+
+    pub fn count(level: &Level, kind: ItemKind) -> usize {
+        match kind {
+            ItemKind::Platform => level.platforms.len(),
+            ItemKind::Exit => level.exits.len(),
+            ItemKind::Slope => level.slopes.len(),
+            ItemKind::Decoration => level.decorations.len(),
+        }
+    }
+
+    fn set(self, level: &mut Level, idx: usize) {
+        match self {
+            Item::Platform(r) => if let Some(slot) = level.platforms.get_mut(idx) { *slot = r; },
+            Item::Exit(e) => if let Some(slot) = level.exits.get_mut(idx) { *slot = e; },
+            Item::Slope(s) => if let Some(slot) = level.slopes.get_mut(idx) { *slot = s; },
+            Item::Decoration(d) => if let Some(slot) = level.decorations.get_mut(idx) { *slot = d; },
+        }
+    }
+
+    fn insert(self, level: &mut Level, idx: usize) {
+        match self {
+            Item::Platform(r) => level.platforms.insert(idx.min(level.platforms.len()), r),
+            Item::Exit(e) => level.exits.insert(idx.min(level.exits.len()), e),
+            Item::Slope(s) => level.slopes.insert(idx.min(level.slopes.len()), s),
+            Item::Decoration(d) => level.decorations.insert(idx.min(level.decorations.len()), d),
+        }
+    }
+
+    fn remove(level: &mut Level, kind: ItemKind, idx: usize) {
+        match kind {
+            ItemKind::Platform => if idx < level.platforms.len() { level.platforms.remove(idx); },
+            ItemKind::Exit => if idx < level.exits.len() { level.exits.remove(idx); },
+            ItemKind::Slope => if idx < level.slopes.len() { level.slopes.remove(idx); },
+            ItemKind::Decoration => if idx < level.decorations.len() { level.decorations.remove(idx); },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    // Create, duplicate, paste
+    Insert { idx: usize, item: Item },
+    Delete { idx: usize, item: Item },
+    // Move, resize, property edits
+    Replace { idx: usize, before: Item, after: Item },
+    SetStart { before: Start, after: Start },
+    SetMeta { before: Meta, after: Meta },
+    // Add, remove, rename or retune layers
+    SetLayers { before: Vec<Layer>, after: Vec<Layer> },
+    SetTextures { before: Vec<Texture>, after: Vec<Texture> },
+    SetTilesets { before: Vec<Tileset>, after: Vec<Tileset> },
+    // Tile layer edits, painting included
+    SetTileLayers { before: Vec<TileLayer>, after: Vec<TileLayer> },
+    // Applied in order, undone in reverse, as one step
+    Batch(Vec<Command>),
+}
+
+impl Command {
+    pub fn apply(&self, level: &mut Level) {
+        match self {
+            Command::Insert { idx, item } => item.clone().insert(level, *idx),
+            Command::Delete { idx, item } => Item::remove(level, item.kind(), *idx),
+            Command::Replace { idx, after, .. } => after.clone().set(level, *idx),
+            Command::SetStart { after, .. } => level.start = *after,
+            Command::SetMeta { after, .. } => level.meta = after.clone(),
+            Command::SetLayers { after, .. } => level.layers = after.clone(),
+            Command::SetTextures { after, .. } => level.textures = after.clone(),
+            Command::SetTilesets { after, .. } => level.tilesets = after.clone(),
+            Command::SetTileLayers { after, .. } => level.tile_layers = after.clone(),
+            Command::Batch(cmds) => cmds.iter().for_each(|c| c.apply(level)),
+        }
+    }
+
+    pub fn revert(&self, level: &mut Level) {
+        match self {
+            Command::Insert { idx, item } => Item::remove(level, item.kind(), *idx),
+            Command::Delete { idx, item } => item.clone().insert(level, *idx),
+            Command::Replace { idx, before, .. } => before.clone().set(level, *idx),
+            Command::SetStart { before, .. } => level.start = *before,
+            Command::SetMeta { before, .. } => level.meta = before.clone(),
+            Command::SetLayers { before, .. } => level.layers = before.clone(),
+            Command::SetTextures { before, .. } => level.textures = before.clone(),
+            Command::SetTilesets { before, .. } => level.tilesets = before.clone(),
+            Command::SetTileLayers { before, .. } => level.tile_layers = before.clone(),
+            Command::Batch(cmds) => cmds.iter().rev().for_each(|c| c.revert(level)),
+        }
+    }
+
+    // Fold a later command of the same gesture into this one: keep our `before`,
+    // take its `after`. Returns false when the two don't line up.
+    fn merge(&mut self, later: Command) -> bool {
+        match (self, later) {
+            (Command::Replace { idx, after, .. }, Command::Replace { idx: i2, after: a2, .. }) if *idx == i2 && after.kind() == a2.kind() => {
+                *after = a2;
+                true
+            }
+            (Command::SetStart { after, .. }, Command::SetStart { after: a2, .. }) => {
+                *after = a2;
+                true
+            }
+            (Command::SetMeta { after, .. }, Command::SetMeta { after: a2, .. }) => {
+                *after = a2;
+                true
+            }
+            (Command::SetLayers { after, .. }, Command::SetLayers { after: a2, .. }) => {
+                *after = a2;
+                true
+            }
+            (Command::SetTextures { after, .. }, Command::SetTextures { after: a2, .. }) => {
+                *after = a2;
+                true
+            }
+            (Command::SetTilesets { after, .. }, Command::SetTilesets { after: a2, .. }) => {
+                *after = a2;
+                true
+            }
+            (Command::SetTileLayers { after, .. }, Command::SetTileLayers { after: a2, .. }) => {
+                *after = a2;
+                true
+            }
+            (Command::Batch(cmds), Command::Batch(later)) if cmds.len() == later.len() => {
+                // Only when every part merges, so a half-merged batch never happens
+                let mut merged = cmds.clone();
+                if merged.iter_mut().zip(later).all(|(c, l)| c.merge(l)) {
+                    *cmds = merged;
+                    true
+                } else {
+                    false
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+// Identifies one continuous gesture (a drag, typing in one field); commands
+// pushed with the same key become a single undo step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MergeKey(pub u64);
+
+struct Entry {
+    cmd: Command,
+    merge: Option<MergeKey>,
+    // Unique per change, so the saved state can be recognized
+    revision: u64,
+}
+
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+    next_revision: u64,
+    next_gesture: u64,
+    // Revision on top of the undo stack when the level was last saved or loaded
+    saved: u64,
+}
+
+impl History {
+    // Apply `cmd` to the level and record it
+    pub fn push(&mut self, level: &mut Level, cmd: Command, merge: Option<MergeKey>) {
+        cmd.apply(level);
+        self.redo.clear();
+        self.next_revision += 1;
+        let revision = self.next_revision;
+        if let (Some(key), Some(top)) = (merge, self.undo.last_mut()) {
+            if top.merge == Some(key) && top.cmd.merge(cmd.clone()) {
+                top.revision = revision;
+                return;
+            }
+        }
+        self.undo.push(Entry { cmd, merge, revision });
+    }
+
+    pub fn undo(&mut self, level: &mut Level) -> bool {
+        let Some(entry) = self.undo.pop() else { return false; };
+        entry.cmd.revert(level);
+        self.redo.push(entry);
+        true
+    }
+
+    pub fn redo(&mut self, level: &mut Level) -> bool {
+        let Some(mut entry) = self.redo.pop() else { return false; };
+        entry.cmd.apply(level);
+        // A redone step never merges with what comes next
+        entry.merge = None;
+        self.undo.push(entry);
+        true
+    }
+
+    // Key for a new gesture
+    pub fn gesture(&mut self) -> MergeKey {
+        self.next_gesture += 1;
+        MergeKey(self.next_gesture)
+    }
+
+    // Forget everything, e.g. for a newly opened level
+    pub fn reset(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.saved = 0;
+    }
+
+    // Unsaved even before any edit, e.g. a level imported from another format
+    pub fn mark_unsaved(&mut self) {
+        self.saved = u64::MAX;
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved = self.top_revision();
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.top_revision() != self.saved
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    fn top_revision(&self) -> u64 {
+        self.undo.last().map_or(0, |e| e.revision)
+    }
+}
+
+#[cfg(test)]
+mod tests;
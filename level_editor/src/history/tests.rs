@@ -0,0 +1,109 @@
+use eframe::egui::vec2;
+use level_format::{Level, Rect as LRect};
+
+use super::{Command, History, Item};
+
+fn level_with_platform() -> Level {
+    Level { platforms: vec![LRect::new(0.0, 0.0, 100.0, 20.0)], ..Default::default() }
+}
+
+// Replace the first platform with a copy moved by (dx, 0)
+fn nudge(level: &Level, dx: f32) -> Command {
+    let before = Item::Platform(level.platforms[0].clone());
+    let after = before.clone().offset(vec2(dx, 0.0));
+    Command::Replace { idx: 0, before, after }
+}
+
+#[test]
+fn a_drag_is_undone_in_one_step() {
+    let mut level = level_with_platform();
+    let mut history = History::default();
+    let drag = history.gesture();
+    for _ in 0..5 {
+        let cmd = nudge(&level, 10.0);
+        history.push(&mut level, cmd, Some(drag));
+    }
+    assert_eq!(level.platforms[0].x, 50.0);
+
+    assert!(history.undo(&mut level));
+    assert_eq!(level.platforms[0].x, 0.0);
+    assert!(!history.can_undo());
+    assert!(history.redo(&mut level));
+    assert_eq!(level.platforms[0].x, 50.0);
+
+    // Another gesture is a step of its own
+    let cmd = nudge(&level, 10.0);
+    let next = history.gesture();
+    history.push(&mut level, cmd, Some(next));
+    history.undo(&mut level);
+    assert_eq!(level.platforms[0].x, 50.0);
+}
+
+#[test]
+fn a_new_change_clears_redo() {
+    let mut level = level_with_platform();
+    let mut history = History::default();
+    let cmd = nudge(&level, 10.0);
+    history.push(&mut level, cmd, None);
+    history.undo(&mut level);
+    assert!(history.can_redo());
+
+    let cmd = nudge(&level, -10.0);
+    history.push(&mut level, cmd, None);
+    assert!(!history.can_redo());
+    assert!(!history.redo(&mut level));
+    assert_eq!(level.platforms[0].x, -10.0);
+}
+
+#[test]
+fn batches_apply_in_order_and_undo_in_reverse() {
+    let mut level = Level::default();
+    let mut history = History::default();
+    let first = Item::Platform(LRect::new(0.0, 0.0, 10.0, 10.0));
+    let moved = first.clone().offset(vec2(5.0, 0.0));
+    // The replace only works after the insert, and must be undone before it
+    let batch = Command::Batch(vec![
+        Command::Insert { idx: 0, item: first.clone() },
+        Command::Replace { idx: 0, before: first, after: moved },
+    ]);
+    history.push(&mut level, batch, None);
+    assert_eq!(level.platforms, vec![LRect::new(5.0, 0.0, 10.0, 10.0)]);
+    history.undo(&mut level);
+    assert!(level.platforms.is_empty());
+    history.redo(&mut level);
+    assert_eq!(level.platforms, vec![LRect::new(5.0, 0.0, 10.0, 10.0)]);
+}
+
+#[test]
+fn dirty_follows_save_undo_and_redo() {
+    let mut level = level_with_platform();
+    let mut history = History::default();
+    assert!(!history.is_dirty());
+
+    let cmd = nudge(&level, 10.0);
+    history.push(&mut level, cmd, None);
+    assert!(history.is_dirty());
+    history.mark_saved();
+    assert!(!history.is_dirty());
+
+    history.undo(&mut level);
+    assert!(history.is_dirty());
+    history.redo(&mut level);
+    assert!(!history.is_dirty());
+
+    // Dragging on after a save is a change, though it merges into the saved step
+    let drag = history.gesture();
+    let cmd = nudge(&level, 10.0);
+    history.push(&mut level, cmd, Some(drag));
+    history.mark_saved();
+    let cmd = nudge(&level, 10.0);
+    history.push(&mut level, cmd, Some(drag));
+    assert!(history.is_dirty());
+
+    // An imported level is unsaved until it has been saved
+    history.reset();
+    history.mark_unsaved();
+    assert!(history.is_dirty());
+    history.mark_saved();
+    assert!(!history.is_dirty());
+}
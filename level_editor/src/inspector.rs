@@ -0,0 +1,561 @@
+// Side panel with editable fields for the selection, the level's meta and start,
+// and warnings about things that won't work in the game.
+
+use std::path::{Path, PathBuf};
+
+use eframe::egui::{self, vec2, Color32, Ui};
+use level_format::{format_color, parse_color, Decoration, Exit, Level, Rect as LRect, Texture, TextureMode};
+
+use crate::history::{Command, Item};
+use crate::paths;
+use crate::selection::{self, Align, Selection};
+use crate::textures::{assets_dir, ASSETS_NOT_FOUND};
+use crate::{EditorApp, ItemKind};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    // The object the warning is about, if it is about one
+    pub item: Option<(ItemKind, usize)>,
+    pub message: String,
+}
+
+impl Warning {
+    fn level(message: impl Into<String>) -> Self {
+        Self { item: None, message: message.into() }
+    }
+
+    fn item(kind: ItemKind, idx: usize, message: impl Into<String>) -> Self {
+        Self { item: Some((kind, idx)), message: message.into() }
+    }
+}
+
+// Problems the game would run into with this level. `known_levels` are the
+// names an exit can lead to.
+pub fn validate(level: &Level, known_levels: &[String]) -> Vec<Warning> {
+    let mut out = Vec::new();
+    if level.meta.name.trim().is_empty() {
+        out.push(Warning::level("Level has no name"));
+    }
+    if level.exits.is_empty() {
+        out.push(Warning::level("Level has no exit"));
+    }
+
+    let start = vec2(level.start.x, level.start.y);
+    let solid_at = |p: egui::Vec2| {
+        level.platforms.iter().any(|r| crate::point_in_center_rect(p, r.x, r.y, r.w, r.h))
+            || level.slopes.iter().any(|s| crate::point_in_slope(p, s))
+    };
+    if solid_at(start) {
+        out.push(Warning::level("Start is inside solid ground"));
+    } else {
+        let ground_below = level.platforms.iter().any(|r| (start.x - r.x).abs() <= r.w * 0.5 && r.y + r.h * 0.5 <= start.y)
+            || level.slopes.iter().any(|s| s.surface_y_at(start.x).is_some_and(|y| y <= start.y));
+        if !ground_below {
+            out.push(Warning::level("Nothing below the start; the player falls out of the level"));
+        }
+    }
+
+    for (i, r) in level.platforms.iter().enumerate() {
+        if ![r.x, r.y, r.w, r.h].iter().all(|v| v.is_finite()) {
+            out.push(Warning::item(ItemKind::Platform, i, "Platform has an invalid number"));
+        } else if r.w <= 0.0 || r.h <= 0.0 {
+            out.push(Warning::item(ItemKind::Platform, i, "Platform has no area"));
+        }
+        if let Some(name) = r.texture.as_deref().filter(|n| level.texture(n).is_none()) {
+            out.push(Warning::item(ItemKind::Platform, i, format!("Platform uses unknown texture '{}'", name)));
+        }
+    }
+    for (i, e) in level.exits.iter().enumerate() {
+        if e.w <= 0.0 || e.h <= 0.0 {
+            out.push(Warning::item(ItemKind::Exit, i, "Exit has no area"));
+        }
+        if e.next.trim().is_empty() {
+            out.push(Warning::item(ItemKind::Exit, i, "Exit has no target level"));
+        } else if !e.next.ends_with(".toml") && !known_levels.contains(&e.next) {
+            out.push(Warning::item(ItemKind::Exit, i, format!("Exit leads to unknown level '{}'", e.next)));
+        }
+    }
+    for (i, s) in level.slopes.iter().enumerate() {
+        if s.points.len() < 2 {
+            out.push(Warning::item(ItemKind::Slope, i, "Slope needs at least two points (the game skips it)"));
+        } else if s.points.windows(2).any(|p| p[1].x <= p[0].x) {
+            out.push(Warning::item(ItemKind::Slope, i, "Slope points must run left to right"));
+        }
+        if s.depth <= 0.0 {
+            out.push(Warning::item(ItemKind::Slope, i, "Slope has no depth"));
+        }
+    }
+    for (i, d) in level.decorations.iter().enumerate() {
+        if d.w <= 0.0 || d.h <= 0.0 {
+            out.push(Warning::item(ItemKind::Decoration, i, "Decoration has no area"));
+        }
+        if level.layer(&d.layer).is_none() {
+            out.push(Warning::item(ItemKind::Decoration, i, format!("Decoration is on unknown layer '{}'", d.layer)));
+        }
+        if parse_color(&d.color).is_none() {
+            out.push(Warning::item(ItemKind::Decoration, i, format!("Decoration color '{}' isn't #rrggbb or #rrggbbaa", d.color)));
+        }
+    }
+    let mut names: Vec<&str> = level.layers.iter().map(|l| l.name.as_str()).collect();
+    names.sort();
+    if names.windows(2).any(|n| n[0] == n[1]) {
+        out.push(Warning::level("Two layers have the same name"));
+    }
+    for layer in &level.tile_layers {
+        match level.tileset(&layer.tileset) {
+            None => out.push(Warning::level(format!("Tile layer '{}' uses unknown tileset '{}'", layer.name, layer.tileset))),
+            Some(t) if layer.tiles.iter().any(|tile| *tile > t.tile_count()) => out.push(Warning::level(format!(
+                "Tile layer '{}' has tiles past the {} in tileset '{}'",
+                layer.name,
+                t.tile_count(),
+                t.name
+            ))),
+            Some(_) => {}
+        }
+    }
+    let images = level
+        .textures
+        .iter()
+        .map(|t| &t.path)
+        .chain(level.tilesets.iter().map(|t| &t.path))
+        .chain(level.decorations.iter().filter_map(|d| d.sprite.as_ref()));
+    match assets_dir() {
+        Some(dir) => {
+            let mut missing: Vec<&String> = images.filter(|p| !dir.join(p).is_file()).collect();
+            missing.sort();
+            missing.dedup();
+            for path in missing {
+                out.push(Warning::level(format!("Image '{}' isn't in the game's assets folder (shown as a checkerboard)", path)));
+            }
+        }
+        None if images.count() > 0 => out.push(Warning::level(ASSETS_NOT_FOUND)),
+        None => {}
+    }
+    for t in &level.textures {
+        if t.mode == TextureMode::Slice && t.border <= 0.0 {
+            out.push(Warning::level(format!("Texture '{}' is sliced but has no border", t.name)));
+        }
+    }
+    out
+}
+
+// Level names an exit can use: files in the game's levels/ folder, if there is
+// one, and next to the level being edited
+pub fn scan_levels(current: Option<&Path>) -> Vec<String> {
+    let mut dirs: Vec<PathBuf> = paths::find("levels").into_iter().collect();
+    if let Some(dir) = current.and_then(Path::parent) {
+        dirs.push(dir.to_path_buf());
+    }
+    let mut names: Vec<String> = dirs
+        .iter()
+        .filter_map(|d| std::fs::read_dir(d).ok())
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "toml"))
+        .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+impl EditorApp {
+    pub(crate) fn inspector(&mut self, ctx: &egui::Context) {
+        egui::SidePanel::right("inspector").resizable(true).default_width(240.0).show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                if self.level.is_none() {
+                    ui.label("No level open");
+                    return;
+                }
+                match self.selection.single() {
+                    Some((kind, idx)) => self.item_fields(ui, kind, idx),
+                    None if self.selection.len() > 1 => self.group_fields(ui),
+                    None => {}
+                }
+                ui.separator();
+                self.level_fields(ui);
+                self.texture_fields(ui);
+                ui.separator();
+                self.warnings(ui);
+            });
+        });
+    }
+
+    fn item_fields(&mut self, ui: &mut Ui, kind: ItemKind, idx: usize) {
+        let Some(level) = self.level.as_mut() else { return; };
+        let Some(before) = Item::get(level, kind, idx) else { return; };
+        let mut after = before.clone();
+        let mut started = false;
+        // Picking from a list is a step of its own rather than part of typing
+        let mut discrete = false;
+
+        ui.heading(format!("{} #{}", kind_name(kind), idx));
+        // Platforms and exits are both rectangles, so one can become the other
+        let mut new_kind = kind;
+        if matches!(kind, ItemKind::Platform | ItemKind::Exit) {
+            ui.horizontal(|ui| {
+                ui.label("Kind");
+                egui::ComboBox::from_id_source("inspector_kind")
+                    .selected_text(kind_name(kind))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut new_kind, ItemKind::Platform, kind_name(ItemKind::Platform));
+                        ui.selectable_value(&mut new_kind, ItemKind::Exit, kind_name(ItemKind::Exit));
+                    });
+            });
+        }
+
+        egui::Grid::new("inspector_item").num_columns(2).show(ui, |ui| {
+            match &mut after {
+                Item::Platform(r) => {
+                    rect_fields(ui, &mut r.x, &mut r.y, &mut r.w, &mut r.h, &mut started);
+                    ui.label("Texture");
+                    egui::ComboBox::from_id_source("inspector_texture")
+                        .selected_text(r.texture.clone().unwrap_or_else(|| "None".to_string()))
+                        .show_ui(ui, |ui| {
+                            discrete |= ui.selectable_value(&mut r.texture, None, "None").changed();
+                            for t in &level.textures {
+                                discrete |= ui.selectable_value(&mut r.texture, Some(t.name.clone()), &t.name).changed();
+                            }
+                        });
+                    ui.end_row();
+                }
+                Item::Exit(e) => {
+                    rect_fields(ui, &mut e.x, &mut e.y, &mut e.w, &mut e.h, &mut started);
+                    ui.label("Next");
+                    ui.vertical(|ui| {
+                        let edit = ui.text_edit_singleline(&mut e.next);
+                        started |= edit.gained_focus();
+                        egui::ComboBox::from_id_source("inspector_next")
+                            .selected_text("Pick a level")
+                            .show_ui(ui, |ui| {
+                                for name in &self.known_levels {
+                                    discrete |= ui.selectable_value(&mut e.next, name.clone(), name).changed();
+                                }
+                            });
+                    });
+                    ui.end_row();
+                }
+                Item::Slope(s) => {
+                    for (i, p) in s.points.iter_mut().enumerate() {
+                        ui.label(format!("Point {}", i + 1));
+                        ui.horizontal(|ui| {
+                            number(ui, &mut p.x, &mut started);
+                            number(ui, &mut p.y, &mut started);
+                        });
+                        ui.end_row();
+                    }
+                    ui.label("Depth");
+                    number(ui, &mut s.depth, &mut started);
+                    ui.end_row();
+                }
+                Item::Decoration(d) => {
+                    rect_fields(ui, &mut d.x, &mut d.y, &mut d.w, &mut d.h, &mut started);
+                    ui.label("Layer");
+                    egui::ComboBox::from_id_source("inspector_layer")
+                        .selected_text(d.layer.clone())
+                        .show_ui(ui, |ui| {
+                            for layer in &level.layers {
+                                discrete |= ui.selectable_value(&mut d.layer, layer.name.clone(), &layer.name).changed();
+                            }
+                        });
+                    ui.end_row();
+                    ui.label("Color");
+                    ui.horizontal(|ui| {
+                        let mut rgba = parse_color(&d.color).unwrap_or([255; 4]);
+                        let mut color = Color32::from_rgba_unmultiplied(rgba[0], rgba[1], rgba[2], rgba[3]);
+                        let picked = ui.color_edit_button_srgba(&mut color);
+                        started |= picked.clicked();
+                        if picked.changed() {
+                            rgba = color.to_srgba_unmultiplied();
+                            d.color = format_color(rgba);
+                        }
+                        started |= ui.add(egui::TextEdit::singleline(&mut d.color).desired_width(80.0)).gained_focus();
+                    });
+                    ui.end_row();
+                    ui.label("Sprite");
+                    let mut sprite = d.sprite.clone().unwrap_or_default();
+                    started |= ui
+                        .add(egui::TextEdit::singleline(&mut sprite).hint_text("none"))
+                        .on_hover_text("Image path in the game's assets folder, stretched over the rectangle")
+                        .gained_focus();
+                    d.sprite = Some(sprite.trim().to_string()).filter(|s| !s.is_empty());
+                    ui.end_row();
+                }
+            }
+        });
+        if kind != ItemKind::Slope && ui.button("Round to whole units").clicked() {
+            after = round_item(after);
+            discrete = true;
+        }
+
+        if started {
+            self.field_gesture = Some(self.history.gesture());
+        }
+        if new_kind != kind {
+            // Removed here, appended to the other list
+            let new_idx = Item::count(level, new_kind);
+            let item = convert(after, new_kind);
+            let cmd = Command::Batch(vec![Command::Delete { idx, item: before }, Command::Insert { idx: new_idx, item }]);
+            self.history.push(level, cmd, None);
+            self.selection = Selection::one(new_kind, new_idx);
+        } else if after != before {
+            let merge = if discrete { None } else { self.field_gesture };
+            self.history.push(level, Command::Replace { idx, before, after }, merge);
+        }
+    }
+
+    fn group_fields(&mut self, ui: &mut Ui) {
+        let Some(level) = self.level.as_mut() else { return; };
+        ui.heading(format!("{} objects", self.selection.len()));
+        let Some(bounds) = selection::bounds(level, &self.selection) else { return; };
+
+        // Editing the center moves everything together
+        let (mut x, mut y) = (bounds.x, bounds.y);
+        let mut started = false;
+        egui::Grid::new("inspector_group").num_columns(2).show(ui, |ui| {
+            ui.label("Center X");
+            number(ui, &mut x, &mut started);
+            ui.end_row();
+            ui.label("Center Y");
+            number(ui, &mut y, &mut started);
+            ui.end_row();
+            ui.label("Size");
+            ui.label(format!("{} x {}", bounds.w, bounds.h));
+            ui.end_row();
+        });
+        if started {
+            self.field_gesture = Some(self.history.gesture());
+        }
+        let delta = vec2(x - bounds.x, y - bounds.y);
+        let mut cmd = None;
+        if delta != egui::Vec2::ZERO {
+            cmd = selection::move_by(level, &self.selection, delta).map(|c| (c, self.field_gesture));
+        }
+
+        ui.label("Align");
+        ui.horizontal_wrapped(|ui| {
+            for (how, label) in [
+                (Align::Left, "Left"),
+                (Align::CenterX, "Center"),
+                (Align::Right, "Right"),
+                (Align::Top, "Top"),
+                (Align::CenterY, "Middle"),
+                (Align::Bottom, "Bottom"),
+            ] {
+                if ui.button(label).clicked() {
+                    cmd = selection::align(level, &self.selection, how).map(|c| (c, None));
+                }
+            }
+        });
+        ui.label("Distribute");
+        ui.horizontal(|ui| {
+            let three = self.selection.len() >= 3;
+            if ui.add_enabled(three, egui::Button::new("Horizontally")).clicked() {
+                cmd = selection::distribute(level, &self.selection, true).map(|c| (c, None));
+            }
+            if ui.add_enabled(three, egui::Button::new("Vertically")).clicked() {
+                cmd = selection::distribute(level, &self.selection, false).map(|c| (c, None));
+            }
+        });
+        if let Some((cmd, merge)) = cmd {
+            self.history.push(level, cmd, merge);
+        }
+    }
+
+    fn level_fields(&mut self, ui: &mut Ui) {
+        let Some(level) = self.level.as_mut() else { return; };
+        ui.heading("Level");
+        let mut meta = level.meta.clone();
+        let mut start = level.start;
+        let mut profile = meta.profile.clone().unwrap_or_default();
+        let (mut meta_started, mut start_started) = (false, false);
+        egui::Grid::new("inspector_level").num_columns(2).show(ui, |ui| {
+            ui.label("Name");
+            meta_started |= ui.text_edit_singleline(&mut meta.name).gained_focus();
+            ui.end_row();
+            ui.label("Profile");
+            meta_started |= ui
+                .add(egui::TextEdit::singleline(&mut profile).hint_text("from config"))
+                .on_hover_text("Movement profile from the game config, e.g. classic, floaty, tight")
+                .gained_focus();
+            ui.end_row();
+            ui.label("Start X");
+            number(ui, &mut start.x, &mut start_started);
+            ui.end_row();
+            ui.label("Start Y");
+            number(ui, &mut start.y, &mut start_started);
+            ui.end_row();
+        });
+        meta.profile = Some(profile.trim().to_string()).filter(|p| !p.is_empty());
+
+        if meta_started || start_started {
+            self.field_gesture = Some(self.history.gesture());
+        }
+        if meta != level.meta {
+            let cmd = Command::SetMeta { before: level.meta.clone(), after: meta };
+            self.history.push(level, cmd, self.field_gesture);
+        }
+        if start != level.start {
+            let cmd = Command::SetStart { before: level.start, after: start };
+            self.history.push(level, cmd, self.field_gesture);
+        }
+    }
+
+    // The level's texture list; a rename applies when the name field loses focus
+    // and carries the platforms using the texture along
+    fn texture_fields(&mut self, ui: &mut Ui) {
+        let Some(level) = self.level.as_mut() else { return; };
+        let before = level.textures.clone();
+        let mut after = before.clone();
+        let mut started = false;
+        let mut rename = None;
+        let mut remove = None;
+        egui::CollapsingHeader::new(format!("Textures ({})", before.len())).id_source("inspector_textures").show(ui, |ui| {
+            for (i, t) in after.iter_mut().enumerate() {
+                egui::Grid::new(("inspector_texture", i)).num_columns(2).show(ui, |ui| {
+                    ui.label("Name");
+                    let id = ui.id().with("name");
+                    let mut name = ui.data_mut(|d| d.get_temp::<String>(id)).unwrap_or_else(|| t.name.clone());
+                    let edit = ui.text_edit_singleline(&mut name);
+                    if edit.lost_focus() && name.trim() != t.name {
+                        rename = Some((t.name.clone(), name.trim().to_string()));
+                    }
+                    if edit.has_focus() {
+                        ui.data_mut(|d| d.insert_temp(id, name));
+                    } else {
+                        ui.data_mut(|d| d.remove::<String>(id));
+                    }
+                    ui.end_row();
+                    ui.label("Image");
+                    started |= ui
+                        .text_edit_singleline(&mut t.path)
+                        .on_hover_text("Path in the game's assets folder")
+                        .gained_focus();
+                    ui.end_row();
+                    ui.label("Mode");
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(&mut t.mode, TextureMode::Stretch, "Stretch");
+                        ui.selectable_value(&mut t.mode, TextureMode::Tile, "Tile");
+                        ui.selectable_value(&mut t.mode, TextureMode::Slice, "Slice");
+                    });
+                    ui.end_row();
+                    if t.mode == TextureMode::Slice {
+                        ui.label("Border").on_hover_text("Width of the fixed edges, in image pixels");
+                        number(ui, &mut t.border, &mut started);
+                        ui.end_row();
+                    }
+                    if t.mode != TextureMode::Stretch {
+                        ui.label("Scale").on_hover_text("World units per image pixel");
+                        number(ui, &mut t.scale, &mut started);
+                        ui.end_row();
+                    }
+                });
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(i);
+                }
+                ui.separator();
+            }
+            if ui.button("Add texture").clicked() {
+                let name = (1..).map(|n| format!("texture{n}")).find(|n| level.texture(n).is_none()).unwrap_or_default();
+                after.push(Texture { name, path: String::new(), mode: TextureMode::Stretch, border: 0.0, scale: 1.0 });
+            }
+        });
+        if let Some(i) = remove {
+            after.remove(i);
+        }
+
+        if started {
+            self.field_gesture = Some(self.history.gesture());
+        }
+        if let Some((from, to)) = rename {
+            if to.is_empty() || level.texture(&to).is_some() {
+                self.status = format!("Can't rename texture '{}' to '{}'", from, to);
+                return;
+            }
+            for t in after.iter_mut().filter(|t| t.name == from) {
+                t.name = to.clone();
+            }
+            let mut cmds = vec![Command::SetTextures { before, after }];
+            for (idx, r) in level.platforms.iter().enumerate().filter(|(_, r)| r.texture.as_deref() == Some(from.as_str())) {
+                let renamed = LRect { texture: Some(to.clone()), ..r.clone() };
+                cmds.push(Command::Replace { idx, before: Item::Platform(r.clone()), after: Item::Platform(renamed) });
+            }
+            self.history.push(level, Command::Batch(cmds), None);
+        } else if after != before {
+            // Adding and removing are steps of their own
+            let merge = if after.len() == before.len() { self.field_gesture } else { None };
+            self.history.push(level, Command::SetTextures { before, after }, merge);
+        }
+    }
+
+    fn warnings(&mut self, ui: &mut Ui) {
+        let Some(level) = &self.level else { return; };
+        let warnings = validate(level, &self.known_levels);
+        if warnings.is_empty() {
+            ui.label("No problems found");
+            return;
+        }
+        ui.heading(format!("Warnings ({})", warnings.len()));
+        for w in warnings {
+            let text = egui::RichText::new(&w.message).color(Color32::from_rgb(255, 190, 60));
+            match w.item {
+                // Clicking selects the object it is about
+                Some((kind, idx)) => {
+                    if ui.link(text).on_hover_text(format!("{} #{}", kind_name(kind), idx)).clicked() {
+                        self.selection = Selection::one(kind, idx);
+                    }
+                }
+                None => {
+                    ui.label(text);
+                }
+            }
+        }
+    }
+}
+
+// X, Y, W, H rows of a center-based rectangle
+fn rect_fields(ui: &mut Ui, x: &mut f32, y: &mut f32, w: &mut f32, h: &mut f32, started: &mut bool) {
+    for (label, value) in [("X", x), ("Y", y), ("Width", w), ("Height", h)] {
+        ui.label(label);
+        number(ui, value, started);
+        ui.end_row();
+    }
+}
+
+// Drag or type a number; `started` is set when a new edit begins
+pub(crate) fn number(ui: &mut Ui, value: &mut f32, started: &mut bool) {
+    let resp = ui.add(egui::DragValue::new(value).speed(1.0).max_decimals(3));
+    *started |= resp.drag_started() || resp.gained_focus();
+}
+
+fn kind_name(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Platform => "Platform",
+        ItemKind::Exit => "Exit",
+        ItemKind::Slope => "Slope",
+        ItemKind::Decoration => "Decoration",
+    }
+}
+
+fn round_item(item: Item) -> Item {
+    match item {
+        Item::Platform(r) => Item::Platform(LRect { x: r.x.round(), y: r.y.round(), w: r.w.round().max(1.0), h: r.h.round().max(1.0), ..r }),
+        Item::Exit(e) => Item::Exit(Exit { x: e.x.round(), y: e.y.round(), w: e.w.round().max(1.0), h: e.h.round().max(1.0), ..e }),
+        Item::Decoration(d) => Item::Decoration(Decoration { x: d.x.round(), y: d.y.round(), w: d.w.round().max(1.0), h: d.h.round().max(1.0), ..d }),
+        slope => slope,
+    }
+}
+
+// The same rectangle as a platform or an exit
+fn convert(item: Item, kind: ItemKind) -> Item {
+    let r = item.bounds();
+    let next = match item {
+        Item::Exit(e) => e.next,
+        _ => String::new(),
+    };
+    match kind {
+        ItemKind::Exit => Item::Exit(Exit { x: r.x, y: r.y, w: r.w, h: r.h, next }),
+        _ => Item::Platform(r),
+    }
+}
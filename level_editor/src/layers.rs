@@ -0,0 +1,237 @@
+// Decoration layers: the panel listing them with editor-only visibility and
+// lock toggles, and the order decorations are drawn and picked in.
+
+use std::collections::BTreeSet;
+
+use eframe::egui::{self, Ui};
+use level_format::{Decoration, Layer, Level};
+
+use crate::history::{Command, Item};
+use crate::selection::Selection;
+use crate::{EditorApp, ItemKind};
+
+// Depth the game gives decorations on a layer that doesn't exist
+const MISSING_LAYER_Z: f32 = -1.0;
+
+// What the editor shows and lets you pick. Layers are keyed by name; None is
+// the level itself (platforms, slopes, exits and the start).
+#[derive(Debug, Default)]
+pub struct LayerView {
+    hidden: BTreeSet<Option<String>>,
+    locked: BTreeSet<Option<String>>,
+    // Layer new decorations are drawn onto
+    pub active: Option<String>,
+    // The active layer's name as it is being typed
+    name_edit: String,
+}
+
+impl LayerView {
+    pub fn visible(&self, level: &Level, kind: ItemKind, idx: usize) -> bool {
+        !self.hidden.contains(&layer_of(level, kind, idx))
+    }
+
+    // Visible and not locked, so it can be picked on the canvas
+    pub fn editable(&self, level: &Level, kind: ItemKind, idx: usize) -> bool {
+        let key = layer_of(level, kind, idx);
+        !self.hidden.contains(&key) && !self.locked.contains(&key)
+    }
+
+    // Forget layers that are gone and keep the active one valid
+    fn retain_existing(&mut self, level: &Level) {
+        let exists = |key: &Option<String>| key.as_deref().is_none_or(|name| level.layer(name).is_some());
+        self.hidden.retain(exists);
+        self.locked.retain(exists);
+        if self.active.as_deref().is_none_or(|name| level.layer(name).is_none()) {
+            self.active = level.layers.first().map(|l| l.name.clone());
+        }
+    }
+
+    fn rename(&mut self, from: &str, to: &str) {
+        for set in [&mut self.hidden, &mut self.locked] {
+            if set.remove(&Some(from.to_string())) {
+                set.insert(Some(to.to_string()));
+            }
+        }
+        if self.active.as_deref() == Some(from) {
+            self.active = Some(to.to_string());
+        }
+        self.name_edit = to.to_string();
+    }
+}
+
+fn layer_of(level: &Level, kind: ItemKind, idx: usize) -> Option<String> {
+    match kind {
+        ItemKind::Decoration => level.decorations.get(idx).map(|d| d.layer.clone()),
+        _ => None,
+    }
+}
+
+pub fn decoration_z(level: &Level, d: &Decoration) -> f32 {
+    level.layer(&d.layer).map_or(MISSING_LAYER_Z, |l| l.z)
+}
+
+// Decoration indices back to front, the way the game stacks them
+pub fn by_depth(level: &Level) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..level.decorations.len()).collect();
+    order.sort_by(|a, b| decoration_z(level, &level.decorations[*a]).total_cmp(&decoration_z(level, &level.decorations[*b])));
+    order
+}
+
+impl EditorApp {
+    pub(crate) fn layer_panel(&mut self, ctx: &egui::Context) {
+        egui::SidePanel::left("layers").resizable(true).default_width(180.0).show(ctx, |ui| {
+            let Some(level) = &self.level else {
+                ui.label("No level open");
+                return;
+            };
+            self.layers.retain_existing(level);
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                ui.heading("Layers");
+                self.layer_list(ui);
+                ui.separator();
+                self.layer_buttons(ui);
+                ui.separator();
+                self.layer_fields(ui);
+                ui.separator();
+                self.tile_panel(ui);
+            });
+        });
+    }
+
+    // Front to back, with the level at depth 0 among them
+    fn layer_list(&mut self, ui: &mut Ui) {
+        let Some(level) = &self.level else { return; };
+        let mut rows: Vec<(f32, Option<String>)> = level.layers.iter().map(|l| (l.z, Some(l.name.clone()))).collect();
+        rows.push((0.0, None));
+        rows.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (_, key) in rows {
+            ui.horizontal(|ui| {
+                let mut visible = !self.layers.hidden.contains(&key);
+                if ui.checkbox(&mut visible, "").on_hover_text("Visible in the editor").changed() {
+                    toggle(&mut self.layers.hidden, &key, !visible);
+                }
+                let mut locked = self.layers.locked.contains(&key);
+                if ui.toggle_value(&mut locked, "🔒").on_hover_text("Locked: can't be picked on the canvas").changed() {
+                    toggle(&mut self.layers.locked, &key, locked);
+                }
+                match &key {
+                    Some(name) => {
+                        let active = self.layers.active.as_ref() == Some(name);
+                        if ui.selectable_label(active, name).on_hover_text("New decorations go on the active layer").clicked() {
+                            self.layers.active = Some(name.clone());
+                        }
+                    }
+                    None => {
+                        ui.label("Level");
+                    }
+                }
+            });
+        }
+        // Hidden or locked things can't stay selected
+        if let Some(level) = &self.level {
+            self.selection.retain(|kind, idx| self.layers.editable(level, kind, idx));
+        }
+    }
+
+    fn layer_buttons(&mut self, ui: &mut Ui) {
+        let Some(level) = self.level.as_mut() else { return; };
+        ui.horizontal(|ui| {
+            if ui.button("Add").clicked() {
+                let name = (1..).map(|n| format!("layer{n}")).find(|n| level.layer(n).is_none()).unwrap_or_default();
+                // Just behind the level, above the background hills
+                let mut after = level.layers.clone();
+                after.push(Layer { name: name.clone(), z: -1.0, parallax: 1.0 });
+                self.history.push(level, Command::SetLayers { before: level.layers.clone(), after }, None);
+                self.layers.active = Some(name);
+            }
+            let active = self.layers.active.clone();
+            let used = level.decorations.iter().filter(|d| Some(&d.layer) == active.as_ref()).count();
+            let delete = ui
+                .add_enabled(active.is_some(), egui::Button::new("Delete"))
+                .on_hover_text(format!("Delete the active layer and its {used} decoration(s)"));
+            if let (true, Some(name)) = (delete.clicked(), active) {
+                // Its decorations go with it, in the same undo step
+                let mut cmds: Vec<Command> = level
+                    .decorations
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .filter(|(_, d)| d.layer == name)
+                    .map(|(idx, d)| Command::Delete { idx, item: Item::Decoration(d.clone()) })
+                    .collect();
+                let after = level.layers.iter().filter(|l| l.name != name).cloned().collect();
+                cmds.push(Command::SetLayers { before: level.layers.clone(), after });
+                self.history.push(level, Command::Batch(cmds), None);
+                self.selection = Selection::default();
+                self.layers.active = None;
+            }
+        });
+    }
+
+    fn layer_fields(&mut self, ui: &mut Ui) {
+        let Some(level) = self.level.as_mut() else { return; };
+        let Some(idx) = self.layers.active.as_deref().and_then(|name| level.layers.iter().position(|l| l.name == name)) else {
+            ui.label("No layer selected");
+            return;
+        };
+        let before = level.layers[idx].clone();
+        let mut layer = before.clone();
+        let mut started = false;
+        egui::Grid::new("layer_fields").num_columns(2).show(ui, |ui| {
+            ui.label("Name");
+            let edit = ui.text_edit_singleline(&mut self.layers.name_edit);
+            if edit.lost_focus() {
+                // Renamed when done typing, if the name is free
+                let name = self.layers.name_edit.trim();
+                if !name.is_empty() && level.layer(name).is_none() {
+                    layer.name = name.to_string();
+                } else if name != before.name {
+                    self.status = format!("There already is a layer named '{name}'");
+                }
+            }
+            if !edit.has_focus() {
+                self.layers.name_edit = layer.name.clone();
+            }
+            ui.end_row();
+            ui.label("Depth");
+            let z = ui
+                .add(egui::DragValue::new(&mut layer.z).speed(0.1).max_decimals(2))
+                .on_hover_text("Game z: below 0 is behind the level, the player is at 1, hills are between -20 and -9");
+            started |= z.drag_started() || z.gained_focus();
+            ui.end_row();
+            ui.label("Parallax");
+            let parallax = ui
+                .add(egui::DragValue::new(&mut layer.parallax).speed(0.01).max_decimals(2))
+                .on_hover_text("1 moves with the level, less is farther away; the editor draws every layer at 1");
+            started |= parallax.drag_started() || parallax.gained_focus();
+            ui.end_row();
+        });
+        if started {
+            self.field_gesture = Some(self.history.gesture());
+        }
+        if layer == before {
+            return;
+        }
+        let mut after = level.layers.clone();
+        after[idx] = layer.clone();
+        let mut cmds = vec![Command::SetLayers { before: level.layers.clone(), after }];
+        if layer.name != before.name {
+            // Decorations follow their layer's new name
+            for (i, d) in level.decorations.iter().enumerate().filter(|(_, d)| d.layer == before.name) {
+                let renamed = Decoration { layer: layer.name.clone(), ..d.clone() };
+                cmds.push(Command::Replace { idx: i, before: Item::Decoration(d.clone()), after: Item::Decoration(renamed) });
+            }
+            self.layers.rename(&before.name, &layer.name);
+        }
+        let (cmd, merge) = if cmds.len() == 1 { (cmds.remove(0), self.field_gesture) } else { (Command::Batch(cmds), None) };
+        self.history.push(level, cmd, merge);
+    }
+}
+
+fn toggle(set: &mut BTreeSet<Option<String>>, key: &Option<String>, on: bool) {
+    if on {
+        set.insert(key.clone());
+    } else {
+        set.remove(key);
+    }
+}
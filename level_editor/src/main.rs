@@ -1,5 +1,19 @@
 use eframe::egui::{self, vec2, Color32, Rect, Rounding, Stroke, Vec2};
-use level_format::{Level, Meta, Rect as LRect, Start};
+use game::resources::PLAYER_SIZE;
+use level_format::{Decoration, Level, Meta, Point, Rect as LRect, Slope, Start, Texture, TextureMode};
+
+mod history;
+mod inspector;
+mod layers;
+mod paths;
+mod playtest;
+mod reach;
+mod selection;
+mod textures;
+mod tiled;
+mod tiles;
+use history::{Command, History, Item, MergeKey};
+use selection::Selection;
 
 #[derive(Debug, Default)]
 struct Camera {
@@ -45,7 +59,7 @@ fn panel_to_cam(screen_panel: Vec2, viewport: Rect) -> Vec2 {
     vec2(screen_panel.x, viewport.height() - screen_panel.y)
 }
 
-fn draw_rect_center_stroked(painter: &egui::Painter, rect: Rect, cam: &Camera, r: LRect, color: Color32, width: f32) {
+fn draw_rect_center_stroked(painter: &egui::Painter, rect: Rect, cam: &Camera, r: &LRect, color: Color32, width: f32) {
     let half = vec2(r.w * 0.5, r.h * 0.5);
     let min = vec2(r.x, r.y) - half;
     let max = vec2(r.x, r.y) + half;
@@ -58,6 +72,36 @@ fn draw_rect_center_stroked(painter: &egui::Painter, rect: Rect, cam: &Camera, r
     painter.rect(rr, Rounding::ZERO, Color32::TRANSPARENT, Stroke { width, color });
 }
 
+fn draw_slope(painter: &egui::Painter, rect: Rect, cam: &Camera, s: &Slope, color: Color32, width: f32) {
+    if s.points.len() < 2 { return; }
+    let to_panel = |w: Vec2| rect.min + cam_to_panel(cam.world_to_screen(w), rect);
+    let top: Vec<egui::Pos2> = s.points.iter().map(|p| to_panel(vec2(p.x, p.y))).collect();
+    let mut outline = top.clone();
+    outline.extend(s.points.iter().rev().map(|p| to_panel(vec2(p.x, p.y - s.depth))));
+    painter.add(egui::Shape::closed_line(outline, Stroke { width: 1.0, color }));
+    // Walkable surface drawn heavier than the rest of the outline
+    painter.add(egui::Shape::line(top, Stroke { width, color }));
+}
+
+// Axis-aligned bounds of a slope's solid band, used for handles and resizing
+fn slope_bounds(s: &Slope) -> LRect {
+    let mut min = vec2(f32::INFINITY, f32::INFINITY);
+    let mut max = vec2(f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for p in &s.points {
+        min.x = min.x.min(p.x); min.y = min.y.min(p.y - s.depth);
+        max.x = max.x.max(p.x); max.y = max.y.max(p.y);
+    }
+    let center = (min + max) * 0.5;
+    LRect::new(center.x, center.y, max.x - min.x, max.y - min.y)
+}
+
+fn point_in_slope(p: Vec2, s: &Slope) -> bool {
+    match s.surface_y_at(p.x) {
+        Some(sy) => p.y <= sy && p.y >= sy - s.depth,
+        None => false,
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum Handle { NW, NE, SW, SE }
 
@@ -77,6 +121,12 @@ impl ResizeState {
             ItemKind::Exit => {
                 let e = &level.exits[idx]; (e.x, e.y, e.w, e.h)
             }
+            ItemKind::Slope => {
+                let b = slope_bounds(&level.slopes[idx]); (b.x, b.y, b.w, b.h)
+            }
+            ItemKind::Decoration => {
+                let d = &level.decorations[idx]; (d.x, d.y, d.w, d.h)
+            }
         };
         let half = vec2(w * 0.5, h * 0.5);
         let min = vec2(cx, cy) - half;
@@ -91,21 +141,33 @@ impl ResizeState {
         Self { kind, idx, anchor_world }
     }
 
-    fn apply(&mut self, level: &mut Level, drag_world: Vec2) {
+    // The item resized so the dragged corner is at `drag_world`
+    fn resized(&self, level: &Level, drag_world: Vec2) -> Option<Item> {
         // New rect from anchor to drag point
         let min = vec2(self.anchor_world.x.min(drag_world.x), self.anchor_world.y.min(drag_world.y));
         let max = vec2(self.anchor_world.x.max(drag_world.x), self.anchor_world.y.max(drag_world.y));
         let center = (min + max) * 0.5;
         let w = (max.x - min.x).max(1.0);
         let h = (max.y - min.y).max(1.0);
-        match self.kind {
-            ItemKind::Platform => {
-                if let Some(r) = level.platforms.get_mut(self.idx) { r.x = center.x; r.y = center.y; r.w = w; r.h = h; }
-            }
-            ItemKind::Exit => {
-                if let Some(e) = level.exits.get_mut(self.idx) { e.x = center.x; e.y = center.y; e.w = w; e.h = h; }
+        let mut item = Item::get(level, self.kind, self.idx)?;
+        match &mut item {
+            Item::Platform(r) => { r.x = center.x; r.y = center.y; r.w = w; r.h = h; }
+            Item::Exit(e) => { e.x = center.x; e.y = center.y; e.w = w; e.h = h; }
+            Item::Decoration(d) => { d.x = center.x; d.y = center.y; d.w = w; d.h = h; }
+            Item::Slope(s) => {
+                // Remap every point from the old bounds into the new ones
+                let old = slope_bounds(s);
+                let old_min = vec2(old.x - old.w * 0.5, old.y - old.h * 0.5);
+                let sx = if old.w > f32::EPSILON { w / old.w } else { 1.0 };
+                let sy = if old.h > f32::EPSILON { h / old.h } else { 1.0 };
+                for p in s.points.iter_mut() {
+                    p.x = min.x + (p.x - old_min.x) * sx;
+                    p.y = min.y + (p.y - old_min.y) * sy;
+                }
+                s.depth *= sy;
             }
         }
+        Some(item)
     }
 }
 
@@ -124,7 +186,7 @@ fn draw_handles(painter: &egui::Painter, rect: Rect, cam: &Camera, r: &LRect) {
     }
 }
 
-fn hit_test_handles(level: &Level, cam: &Camera, viewport: Rect, world: Vec2) -> Option<(ItemKind, usize, Handle)> {
+fn hit_test_handles(level: &Level, view: &layers::LayerView, cam: &Camera, viewport: Rect, world: Vec2) -> Option<(ItemKind, usize, Handle)> {
     // check exits then platforms for selection consistency
     // Use screen-space hit test for small squares
     let handle_hit = |center_world: Vec2, viewport: Rect, cam: &Camera, world: Vec2| -> bool {
@@ -154,11 +216,9 @@ fn hit_test_handles(level: &Level, cam: &Camera, viewport: Rect, world: Vec2) ->
         None
     };
 
-    for (i, e) in level.exits.iter().enumerate().rev() {
-        if let Some(h) = check_rect(e.x, e.y, e.w, e.h) { return Some((ItemKind::Exit, i, h)); }
-    }
-    for (i, r) in level.platforms.iter().enumerate().rev() {
-        if let Some(h) = check_rect(r.x, r.y, r.w, r.h) { return Some((ItemKind::Platform, i, h)); }
+    for (idx, kind) in pick_order(level, view) {
+        let Some(b) = Item::get(level, kind, idx).map(|item| item.bounds()) else { continue; };
+        if let Some(h) = check_rect(b.x, b.y, b.w, b.h) { return Some((kind, idx, h)); }
     }
     None
 }
@@ -181,11 +241,39 @@ struct EditorApp {
     snap_size: f32,
     status: String,
     current_path: Option<std::path::PathBuf>,
+    // Level names exits can lead to, refreshed when opening or saving
+    known_levels: Vec<String>,
     tool: Tool,
     selection: Selection,
     drag_start_world: Option<Vec2>,
+    // Corner where a rubber-band selection started
+    box_select: Option<Vec2>,
+    // Points placed so far with the Slope tool
+    slope_points: Vec<Vec2>,
     resizing: Option<ResizeState>,
+    // Current or last play-test; its trail stays visible after it stops
+    playtest: Option<playtest::PlayTest>,
+    // The game started with "Run game", until it exits
+    game: Option<std::process::Child>,
+    // Jump reachability overlay, recomputed when the level changes
+    show_reach: bool,
+    reach: reach::ReachAnalysis,
+    // Editor-only layer visibility and locks, and the layer being drawn on
+    layers: layers::LayerView,
+    // Images read from the game's assets folder for previews
+    textures: textures::TextureCache,
+    // Tile layer being painted and the brush
+    tile_tool: tiles::TileTool,
+    // What the last Tiled import left out, shown until dismissed
+    import_report: Vec<String>,
     needs_frame: bool,
+    history: History,
+    // Current drag on the canvas, and current edit in the properties panel
+    drag_gesture: Option<MergeKey>,
+    field_gesture: Option<MergeKey>,
+    // Waiting for the user to save or discard unsaved changes
+    pending: Option<PendingAction>,
+    allow_close: bool,
     web_repo_owner: String,
     web_repo_name: String,
     web_token: String,
@@ -198,13 +286,30 @@ impl Default for EditorApp {
             camera: Camera { offset: vec2(0.0, 0.0), zoom: 1.0 },
             snap_enabled: true,
             snap_size: 10.0,
-            status: String::new(),
+            // Said up front, since otherwise every image just shows as missing
+            status: if textures::assets_dir().is_some() { String::new() } else { textures::ASSETS_NOT_FOUND.into() },
             current_path: None,
+            known_levels: inspector::scan_levels(None),
             tool: Tool::Select,
-            selection: Selection::None,
+            selection: Selection::default(),
             drag_start_world: None,
+            box_select: None,
+            slope_points: Vec::new(),
             resizing: None,
+            playtest: None,
+            game: None,
+            show_reach: false,
+            reach: reach::ReachAnalysis::default(),
+            layers: layers::LayerView::default(),
+            textures: textures::TextureCache::default(),
+            tile_tool: tiles::TileTool::default(),
+            import_report: Vec::new(),
             needs_frame: false,
+            history: History::default(),
+            drag_gesture: None,
+            field_gesture: None,
+            pending: None,
+            allow_close: false,
             web_repo_owner: String::new(),
             web_repo_name: String::new(),
             web_token: String::new(),
@@ -212,112 +317,338 @@ impl Default for EditorApp {
     }
 }
 
+// What to do once unsaved changes have been dealt with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PendingAction { New, Open, Close }
+
+impl EditorApp {
+    fn new_level(&mut self) {
+        self.level = Some(Level {
+            meta: Meta { name: "untitled".to_string(), profile: None },
+            start: Start { x: 0.0, y: 0.0 },
+            platforms: vec![],
+            exits: vec![],
+            slopes: vec![],
+            ..Default::default()
+        });
+        self.status = "Created new level".into();
+        self.current_path = None;
+        self.selection = Selection::default();
+        self.history.reset();
+        self.needs_frame = true;
+    }
+
+    fn open_level(&mut self) {
+        let dialog = rfd::FileDialog::new()
+            .add_filter("Levels and Tiled maps", &["toml", "tmx", "tmj", "json"])
+            .add_filter("TOML", &["toml"])
+            .add_filter("Tiled map", &["tmx", "tmj", "json"]);
+        if let Some(path) = dialog.pick_file() {
+            if tiled::is_tiled_map(&path) {
+                self.import_map(&path);
+                return;
+            }
+            match std::fs::read_to_string(&path) {
+                Ok(contents) => match Level::from_toml_str(&contents) {
+                    Ok(level) => {
+                        self.level = Some(level);
+                        self.status = format!("Opened {}", path.display());
+                        self.current_path = Some(path.clone());
+                        self.known_levels = inspector::scan_levels(Some(&path));
+                        self.selection = Selection::default();
+                        self.history.reset();
+                        self.textures.reload();
+                        self.needs_frame = true;
+                    }
+                    Err(e) => self.status = format!("Failed to parse: {e}"),
+                },
+                Err(e) => self.status = format!("Failed to read: {e}"),
+            }
+        }
+    }
+
+    // A Tiled map as a new, unsaved level; saving asks where to put it
+    fn import_map(&mut self, path: &std::path::Path) {
+        match tiled::import(path) {
+            Ok(import) => {
+                self.level = Some(import.level);
+                self.status = match import.skipped.len() {
+                    0 => format!("Imported {}", path.display()),
+                    n => format!("Imported {}, {} kind(s) of things left out", path.display(), n),
+                };
+                self.import_report = import.skipped;
+                self.current_path = None;
+                self.known_levels = inspector::scan_levels(None);
+                self.selection = Selection::default();
+                self.history.reset();
+                self.history.mark_unsaved();
+                self.textures.reload();
+                self.needs_frame = true;
+            }
+            Err(e) => self.status = format!("Failed to import: {e}"),
+        }
+    }
+
+    fn import_report_window(&mut self, ctx: &egui::Context) {
+        if self.import_report.is_empty() {
+            return;
+        }
+        let mut open = true;
+        egui::Window::new("Left out of the import").open(&mut open).default_width(420.0).show(ctx, |ui| {
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for line in &self.import_report {
+                    ui.label(line);
+                }
+            });
+        });
+        if !open {
+            self.import_report.clear();
+        }
+    }
+
+    // Save to the current file, or ask for one; returns whether the level was written
+    fn save(&mut self, save_as: bool) -> bool {
+        let Some(level) = &self.level else {
+            self.status = "No level to save".into();
+            return false;
+        };
+        let toml = match level.to_toml_string_pretty() {
+            Ok(s) => s,
+            Err(e) => {
+                self.status = format!("Serialize error: {e}");
+                return false;
+            }
+        };
+        let path = match (&self.current_path, save_as) {
+            (Some(path), false) => path.clone(),
+            _ => match rfd::FileDialog::new().add_filter("TOML", &["toml"]).save_file() {
+                Some(path) => path,
+                None => return false,
+            },
+        };
+        if let Err(e) = std::fs::write(&path, toml) {
+            self.status = format!("Failed to save: {e}");
+            return false;
+        }
+        self.status = format!("Saved {}", path.display());
+        self.known_levels = inspector::scan_levels(Some(&path));
+        self.current_path = Some(path);
+        self.history.mark_saved();
+        true
+    }
+
+    // Run `action` now, or ask first when it would lose unsaved changes
+    fn request(&mut self, action: PendingAction, ctx: &egui::Context) {
+        if self.history.is_dirty() {
+            self.pending = Some(action);
+        } else {
+            self.run(action, ctx);
+        }
+    }
+
+    fn run(&mut self, action: PendingAction, ctx: &egui::Context) {
+        match action {
+            PendingAction::New => self.new_level(),
+            PendingAction::Open => self.open_level(),
+            PendingAction::Close => {
+                self.allow_close = true;
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            }
+        }
+    }
+
+    fn unsaved_changes_dialog(&mut self, ctx: &egui::Context) {
+        let Some(action) = self.pending else { return; };
+        egui::Window::new("Unsaved changes")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, vec2(0.0, 0.0))
+            .show(ctx, |ui| {
+                ui.label("The level has changes that haven't been saved.");
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        self.pending = None;
+                        if self.save(false) {
+                            self.run(action, ctx);
+                        }
+                    }
+                    if ui.button("Discard").clicked() {
+                        self.pending = None;
+                        self.run(action, ctx);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.pending = None;
+                    }
+                });
+            });
+    }
+
+    fn undo(&mut self) {
+        if let Some(level) = self.level.as_mut() {
+            if self.history.undo(level) {
+                self.status = "Undo".into();
+            }
+        }
+        self.after_history_step();
+    }
+
+    fn redo(&mut self) {
+        if let Some(level) = self.level.as_mut() {
+            if self.history.redo(level) {
+                self.status = "Redo".into();
+            }
+        }
+        self.after_history_step();
+    }
+
+    // Play the in-memory level from `from`, or from its start point
+    fn start_playtest(&mut self, from: Option<Vec2>) {
+        let Some(level) = &self.level else { return; };
+        match playtest::PlayTest::start(level, from) {
+            Ok(pt) => {
+                self.playtest = Some(pt);
+                self.status = "Play-testing: A/D or arrows to run, Space to jump, Esc to stop".into();
+            }
+            Err(e) => self.status = format!("Can't play-test: {e}"),
+        }
+    }
+
+    // Save, then run the game executable built next to the editor's on this level
+    fn run_game(&mut self) {
+        if self.current_path.is_none() {
+            self.status = "Open or save the level first to get a path".into();
+            return;
+        }
+        if !self.save(false) {
+            return;
+        }
+        let Some(path) = self.current_path.clone() else { return; };
+        let exe = std::env::current_exe()
+            .ok()
+            .and_then(|e| e.parent().map(|dir| dir.join(format!("game{}", std::env::consts::EXE_SUFFIX))))
+            .filter(|p| p.is_file());
+        let Some(exe) = exe else {
+            self.status = "No game executable next to the editor; build it with `cargo build -p game`".into();
+            return;
+        };
+        self.status = match std::process::Command::new(&exe).arg("--level").arg(&path).spawn() {
+            Ok(child) => {
+                self.game = Some(child);
+                "Launched game".into()
+            }
+            Err(e) => format!("Failed to run {}: {e}", exe.display()),
+        };
+    }
+
+    // Report how the game started with "Run game" ended, if it failed
+    fn poll_game(&mut self, ctx: &egui::Context) {
+        let Some(child) = &mut self.game else { return; };
+        match child.try_wait() {
+            Ok(None) => ctx.request_repaint_after(std::time::Duration::from_millis(500)),
+            Ok(Some(status)) => {
+                if !status.success() {
+                    self.status = format!("The game exited with {status} (see the terminal for its output)");
+                }
+                self.game = None;
+            }
+            Err(e) => {
+                self.status = format!("Lost track of the game: {e}");
+                self.game = None;
+            }
+        }
+    }
+
+    fn playing(&self) -> bool {
+        self.playtest.as_ref().is_some_and(|pt| pt.running)
+    }
+
+    // The selected item may be gone after undo/redo, and a drag in progress no
+    // longer matches the level
+    fn after_history_step(&mut self) {
+        if let Some(level) = &self.level {
+            self.selection.retain_existing(level);
+            self.tile_tool.retain_existing(level);
+        }
+        self.tile_tool.end_stroke();
+        self.resizing = None;
+        self.drag_gesture = None;
+        self.field_gesture = None;
+    }
+}
+
 impl eframe::App for EditorApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Closing the window with unsaved changes asks first
+        if ctx.input(|i| i.viewport().close_requested()) && self.history.is_dirty() && !self.allow_close {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.pending = Some(PendingAction::Close);
+        }
+        self.unsaved_changes_dialog(ctx);
+        self.import_report_window(ctx);
+        self.poll_game(ctx);
+
+        // A running play-test takes the keyboard
+        if let Some(message) = self.playtest.as_mut().and_then(|pt| pt.update(ctx)) {
+            self.status = message;
+        }
+
+        // Undo/redo, unless a text field is taking the keys (it has its own undo)
+        if !ctx.wants_keyboard_input() && !self.playing() {
+            let (redo, undo) = ctx.input_mut(|i| {
+                let redo = i.consume_key(egui::Modifiers::COMMAND | egui::Modifiers::SHIFT, egui::Key::Z)
+                    || i.consume_key(egui::Modifiers::COMMAND, egui::Key::Y);
+                (redo, i.consume_key(egui::Modifiers::COMMAND, egui::Key::Z))
+            });
+            if redo { self.redo(); }
+            if undo { self.undo(); }
+        }
+
         // Top menu bar
         egui::TopBottomPanel::top("top_bar").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 if ui.button("New").clicked() {
-                    self.level = Some(Level {
-                        meta: Meta { name: "untitled".to_string() },
-                        start: Start { x: 0.0, y: 0.0 },
-                        platforms: vec![],
-                        exits: vec![],
-                    });
-                    self.status = "Created new level".into();
-                    self.current_path = None;
-                    self.selection = Selection::None;
-                    self.needs_frame = true;
+                    self.request(PendingAction::New, ctx);
                 }
-                if ui.button("Play").on_hover_text("Run game with this level").clicked() {
-                    if let (Some(level), Some(path)) = (&self.level, &self.current_path) {
-                        // Save first to ensure latest edits are on disk
-                        if let Ok(toml) = level.to_toml_string_pretty() {
-                            let _ = std::fs::write(path, toml);
-                        }
-                        // Spawn the game as a child process with --level <path>
-                        let _ = std::process::Command::new("cargo")
-                            .arg("run").arg("-p").arg("game")
-                            .arg("--")
-                            .arg("--level")
-                            .arg(path.to_string_lossy().to_string())
-                            .spawn();
-                        self.status = "Launched game".into();
-                    } else {
-                        self.status = "Open or save the level first to get a path".into();
+                if self.playing() {
+                    if ui.button("Stop").on_hover_text("Esc").clicked() {
+                        self.playtest = None;
+                        self.status = "Stopped play-test".into();
                     }
+                } else if ui
+                    .add_enabled(self.level.is_some(), egui::Button::new("Test"))
+                    .on_hover_text("Play-test here from the start point (F5), or from the mouse cursor (F6)")
+                    .clicked()
+                {
+                    self.start_playtest(None);
+                }
+                if ui.button("Run game").on_hover_text("Save and run the game with this level").clicked() {
+                    self.run_game();
                 }
                 if ui.button("Open").clicked() {
-                    if let Some(path) = rfd::FileDialog::new().add_filter("TOML", &["toml"]).pick_file() {
-                        match std::fs::read_to_string(&path) {
-                            Ok(contents) => match Level::from_toml_str(&contents) {
-                                Ok(level) => {
-                                    self.level = Some(level);
-                                    self.status = format!("Opened {}", path.display());
-                                    self.current_path = Some(path.clone());
-                                    self.selection = Selection::None;
-                                    self.needs_frame = true;
-                                }
-                                Err(e) => self.status = format!("Failed to parse: {e}"),
-                            },
-                            Err(e) => self.status = format!("Failed to read: {e}"),
-                        }
-                    }
+                    self.request(PendingAction::Open, ctx);
                 }
                 if ui.button("Frame").on_hover_text("Fit content to view").clicked() {
                     self.needs_frame = true;
                 }
                 if ui.button("Save").clicked() {
-                    if let Some(level) = &self.level {
-                        // Enforce start exists (always present in model). Additional guard in case of None level.
-                        let toml = match level.to_toml_string_pretty() {
-                            Ok(s) => s,
-                            Err(e) => {
-                                self.status = format!("Serialize error: {e}");
-                                String::new()
-                            }
-                        };
-                        if !toml.is_empty() {
-                            if let Some(path) = &self.current_path {
-                                if let Err(e) = std::fs::write(path, toml) {
-                                    self.status = format!("Failed to save: {e}");
-                                } else {
-                                    self.status = format!("Saved {}", path.display());
-                                }
-                            } else {
-                                if let Some(path) = rfd::FileDialog::new().add_filter("TOML", &["toml"]).save_file() {
-                                    if let Err(e) = std::fs::write(&path, toml) {
-                                        self.status = format!("Failed to save: {e}");
-                                    } else {
-                                        self.current_path = Some(path.clone());
-                                        self.status = format!("Saved {}", path.display());
-                                    }
-                                }
-                            }
-                        }
-                    } else {
-                        self.status = "No level to save".into();
-                    }
+                    self.save(false);
                 }
                 if ui.button("Save As").clicked() {
-                    if let Some(level) = &self.level {
-                        if let Some(path) = rfd::FileDialog::new().add_filter("TOML", &["toml"]).save_file() {
-                            // Enforce single start exists: Level always has one, so just proceed
-                            match level.to_toml_string_pretty() {
-                                Ok(s) => {
-                                    if let Err(e) = std::fs::write(&path, s) {
-                                        self.status = format!("Failed to save: {e}");
-                                    } else {
-                                        self.current_path = Some(path.clone());
-                                        self.status = format!("Saved {}", path.display());
-                                    }
-                                }
-                                Err(e) => self.status = format!("Serialize error: {e}"),
-                            }
-                        }
-                    } else {
-                        self.status = "No level to save".into();
-                    }
+                    self.save(true);
+                }
+                ui.separator();
+                if ui.add_enabled(self.history.can_undo(), egui::Button::new("Undo")).on_hover_text("Ctrl+Z").clicked() {
+                    self.undo();
+                }
+                if ui.add_enabled(self.history.can_redo(), egui::Button::new("Redo")).on_hover_text("Ctrl+Shift+Z").clicked() {
+                    self.redo();
+                }
+                let reach_toggle = ui
+                    .toggle_value(&mut self.show_reach, "Reach")
+                    .on_hover_text("Show jump arcs from the selected platform (or the start) and what can't be reached");
+                if reach_toggle.clicked() && self.show_reach {
+                    // Start over with the current config.toml
+                    self.reach = reach::ReachAnalysis::default();
                 }
                 ui.separator();
                 ui.toggle_value(&mut self.snap_enabled, "Snap (10px)");
@@ -327,10 +658,19 @@ impl eframe::App for EditorApp {
                 ui.selectable_value(&mut self.tool, Tool::Select, "Select");
                 ui.selectable_value(&mut self.tool, Tool::Platform, "Platform");
                 ui.selectable_value(&mut self.tool, Tool::Exit, "Exit");
+                ui.selectable_value(&mut self.tool, Tool::Slope, "Slope")
+                    .on_hover_text("Click to add points, Enter or double-click to finish, Esc to cancel");
+                ui.selectable_value(&mut self.tool, Tool::Decoration, "Decoration")
+                    .on_hover_text("Drag a rectangle onto the active layer");
                 ui.selectable_value(&mut self.tool, Tool::Start, "Start");
+                ui.selectable_value(&mut self.tool, Tool::Tiles, "Tiles")
+                    .on_hover_text("Paint, erase or fill cells of the active tile layer");
             });
         });
 
+        self.layer_panel(ctx);
+        self.inspector(ctx);
+
         // Central canvas
         egui::CentralPanel::default().show(ctx, |ui| {
             let available = ui.available_rect_before_wrap();
@@ -359,6 +699,16 @@ impl eframe::App for EditorApp {
                         min_w.x = min_w.x.min(rmin.x); min_w.y = min_w.y.min(rmin.y);
                         max_w.x = max_w.x.max(rmax.x); max_w.y = max_w.y.max(rmax.y);
                     }
+                    let slope_rects = level.slopes.iter().map(slope_bounds);
+                    let decoration_rects = level.decorations.iter().map(|d| LRect::new(d.x, d.y, d.w, d.h));
+                    let tile_rects = level.tile_layers.iter().map(|l| l.bounds());
+                    for b in slope_rects.chain(decoration_rects).chain(tile_rects) {
+                        let half = vec2(b.w * 0.5, b.h * 0.5);
+                        let rmin = vec2(b.x, b.y) - half;
+                        let rmax = vec2(b.x, b.y) + half;
+                        min_w.x = min_w.x.min(rmin.x); min_w.y = min_w.y.min(rmin.y);
+                        max_w.x = max_w.x.max(rmax.x); max_w.y = max_w.y.max(rmax.y);
+                    }
                     let have_any = min_w.x.is_finite();
                     let viewport_size = available.size();
                     if have_any {
@@ -422,129 +772,244 @@ impl eframe::App for EditorApp {
             draw_axes(&painter, available, &self.camera);
 
             // Draw level geometry
-            if let Some(level) = &self.level {
+            self.draw_decorations(&painter, available, false);
+            if let Some(level) = self.level.as_ref().filter(|l| self.layers.visible(l, ItemKind::Platform, 0)) {
+                // Tile layers, under the platforms
+                for layer in &level.tile_layers {
+                    let cell_rect = |c, r| {
+                        let cell = layer.cells_rect(c, r, 1, 1);
+                        center_rect_to_panel(available, &self.camera, cell.x, cell.y, cell.w, cell.h)
+                    };
+                    self.textures.draw_tiles(&painter, layer, level.tileset(&layer.tileset), cell_rect);
+                }
                 // Platforms
                 for (i, r) in level.platforms.iter().enumerate() {
                     let color = Color32::from_rgb(80, 160, 255);
-                    let selected = matches!(self.selection, Selection::Item(ItemKind::Platform, si) if si == i);
+                    let selected = self.selection.contains(ItemKind::Platform, i);
                     let stroke_w = if selected { 3.0 } else { 2.0 };
-                    draw_rect_center_stroked(&painter, available, &self.camera, *r, color, stroke_w);
-                    if selected { draw_handles(&painter, available, &self.camera, r); }
+                    if let Some(texture) = r.texture.as_deref().and_then(|name| level.texture(name)) {
+                        let dest = center_rect_to_panel(available, &self.camera, r.x, r.y, r.w, r.h);
+                        self.textures.draw(&painter, texture, dest, self.camera.zoom, Color32::WHITE);
+                    }
+                    draw_rect_center_stroked(&painter, available, &self.camera, r, color, stroke_w);
+                    if self.selection.single() == Some((ItemKind::Platform, i)) { draw_handles(&painter, available, &self.camera, r); }
+                }
+                // Slopes (same blue as platforms)
+                for (i, s) in level.slopes.iter().enumerate() {
+                    let selected = self.selection.contains(ItemKind::Slope, i);
+                    let stroke_w = if selected { 3.0 } else { 2.0 };
+                    draw_slope(&painter, available, &self.camera, s, Color32::from_rgb(80, 160, 255), stroke_w);
+                    if self.selection.single() == Some((ItemKind::Slope, i)) { draw_handles(&painter, available, &self.camera, &slope_bounds(s)); }
                 }
                 // Exits (orange)
                 for (i, e) in level.exits.iter().enumerate() {
-                    let r = LRect { x: e.x, y: e.y, w: e.w, h: e.h };
-                    let selected = matches!(self.selection, Selection::Item(ItemKind::Exit, si) if si == i);
+                    let r = LRect::new(e.x, e.y, e.w, e.h);
+                    let selected = self.selection.contains(ItemKind::Exit, i);
                     let stroke_w = if selected { 3.0 } else { 2.0 };
-                    draw_rect_center_stroked(&painter, available, &self.camera, r, Color32::from_rgb(255, 160, 40), stroke_w);
-                    if selected { draw_handles(&painter, available, &self.camera, &r); }
+                    draw_rect_center_stroked(&painter, available, &self.camera, &r, Color32::from_rgb(255, 160, 40), stroke_w);
+                    if self.selection.single() == Some((ItemKind::Exit, i)) { draw_handles(&painter, available, &self.camera, &r); }
                 }
                 // Start marker (green cross)
                 let start = vec2(level.start.x, level.start.y);
                 draw_cross(&painter, available, &self.camera, start, 10.0, Color32::from_rgb(80, 220, 120));
             }
+            self.draw_decorations(&painter, available, true);
+
+            // Reachability, not recomputed in the middle of a drag
+            if let Some(level) = self.level.as_ref().filter(|_| self.show_reach) {
+                let idle = !ui.input(|i| i.pointer.any_down());
+                self.reach.update(ui.ctx(), level, idle);
+                if let Some(r) = self.reach.overlay.as_mut() {
+                    r.draw(&painter, available, &self.camera, self.selection.single());
+                }
+            }
 
             // Handle interactions per tool
-            if let Some(level) = self.level.as_mut() {
+            // Keys go to a focused text field instead
+            let keys_free = !ui.ctx().wants_keyboard_input();
+
+            // Play-test: start keys, the trail of the last run and the player
+            let hover_world = ui.input(|i| i.pointer.hover_pos())
+                .filter(|p| available.contains(*p))
+                .map(|p| self.camera.screen_to_world(panel_to_cam(p - available.min, available)));
+            if keys_free && !self.playing() {
+                if ui.input(|i| i.key_pressed(egui::Key::F5)) {
+                    self.start_playtest(None);
+                } else if let Some(at) = hover_world.filter(|_| ui.input(|i| i.key_pressed(egui::Key::F6))) {
+                    self.start_playtest(Some(at));
+                }
+            }
+            if let Some(pt) = &self.playtest {
+                let to_panel = |w: Vec2| available.min + cam_to_panel(self.camera.world_to_screen(w), available);
+                let trail: Vec<egui::Pos2> = pt.trail.iter().map(|p| to_panel(*p)).collect();
+                painter.add(egui::Shape::line(trail, Stroke { width: 2.0, color: Color32::from_rgb(230, 80, 200) }));
+                // First frame of the game's sheet, stretched over the collision box like in the game
+                let dest = center_rect_to_panel(available, &self.camera, pt.player_pos().x, pt.player_pos().y, PLAYER_SIZE.x, PLAYER_SIZE.y);
+                let grid = [pt.sheet.columns, pt.sheet.rows];
+                self.textures.draw_frame(&painter, &pt.sheet.sheet, grid, 0, pt.facing_left, dest);
+                let player = LRect::new(pt.player_pos().x, pt.player_pos().y, PLAYER_SIZE.x, PLAYER_SIZE.y);
+                draw_rect_center(&painter, available, &self.camera, player, Color32::from_rgb(240, 240, 120));
+            }
+            if let Some(pt) = self.playtest.as_ref().filter(|pt| pt.running) {
+                // Keep the player in the middle of the view
+                let viewport_center_cam = panel_to_cam(available.size() * 0.5, available);
+                self.camera.offset = pt.player_pos() - viewport_center_cam / self.camera.zoom;
+            }
+
+            if response.drag_started() {
+                self.drag_gesture = Some(self.history.gesture());
+            }
+            let gesture = self.drag_gesture;
+            // No editing while the level is being played
+            if let Some(level) = self.level.as_mut().filter(|_| !self.playtest.as_ref().is_some_and(|pt| pt.running)) {
                 let ctrl_down = ui.input(|i| i.modifiers.ctrl);
                 let snap_now = self.snap_enabled && !ctrl_down;
 
-                // Delete key removes selected item
-                if ui.input(|i| i.key_pressed(egui::Key::Delete) || i.key_pressed(egui::Key::Backspace)) {
-                    if let Selection::Item(kind, idx) = self.selection {
-                        match kind {
-                            ItemKind::Platform => { if idx < level.platforms.len() { level.platforms.remove(idx); } }
-                            ItemKind::Exit => { if idx < level.exits.len() { level.exits.remove(idx); } }
-                        }
-                        self.selection = Selection::None;
+                // Delete key removes the selected items
+                if keys_free && !self.selection.is_empty() && ui.input(|i| i.key_pressed(egui::Key::Delete) || i.key_pressed(egui::Key::Backspace)) {
+                    if let Some(cmd) = selection::delete(level, &self.selection) {
+                        self.history.push(level, cmd, None);
+                    }
+                    self.selection = Selection::default();
+                }
+
+                // Duplicate the selected items (Cmd/Ctrl + D)
+                if keys_free && ui.input(|i| i.modifiers.command && i.key_pressed(egui::Key::D)) {
+                    if let Some((cmd, copies)) = selection::duplicate(level, &self.selection, vec2(10.0, -10.0)) {
+                        self.history.push(level, cmd, None);
+                        self.selection = copies;
                     }
                 }
 
-                // Duplicate selected item (Cmd/Ctrl + D)
-                if ui.input(|i| i.modifiers.command && i.key_pressed(egui::Key::D)) {
-                    if let Selection::Item(kind, idx) = self.selection {
-                        match kind {
-                            ItemKind::Platform => {
-                                if let Some(r) = level.platforms.get(idx).cloned() {
-                                    level.platforms.push(LRect { x: r.x + 10.0, y: r.y - 10.0, ..r });
+                // Clipboard: the selection as a level_format fragment in TOML
+                if keys_free {
+                    for event in ui.input(|i| i.events.clone()) {
+                        match event {
+                            egui::Event::Copy | egui::Event::Cut => {
+                                if let Some(text) = selection::copy(level, &self.selection) {
+                                    ui.ctx().copy_text(text);
+                                    self.status = format!("Copied {} item(s)", self.selection.len());
                                 }
-                            }
-                            ItemKind::Exit => {
-                                if let Some(e) = level.exits.get(idx).cloned() {
-                                    level.exits.push(level_format::Exit { x: e.x + 10.0, y: e.y - 10.0, ..e });
+                                if event == egui::Event::Cut {
+                                    if let Some(cmd) = selection::delete(level, &self.selection) {
+                                        self.history.push(level, cmd, None);
+                                    }
+                                    self.selection = Selection::default();
                                 }
                             }
+                            egui::Event::Paste(text) => match selection::paste(level, &text, hover_world) {
+                                Ok((cmd, pasted)) => {
+                                    self.history.push(level, cmd, None);
+                                    self.status = format!("Pasted {} item(s)", pasted.len());
+                                    self.selection = pasted;
+                                }
+                                Err(e) => self.status = e,
+                            },
+                            _ => {}
                         }
                     }
                 }
 
                 match self.tool {
                     Tool::Select => {
-                        // Simple: click on rect to select, drag to move, handles to resize
+                        let shift = ui.input(|i| i.modifiers.shift);
+                        let world_at = |p: egui::Pos2| self.camera.screen_to_world(panel_to_cam(p - available.min, available));
+                        // Click selects; Shift+click adds or removes one item
                         if response.clicked_by(egui::PointerButton::Primary) {
                             let mouse = ui.input(|i| i.pointer.interact_pos()).unwrap_or(available.center());
-                            let cam_pt = panel_to_cam(mouse - available.min, available);
-                            let world = self.camera.screen_to_world(cam_pt);
+                            let world = world_at(mouse);
+                            let handle = if shift { None } else { hit_test_handles(level, &self.layers, &self.camera, available, world) };
                             // Test resize handles first
-                            if let Some((kind, idx, handle)) = hit_test_handles(level, &self.camera, available, world) {
-                                self.selection = Selection::Item(kind, idx);
+                            if let Some((kind, idx, handle)) = handle {
+                                self.selection = Selection::one(kind, idx);
                                 self.resizing = Some(ResizeState::start(level, kind, idx, handle));
                             } else {
-                                // hit test exits then platforms
-                                if let Some((idx, kind)) = hit_test_level(level, &self.camera, available, world) {
-                                    self.selection = Selection::Item(kind, idx);
-                                } else {
-                                    self.selection = Selection::None;
+                                match hit_test_level(level, &self.layers, &self.camera, available, world) {
+                                    Some((idx, kind)) if shift => self.selection.toggle(kind, idx),
+                                    Some((idx, kind)) => self.selection = Selection::one(kind, idx),
+                                    None if shift => {}
+                                    None => self.selection = Selection::default(),
                                 }
                             }
                         }
 
-                        // Drag to move selected item
-                        if let Selection::Item(kind, idx) = self.selection {
-                            if response.dragged() && !is_panning(&ui, &response) && self.resizing.is_none() {
-                                let delta_panel = response.drag_delta();
-                                // Convert panel-space delta (Y down) to camera/world delta (Y up)
-                                let mut delta_world = vec2(delta_panel.x, -delta_panel.y) / self.camera.zoom;
-                                // Constrain axis with Shift: zero out smaller magnitude
-                                if ui.input(|i| i.modifiers.shift) {
-                                    if delta_world.x.abs() > delta_world.y.abs() { delta_world.y = 0.0; } else { delta_world.x = 0.0; }
+                        // A drag from a handle resizes, from an item moves the selection
+                        // (selecting the item first if needed), from empty space draws a
+                        // selection box
+                        if response.drag_started() && !is_panning(ui, &response) {
+                            let origin = ui.input(|i| i.pointer.press_origin()).unwrap_or(available.center());
+                            let world = world_at(origin);
+                            if let Some((kind, idx, handle)) = hit_test_handles(level, &self.layers, &self.camera, available, world).filter(|_| !shift) {
+                                self.selection = Selection::one(kind, idx);
+                                self.resizing = Some(ResizeState::start(level, kind, idx, handle));
+                            } else if let Some((idx, kind)) = hit_test_level(level, &self.layers, &self.camera, available, world) {
+                                if !self.selection.contains(kind, idx) {
+                                    if !shift { self.selection = Selection::default(); }
+                                    self.selection.add(kind, idx);
                                 }
-                                if snap_now {
-                                    delta_world.x = snap_value(delta_world.x, self.snap_size);
-                                    delta_world.y = snap_value(delta_world.y, self.snap_size);
+                            } else {
+                                self.box_select = Some(world);
+                            }
+                        }
+
+                        if let Some(corner) = self.box_select {
+                            let mouse = ui.input(|i| i.pointer.hover_pos()).unwrap_or(available.center());
+                            let world = world_at(mouse);
+                            let a = available.min + cam_to_panel(self.camera.world_to_screen(corner), available);
+                            let b = available.min + cam_to_panel(self.camera.world_to_screen(world), available);
+                            painter.rect(
+                                Rect::from_two_pos(a, b),
+                                Rounding::ZERO,
+                                Color32::from_rgba_unmultiplied(120, 170, 255, 30),
+                                Stroke { width: 1.0, color: Color32::from_rgb(120, 170, 255) },
+                            );
+                            if response.drag_stopped() {
+                                if !shift { self.selection = Selection::default(); }
+                                let picked = selection::in_box(level, corner, world).into_iter().filter(|(k, i)| self.layers.editable(level, *k, *i));
+                                for (kind, idx) in picked {
+                                    self.selection.add(kind, idx);
                                 }
-                                match kind {
-                                    ItemKind::Platform => {
-                                        if let Some(r) = level.platforms.get_mut(idx) {
-                                            r.x += delta_world.x;
-                                            r.y += delta_world.y;
-                                        }
-                                    }
-                                    ItemKind::Exit => {
-                                        if let Some(e) = level.exits.get_mut(idx) {
-                                            e.x += delta_world.x;
-                                            e.y += delta_world.y;
-                                        }
-                                    }
+                                self.box_select = None;
+                            }
+                        } else if response.dragged() && !is_panning(ui, &response) && self.resizing.is_none() {
+                            // Move the whole selection; the whole drag is one undo step
+                            let delta_panel = response.drag_delta();
+                            // Convert panel-space delta (Y down) to camera/world delta (Y up)
+                            let mut delta_world = vec2(delta_panel.x, -delta_panel.y) / self.camera.zoom;
+                            // Constrain axis with Shift: zero out smaller magnitude
+                            if shift {
+                                if delta_world.x.abs() > delta_world.y.abs() { delta_world.y = 0.0; } else { delta_world.x = 0.0; }
+                            }
+                            if snap_now {
+                                delta_world.x = snap_value(delta_world.x, self.snap_size);
+                                delta_world.y = snap_value(delta_world.y, self.snap_size);
+                            }
+                            if delta_world != Vec2::ZERO {
+                                if let Some(cmd) = selection::move_by(level, &self.selection, delta_world) {
+                                    self.history.push(level, cmd, gesture);
                                 }
                             }
                         }
 
                         // Resizing if active
-                        if let Some(state) = &mut self.resizing {
+                        if let Some(state) = &self.resizing {
                             if response.dragged() {
                                 let mouse = ui.input(|i| i.pointer.hover_pos()).unwrap_or(available.center());
-                                let cam_pt = panel_to_cam(mouse - available.min, available);
-                                let mut world = self.camera.screen_to_world(cam_pt);
+                                let mut world = world_at(mouse);
                                 if snap_now { world = snap_vec2(world, self.snap_size); }
-                                state.apply(level, world);
+                                let before = Item::get(level, state.kind, state.idx);
+                                if let (Some(before), Some(after)) = (before, state.resized(level, world)) {
+                                    if before != after {
+                                        self.history.push(level, Command::Replace { idx: state.idx, before, after }, gesture);
+                                    }
+                                }
                             }
                             if response.drag_stopped() {
                                 self.resizing = None;
                             }
                         }
                     }
-                    Tool::Platform | Tool::Exit => {
+                    Tool::Platform | Tool::Exit | Tool::Decoration => {
                         if response.drag_started() {
                             let mouse = ui.input(|i| i.pointer.interact_pos()).unwrap_or(available.center());
                             let cam_pt = panel_to_cam(mouse - available.min, available);
@@ -563,8 +1028,12 @@ impl eframe::App for EditorApp {
                                 w = snap_positive(w, self.snap_size);
                                 h = snap_positive(h, self.snap_size);
                             }
-                            let preview = LRect { x: center.x, y: center.y, w: w.max(1.0), h: h.max(1.0) };
-                            let color = if matches!(self.tool, Tool::Platform) { Color32::from_rgb(80,160,255) } else { Color32::from_rgb(255,160,40) };
+                            let preview = LRect::new(center.x, center.y, w.max(1.0), h.max(1.0));
+                            let color = match self.tool {
+                                Tool::Platform => Color32::from_rgb(80,160,255),
+                                Tool::Decoration => Color32::from_rgb(200,200,200),
+                                _ => Color32::from_rgb(255,160,40),
+                            };
                             draw_rect_center(&painter, available, &self.camera, preview, color);
                         }
                         if response.drag_stopped() {
@@ -579,15 +1048,80 @@ impl eframe::App for EditorApp {
                                     w = snap_positive(w, self.snap_size);
                                     h = snap_positive(h, self.snap_size);
                                 }
-                                let rect = LRect { x: center.x, y: center.y, w: w.max(1.0), h: h.max(1.0) };
-                                match self.tool {
-                                    Tool::Platform => level.platforms.push(rect),
-                                    Tool::Exit => level.exits.push(level_format::Exit { x: rect.x, y: rect.y, w: rect.w, h: rect.h, next: String::from("level2") }),
-                                    _ => {}
+                                let rect = LRect::new(center.x, center.y, w.max(1.0), h.max(1.0));
+                                let item = match (self.tool, &self.layers.active) {
+                                    (Tool::Platform, _) => Some(Item::Platform(rect)),
+                                    (Tool::Decoration, Some(layer)) => Some(Item::Decoration(Decoration {
+                                        layer: layer.clone(),
+                                        x: rect.x, y: rect.y, w: rect.w, h: rect.h,
+                                        color: level_format::default_decoration_color(),
+                                        sprite: None,
+                                    })),
+                                    (Tool::Decoration, None) => {
+                                        self.status = "Add a layer to draw decorations on".into();
+                                        None
+                                    }
+                                    _ => Some(Item::Exit(level_format::Exit { x: rect.x, y: rect.y, w: rect.w, h: rect.h, next: String::from("level2") })),
+                                };
+                                if let Some(item) = item {
+                                    let idx = Item::count(level, item.kind());
+                                    self.history.push(level, Command::Insert { idx, item }, None);
                                 }
                             }
                         }
                     }
+                    Tool::Slope => {
+                        let mouse = ui.input(|i| i.pointer.hover_pos()).unwrap_or(available.center());
+                        let cam_pt = panel_to_cam(mouse - available.min, available);
+                        let mut world = self.camera.screen_to_world(cam_pt);
+                        if snap_now { world = snap_vec2(world, self.snap_size); }
+                        if response.clicked_by(egui::PointerButton::Primary) {
+                            self.slope_points.push(world);
+                        }
+                        let finish = response.double_clicked() || (keys_free && ui.input(|i| i.key_pressed(egui::Key::Enter)));
+                        if keys_free && ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                            self.slope_points.clear();
+                        } else if finish && !self.slope_points.is_empty() {
+                            // Surface must run left to right; drop points sharing an x
+                            let mut pts = std::mem::take(&mut self.slope_points);
+                            pts.sort_by(|a, b| a.x.total_cmp(&b.x));
+                            pts.dedup_by(|a, b| (a.x - b.x).abs() < f32::EPSILON);
+                            if pts.len() >= 2 {
+                                let points = pts.iter().map(|p| Point { x: p.x, y: p.y }).collect();
+                                let item = Item::Slope(Slope { points, depth: level_format::default_slope_depth() });
+                                let idx = level.slopes.len();
+                                self.history.push(level, Command::Insert { idx, item }, None);
+                                self.selection = Selection::one(ItemKind::Slope, idx);
+                            } else {
+                                self.status = "A slope needs at least two points at different x".into();
+                            }
+                        }
+                        // Preview the polyline being placed, including the segment to the cursor
+                        if !self.slope_points.is_empty() {
+                            let stroke = Stroke { width: 2.0, color: Color32::from_rgb(80, 160, 255) };
+                            let to_panel = |w: Vec2| available.min + cam_to_panel(self.camera.world_to_screen(w), available);
+                            let mut line: Vec<egui::Pos2> = self.slope_points.iter().map(|p| to_panel(*p)).collect();
+                            line.push(to_panel(world));
+                            painter.add(egui::Shape::line(line, stroke));
+                        }
+                    }
+                    Tool::Tiles => {
+                        if let Some(layer) = self.tile_tool.active.and_then(|i| level.tile_layers.get(i)) {
+                            let cell_rect = |c, r, w, h| {
+                                let cells = layer.cells_rect(c, r, w, h);
+                                center_rect_to_panel(available, &self.camera, cells.x, cells.y, cells.w, cells.h)
+                            };
+                            let hover = hover_world.and_then(|w| layer.cell_at(w.x, w.y));
+                            tiles::draw_grid(&painter, layer, cell_rect, hover);
+                        }
+                        let painting = response.is_pointer_button_down_on()
+                            && ui.input(|i| i.pointer.primary_down())
+                            && !is_panning(ui, &response);
+                        match hover_world.filter(|_| painting) {
+                            Some(world) => self.tile_tool.stroke(level, &mut self.history, world),
+                            None => self.tile_tool.end_stroke(),
+                        }
+                    }
                     Tool::Start => {
                         // Drag start point
                         if response.dragged() {
@@ -595,8 +1129,10 @@ impl eframe::App for EditorApp {
                             let cam_pt = panel_to_cam(mouse - available.min, available);
                             let mut world = self.camera.screen_to_world(cam_pt);
                             if snap_now { world = snap_vec2(world, self.snap_size); }
-                            level.start.x = world.x;
-                            level.start.y = world.y;
+                            let after = Start { x: world.x, y: world.y };
+                            if after != level.start {
+                                self.history.push(level, Command::SetStart { before: level.start, after }, gesture);
+                            }
                         }
                     }
                 }
@@ -605,12 +1141,17 @@ impl eframe::App for EditorApp {
             // Status in corner
             let snap_temp_disabled = ui.input(|i| i.modifiers.ctrl) && self.snap_enabled;
             let info = format!(
-                "zoom: {:.2}  offset: ({:.1},{:.1})  snap:{}{}  {}",
+                "zoom: {:.2}  offset: ({:.1},{:.1})  snap:{}{}{}{}  {}",
                 self.camera.zoom,
                 self.camera.offset.x,
                 self.camera.offset.y,
                 self.snap_size,
                 if snap_temp_disabled { " (Ctrl held)" } else { "" },
+                if self.history.is_dirty() { "  [unsaved]" } else { "" },
+                match self.reach.overlay.as_ref().filter(|_| self.show_reach) {
+                    Some(r) => format!("  {}", r.summary()),
+                    None => String::new(),
+                },
                 self.status
             );
             painter.text(
@@ -621,42 +1162,83 @@ impl eframe::App for EditorApp {
                 Color32::LIGHT_GRAY,
             );
         });
-        // Side panel for properties of selected Exit
-        egui::TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
-            if let (Some(level), Selection::Item(ItemKind::Exit, idx)) = (self.level.as_mut(), self.selection) {
-                if let Some(exit) = level.exits.get_mut(idx) {
-                    ui.horizontal(|ui| {
-                        ui.label("Exit next:");
-                        ui.text_edit_singleline(&mut exit.next);
-                    });
-                }
-            }
-        });
     }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Tool { Select, Platform, Exit, Start }
+enum Tool { Select, Platform, Exit, Slope, Decoration, Start, Tiles }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum ItemKind { Platform, Exit }
+enum ItemKind { Platform, Exit, Slope, Decoration }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Selection { None, Item(ItemKind, usize) }
 
 fn is_panning(ui: &egui::Ui, _response: &egui::Response) -> bool {
     ui.input(|i| i.modifiers.alt) || ui.input(|i| i.pointer.middle_down()) || ui.input(|i| i.key_down(egui::Key::Space))
 }
 
-fn hit_test_level(level: &Level, _cam: &Camera, _viewport: Rect, world: Vec2) -> Option<(usize, ItemKind)> {
-    // Prioritize exits (top), then platforms
-    for (i, e) in level.exits.iter().enumerate().rev() {
-        if point_in_center_rect(world, e.x, e.y, e.w, e.h) { return Some((i, ItemKind::Exit)); }
-    }
-    for (i, r) in level.platforms.iter().enumerate().rev() {
-        if point_in_center_rect(world, r.x, r.y, r.w, r.h) { return Some((i, ItemKind::Platform)); }
+fn hit_test_level(level: &Level, view: &layers::LayerView, _cam: &Camera, _viewport: Rect, world: Vec2) -> Option<(usize, ItemKind)> {
+    pick_order(level, view).into_iter().find(|(idx, kind)| match kind {
+        ItemKind::Slope => point_in_slope(world, &level.slopes[*idx]),
+        _ => Item::get(level, *kind, *idx).is_some_and(|item| {
+            let b = item.bounds();
+            point_in_center_rect(world, b.x, b.y, b.w, b.h)
+        }),
+    })
+}
+
+// Items that can be picked on the canvas, topmost first: decorations in front
+// of the level, exits, slopes, platforms, then decorations behind the level
+fn pick_order(level: &Level, view: &layers::LayerView) -> Vec<(usize, ItemKind)> {
+    let depth = layers::by_depth(level);
+    let (behind, front): (Vec<usize>, Vec<usize>) =
+        depth.into_iter().partition(|i| layers::decoration_z(level, &level.decorations[*i]) < 0.0);
+    let mut order: Vec<(usize, ItemKind)> = front.into_iter().rev().map(|i| (i, ItemKind::Decoration)).collect();
+    order.extend((0..level.exits.len()).rev().map(|i| (i, ItemKind::Exit)));
+    order.extend((0..level.slopes.len()).rev().map(|i| (i, ItemKind::Slope)));
+    order.extend((0..level.platforms.len()).rev().map(|i| (i, ItemKind::Platform)));
+    order.extend(behind.into_iter().rev().map(|i| (i, ItemKind::Decoration)));
+    order.retain(|(idx, kind)| view.editable(level, *kind, *idx));
+    order
+}
+
+impl EditorApp {
+    // Decorations behind (z < 0) or in front of the level, in the game's stacking
+    // order; a sprite is stretched over the decoration and tinted by its color
+    fn draw_decorations(&mut self, painter: &egui::Painter, rect: Rect, front: bool) {
+        let Some(level) = &self.level else { return; };
+        let (cam, sel) = (&self.camera, &self.selection);
+        for i in layers::by_depth(level) {
+            let d: &Decoration = &level.decorations[i];
+            if (layers::decoration_z(level, d) >= 0.0) != front || !self.layers.visible(level, ItemKind::Decoration, i) {
+                continue;
+            }
+            let [r, g, b, a] = level_format::parse_color(&d.color).unwrap_or([255, 0, 255, 255]);
+            let color = Color32::from_rgba_unmultiplied(r, g, b, a);
+            let rr = center_rect_to_panel(rect, cam, d.x, d.y, d.w, d.h);
+            let selected = sel.contains(ItemKind::Decoration, i);
+            let stroke = if selected { Stroke { width: 2.0, color: Color32::WHITE } } else { Stroke::NONE };
+            match &d.sprite {
+                Some(path) => {
+                    let sprite = Texture { name: String::new(), path: path.clone(), mode: TextureMode::Stretch, border: 0.0, scale: 1.0 };
+                    self.textures.draw(painter, &sprite, rr, cam.zoom, color);
+                    painter.rect_stroke(rr, Rounding::ZERO, stroke);
+                }
+                None => {
+                    painter.rect(rr, Rounding::ZERO, color, stroke);
+                }
+            }
+            if sel.single() == Some((ItemKind::Decoration, i)) {
+                draw_handles(painter, rect, cam, &LRect::new(d.x, d.y, d.w, d.h));
+            }
+        }
     }
-    None
+}
+
+// Panel rectangle covered by a center-based world rectangle
+fn center_rect_to_panel(rect: Rect, cam: &Camera, x: f32, y: f32, w: f32, h: f32) -> Rect {
+    let to_panel = |p: Vec2| rect.min + cam_to_panel(cam.world_to_screen(p), rect);
+    let half = vec2(w * 0.5, h * 0.5);
+    Rect::from_two_pos(to_panel(vec2(x, y) - half), to_panel(vec2(x, y) + half))
 }
 
 fn point_in_center_rect(p: Vec2, x: f32, y: f32, w: f32, h: f32) -> bool {
@@ -730,6 +1312,17 @@ fn draw_cross(painter: &egui::Painter, rect: Rect, cam: &Camera, world: Vec2, si
 }
 
 fn main() -> eframe::Result<()> {
+    // A path that isn't valid Unicode is reported like any other bad argument
+    // rather than panicking in std::env::args
+    let args: Result<Vec<String>, _> = std::env::args_os().skip(1).map(|a| a.into_string()).collect();
+    let args = args.unwrap_or_else(|arg| {
+        eprintln!("error: argument {} isn't valid Unicode", arg.to_string_lossy());
+        eprintln!("{}", tiled::USAGE);
+        std::process::exit(2);
+    });
+    if !args.is_empty() {
+        std::process::exit(tiled::run_cli(&args));
+    }
     let native_options = eframe::NativeOptions::default();
     eframe::run_native(
         "Level Editor",
@@ -0,0 +1,13 @@
+// Finding the game's files at run time: in the current directory, else next
+// to the executable or in a folder above it (the workspace, for a build in
+// target/), the same places the game looks for its config.toml
+
+use std::path::PathBuf;
+
+pub fn find(relative: &str) -> Option<PathBuf> {
+    let local = PathBuf::from(relative);
+    if local.exists() {
+        return Some(local);
+    }
+    std::env::current_exe().ok()?.ancestors().skip(1).map(|dir| dir.join(relative)).find(|p| p.exists())
+}
@@ -0,0 +1,125 @@
+// Play the level being edited inside the editor, with the game's own physics
+// and config (game::physics). The path the player took stays on the canvas
+// after stopping.
+
+use eframe::egui::{self, vec2, Vec2};
+use game::config::{load_config, GameConfig, Movement, PlayerCfg};
+use game::physics::{Sim, SimEvent, TICK_HZ};
+use game::systems::input::InputFrame;
+use level_format::Level;
+
+// Don't try to catch up on more than this after a stall
+const MAX_FRAME_DT: f32 = 0.25;
+
+pub struct PlayTest {
+    sim: Sim,
+    accumulator: f32,
+    // Space pressed and released between two ticks still jumps
+    jump_tapped: bool,
+    pub running: bool,
+    // Player position at every tick so far
+    pub trail: Vec<Vec2>,
+    // Sprite sheet the game draws the player with, and which way it faces
+    pub sheet: PlayerCfg,
+    pub facing_left: bool,
+}
+
+impl PlayTest {
+    // Start at `from`, or at the level's start point
+    pub fn start(level: &Level, from: Option<Vec2>) -> Result<Self, String> {
+        let cfg = game_config()?;
+        let movement = movement_in(&cfg, level);
+        let start = from.unwrap_or(vec2(level.start.x, level.start.y));
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        let sim = Sim::new(level.clone(), movement, seed, game_vec(start));
+        Ok(Self {
+            sim,
+            accumulator: 0.0,
+            jump_tapped: false,
+            running: true,
+            trail: vec![start],
+            sheet: cfg.player,
+            facing_left: false,
+        })
+    }
+
+    pub fn player_pos(&self) -> Vec2 {
+        vec2(self.sim.body.pos.x, self.sim.body.pos.y)
+    }
+
+    pub fn seconds(&self) -> f32 {
+        self.sim.ticks as f32 / TICK_HZ as f32
+    }
+
+    // Run the ticks due this frame with the keyboard as input; returns a status
+    // message once the run has ended
+    pub fn update(&mut self, ctx: &egui::Context) -> Option<String> {
+        if !self.running {
+            return None;
+        }
+        let (frame_dt, left, right, jump, jump_pressed, stop) = ctx.input(|i| {
+            (
+                i.stable_dt.min(MAX_FRAME_DT),
+                i.key_down(egui::Key::A) || i.key_down(egui::Key::ArrowLeft),
+                i.key_down(egui::Key::D) || i.key_down(egui::Key::ArrowRight),
+                i.key_down(egui::Key::Space),
+                i.key_pressed(egui::Key::Space),
+                i.key_pressed(egui::Key::Escape),
+            )
+        });
+        if stop {
+            return Some(self.stop(format!("Stopped after {:.1}s", self.seconds())));
+        }
+        self.jump_tapped |= jump_pressed;
+        self.accumulator += frame_dt;
+        let dt = (1.0 / TICK_HZ) as f32;
+        while self.accumulator >= dt {
+            self.accumulator -= dt;
+            let frame = InputFrame { left, right, jump: jump || self.jump_tapped };
+            self.jump_tapped = false;
+            let event = self.sim.tick(frame, dt);
+            self.trail.push(self.player_pos());
+            // Same threshold as the game's face_player_sprite
+            if self.sim.body.vel.x.abs() > 1.0 {
+                self.facing_left = self.sim.body.vel.x < 0.0;
+            }
+            match event {
+                Some(SimEvent::Fell) => {
+                    return Some(self.stop(format!("Fell out of the level after {:.1}s", self.seconds())));
+                }
+                Some(SimEvent::Exit(next)) => {
+                    return Some(self.stop(format!("Reached the exit to '{}' after {:.1}s", next, self.seconds())));
+                }
+                None => {}
+            }
+        }
+        ctx.request_repaint();
+        None
+    }
+
+    fn stop(&mut self, message: String) -> String {
+        self.running = false;
+        message
+    }
+}
+
+// Movement values the game would play this level with under `cfg`
+pub fn movement_in(cfg: &GameConfig, level: &Level) -> Movement {
+    let profile = level.meta.profile.as_deref().filter(|p| cfg.has_profile(p)).unwrap_or(&cfg.profile);
+    cfg.movement(profile)
+}
+
+// The config.toml the game would use if started from here: in the current
+// directory, else next to the executable or above it, else the defaults
+pub fn game_config() -> Result<GameConfig, String> {
+    match load_config(None, &[]) {
+        Ok((cfg, _)) => Ok(cfg),
+        Err(errors) => Err(errors.iter().map(|e| e.to_string()).collect::<Vec<_>>().join("; ")),
+    }
+}
+
+fn game_vec(v: Vec2) -> game::physics::Vec2 {
+    game::physics::Vec2::new(v.x, v.y)
+}
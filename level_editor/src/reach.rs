@@ -0,0 +1,170 @@
+// Jump reachability overlay: arcs the player can jump from the selected surface
+// (or from the start), and everything that can't be reached from the start.
+
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+
+use eframe::egui::{self, vec2, Color32, Rect, Stroke, Vec2};
+use game::config::{GameConfig, Movement};
+use game::reach::{self, Arc, Reachability, Surface};
+use level_format::{Level, Rect as LRect};
+
+use crate::playtest::{game_config, movement_in};
+use crate::{cam_to_panel, slope_bounds, Camera, ItemKind};
+
+// How long the level has to stay the same before it is analyzed again
+const SETTLE: Duration = Duration::from_millis(250);
+
+// Keeps the overlay up to date with the level. The analysis runs on a worker
+// thread once edits have settled, one at a time; the last finished overlay is
+// shown meanwhile. config.toml is read once, when the overlay is turned on.
+#[derive(Default)]
+pub struct ReachAnalysis {
+    config: Option<Result<GameConfig, String>>,
+    // Latest version of the level and when it was first seen
+    seen: Option<(Level, Instant)>,
+    // Level being analyzed, and where its overlay arrives
+    running: Option<(Level, Receiver<ReachOverlay>)>,
+    pub overlay: Option<ReachOverlay>,
+}
+
+impl ReachAnalysis {
+    // `idle` is false while the mouse is down; nothing starts in a drag
+    pub fn update(&mut self, ctx: &egui::Context, level: &Level, idle: bool) {
+        if let Some((_, rx)) = &self.running {
+            match rx.try_recv() {
+                Ok(overlay) => {
+                    self.overlay = Some(overlay);
+                    self.running = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => ctx.request_repaint_after(Duration::from_millis(50)),
+                Err(mpsc::TryRecvError::Disconnected) => self.running = None,
+            }
+        }
+        if self.overlay.as_ref().is_some_and(|o| o.is_for(level)) || self.running.as_ref().is_some_and(|(l, _)| l == level) {
+            return;
+        }
+        let since = match &self.seen {
+            Some((l, since)) if l == level => *since,
+            _ => {
+                let now = Instant::now();
+                self.seen = Some((level.clone(), now));
+                now
+            }
+        };
+        // The first analysis needn't wait
+        let settled = self.overlay.is_none() || since.elapsed() >= SETTLE;
+        if !settled || !idle || self.running.is_some() {
+            ctx.request_repaint_after(SETTLE.saturating_sub(since.elapsed()).max(Duration::from_millis(50)));
+            return;
+        }
+        let movement = match self.config.get_or_insert_with(game_config) {
+            Ok(cfg) => Ok(movement_in(cfg, level)),
+            Err(e) => Err(e.clone()),
+        };
+        let (tx, rx) = mpsc::channel();
+        let copy = level.clone();
+        let repaint = ctx.clone();
+        std::thread::spawn(move || {
+            let _ = tx.send(ReachOverlay::new(copy, movement));
+            repaint.request_repaint();
+        });
+        self.running = Some((level.clone(), rx));
+    }
+}
+
+pub struct ReachOverlay {
+    // Level the results are for
+    level: Level,
+    movement: Result<Movement, String>,
+    result: Result<Reachability, String>,
+    // Jumps from a surface the start can't reach, worked out when it is selected
+    extra: Option<(Surface, Vec<Arc>)>,
+}
+
+impl ReachOverlay {
+    fn new(level: Level, movement: Result<Movement, String>) -> Self {
+        let result = movement.as_ref().map(|mv| reach::analyze(&level, mv)).map_err(|e| e.clone());
+        Self { level, movement, result, extra: None }
+    }
+
+    pub fn is_for(&self, level: &Level) -> bool {
+        self.level == *level
+    }
+
+    // One-line summary for the status line
+    pub fn summary(&self) -> String {
+        match &self.result {
+            Err(e) => format!("reach: {e}"),
+            Ok(r) => {
+                let surfaces = r.unreachable_platforms(&self.level).len() + r.unreachable_slopes(&self.level).len();
+                let exits = r.unreachable_exits(&self.level).len();
+                if r.start.is_none() {
+                    "reach: the start is over a pit".to_string()
+                } else if surfaces + exits == 0 {
+                    "reach: everything reachable".to_string()
+                } else {
+                    format!("reach: {} surface(s), {} exit(s) unreachable", surfaces, exits)
+                }
+            }
+        }
+    }
+
+    // Arcs from the selected platform or slope, else from the start
+    fn arcs(&mut self, selected: Option<(ItemKind, usize)>) -> Vec<&Arc> {
+        let Ok(r) = &self.result else { return Vec::new(); };
+        let from = match selected {
+            Some((ItemKind::Platform, i)) => Some(Surface::Platform(i)),
+            Some((ItemKind::Slope, i)) => Some(Surface::Slope(i)),
+            _ => None,
+        };
+        match from {
+            Some(surface) if !r.reachable.contains(&surface) => {
+                if self.extra.as_ref().map(|(s, _)| *s) != Some(surface) {
+                    let arcs = self.movement.as_ref().map(|mv| reach::jumps_from(&self.level, mv, surface)).unwrap_or_default();
+                    self.extra = Some((surface, arcs));
+                }
+                self.extra.as_ref().map(|(_, arcs)| arcs.iter().collect()).unwrap_or_default()
+            }
+            Some(surface) => r.arcs.iter().filter(|a| a.from == Some(surface)).collect(),
+            // The drop from the start point and the jumps from where it lands
+            None => r.arcs.iter().filter(|a| a.from.is_none() || a.from == r.start).collect(),
+        }
+    }
+
+    pub fn draw(&mut self, painter: &egui::Painter, viewport: Rect, cam: &Camera, selected: Option<(ItemKind, usize)>) {
+        let to_panel = |w: game::physics::Vec2| viewport.min + cam_to_panel(cam.world_to_screen(vec2(w.x, w.y)), viewport);
+        for arc in self.arcs(selected) {
+            // Landing somewhere else (or touching an exit) is what matters
+            let useful = arc.to != arc.from || !arc.exits.is_empty();
+            let color = match (arc.to, useful) {
+                (None, _) => Color32::from_rgba_unmultiplied(255, 90, 90, 40),
+                (Some(_), true) => Color32::from_rgba_unmultiplied(90, 220, 255, 140),
+                (Some(_), false) => Color32::from_rgba_unmultiplied(160, 160, 160, 40),
+            };
+            let line: Vec<egui::Pos2> = arc.points.iter().map(|p| to_panel(*p)).collect();
+            painter.add(egui::Shape::line(line, Stroke { width: 1.0, color }));
+        }
+
+        let Ok(r) = &self.result else { return; };
+        let fill = Color32::from_rgba_unmultiplied(255, 40, 40, 60);
+        let flag = |b: &LRect| {
+            let half = vec2(b.w * 0.5, b.h * 0.5);
+            let a = viewport.min + cam_to_panel(cam.world_to_screen(vec2(b.x, b.y) - half), viewport);
+            let c = viewport.min + cam_to_panel(cam.world_to_screen(vec2(b.x, b.y) + half), viewport);
+            let rect = Rect::from_two_pos(a, c);
+            painter.rect(rect, 0.0, fill, Stroke { width: 1.0, color: Color32::from_rgb(255, 60, 60) });
+            painter.text(rect.center_top() - Vec2::new(0.0, 2.0), egui::Align2::CENTER_BOTTOM, "unreachable", egui::FontId::proportional(11.0), Color32::from_rgb(255, 110, 110));
+        };
+        for i in r.unreachable_platforms(&self.level) {
+            flag(&self.level.platforms[i]);
+        }
+        for i in r.unreachable_slopes(&self.level) {
+            flag(&slope_bounds(&self.level.slopes[i]));
+        }
+        for i in r.unreachable_exits(&self.level) {
+            let e = &self.level.exits[i];
+            flag(&LRect::new(e.x, e.y, e.w, e.h));
+        }
+    }
+}
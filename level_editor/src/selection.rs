@@ -0,0 +1,237 @@
+// The set of selected level objects and the operations on all of them at once.
+// Every operation returns a Command (a Batch for several items) for the history.
+
+use eframe::egui::{vec2, Vec2};
+use level_format::{Fragment, Level, Rect as LRect};
+
+use crate::history::{Command, Item};
+use crate::ItemKind;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Selection(Vec<(ItemKind, usize)>);
+
+impl Selection {
+    pub fn one(kind: ItemKind, idx: usize) -> Self {
+        Self(vec![(kind, idx)])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    // The selected item when exactly one is selected
+    pub fn single(&self) -> Option<(ItemKind, usize)> {
+        match self.0.as_slice() {
+            [one] => Some(*one),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, kind: ItemKind, idx: usize) -> bool {
+        self.0.contains(&(kind, idx))
+    }
+
+    pub fn add(&mut self, kind: ItemKind, idx: usize) {
+        if !self.contains(kind, idx) {
+            self.0.push((kind, idx));
+        }
+    }
+
+    pub fn toggle(&mut self, kind: ItemKind, idx: usize) {
+        if self.contains(kind, idx) {
+            self.0.retain(|i| *i != (kind, idx));
+        } else {
+            self.0.push((kind, idx));
+        }
+    }
+
+    // Drop entries whose item no longer exists (after undo, redo, delete)
+    pub fn retain_existing(&mut self, level: &Level) {
+        self.retain(|kind, idx| idx < Item::count(level, kind));
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(ItemKind, usize) -> bool) {
+        self.0.retain(|(kind, idx)| keep(*kind, *idx));
+    }
+
+    fn get(&self, level: &Level) -> Vec<(usize, Item)> {
+        self.0.iter().filter_map(|(kind, idx)| Item::get(level, *kind, *idx).map(|item| (*idx, item))).collect()
+    }
+}
+
+// Bounding box around all selected items
+pub fn bounds(level: &Level, sel: &Selection) -> Option<LRect> {
+    union_bounds(sel.get(level).iter().map(|(_, i)| i))
+}
+
+// Everything whose bounds overlap the box between two corners
+pub fn in_box(level: &Level, a: Vec2, b: Vec2) -> Vec<(ItemKind, usize)> {
+    let (min, max) = (a.min(b), a.max(b));
+    let mut out = Vec::new();
+    for kind in [ItemKind::Platform, ItemKind::Slope, ItemKind::Exit, ItemKind::Decoration] {
+        for idx in 0..Item::count(level, kind) {
+            let Some(item) = Item::get(level, kind, idx) else { continue; };
+            let (lo, hi) = min_max(&item.bounds());
+            if lo.x <= max.x && hi.x >= min.x && lo.y <= max.y && hi.y >= min.y {
+                out.push((kind, idx));
+            }
+        }
+    }
+    out
+}
+
+pub fn move_by(level: &Level, sel: &Selection, delta: Vec2) -> Option<Command> {
+    let cmds = sel
+        .get(level)
+        .into_iter()
+        .map(|(idx, before)| Command::Replace { idx, after: before.clone().offset(delta), before })
+        .collect();
+    batch(cmds)
+}
+
+// Deleted highest index first, so the indices of the rest stay valid
+pub fn delete(level: &Level, sel: &Selection) -> Option<Command> {
+    let mut items = sel.get(level);
+    items.sort_by_key(|(idx, item)| (item.kind() as u8, std::cmp::Reverse(*idx)));
+    batch(items.into_iter().map(|(idx, item)| Command::Delete { idx, item }).collect())
+}
+
+// Copies appended after the existing items, with a selection of the copies
+pub fn duplicate(level: &Level, sel: &Selection, delta: Vec2) -> Option<(Command, Selection)> {
+    let items: Vec<Item> = sel.get(level).into_iter().map(|(_, item)| item.offset(delta)).collect();
+    insert_all(level, items)
+}
+
+pub fn copy(level: &Level, sel: &Selection) -> Option<String> {
+    let mut fragment = Fragment::default();
+    for (_, item) in sel.get(level) {
+        match item {
+            Item::Platform(r) => fragment.platforms.push(r),
+            Item::Exit(e) => fragment.exits.push(e),
+            Item::Slope(s) => fragment.slopes.push(s),
+            Item::Decoration(d) => fragment.decorations.push(d),
+        }
+    }
+    if fragment.is_empty() {
+        return None;
+    }
+    fragment.to_toml_string_pretty().ok()
+}
+
+// Insert a copied fragment with its center at `at`, or where it was copied from
+pub fn paste(level: &Level, text: &str, at: Option<Vec2>) -> Result<(Command, Selection), String> {
+    let fragment = Fragment::from_toml_str(text).map_err(|e| format!("Clipboard is not a level fragment: {}", e.message()))?;
+    let mut items: Vec<Item> = fragment.platforms.into_iter().map(Item::Platform).collect();
+    items.extend(fragment.exits.into_iter().map(Item::Exit));
+    items.extend(fragment.slopes.into_iter().map(Item::Slope));
+    items.extend(fragment.decorations.into_iter().map(Item::Decoration));
+    let delta = match (at, union_bounds(items.iter())) {
+        (Some(at), Some(b)) => at - vec2(b.x, b.y),
+        _ => Vec2::ZERO,
+    };
+    let items = items.into_iter().map(|item| item.offset(delta)).collect();
+    insert_all(level, items).ok_or_else(|| "Clipboard fragment is empty".to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align { Left, CenterX, Right, Top, CenterY, Bottom }
+
+// Line every item up with the selection's bounds
+pub fn align(level: &Level, sel: &Selection, how: Align) -> Option<Command> {
+    let items = sel.get(level);
+    if items.len() < 2 {
+        return None;
+    }
+    let (lo, hi) = min_max(&union_bounds(items.iter().map(|(_, i)| i))?);
+    let cmds = items
+        .into_iter()
+        .map(|(idx, before)| {
+            let (ilo, ihi) = min_max(&before.bounds());
+            let delta = match how {
+                Align::Left => vec2(lo.x - ilo.x, 0.0),
+                Align::Right => vec2(hi.x - ihi.x, 0.0),
+                Align::CenterX => vec2((lo.x + hi.x - ilo.x - ihi.x) * 0.5, 0.0),
+                Align::Bottom => vec2(0.0, lo.y - ilo.y),
+                Align::Top => vec2(0.0, hi.y - ihi.y),
+                Align::CenterY => vec2(0.0, (lo.y + hi.y - ilo.y - ihi.y) * 0.5),
+            };
+            Command::Replace { idx, after: before.clone().offset(delta), before }
+        })
+        .collect();
+    batch(cmds)
+}
+
+// Equal gaps between neighbours along one axis, keeping the outermost two in place
+pub fn distribute(level: &Level, sel: &Selection, horizontal: bool) -> Option<Command> {
+    let mut items = sel.get(level);
+    if items.len() < 3 {
+        return None;
+    }
+    let axis = |v: Vec2| if horizontal { v.x } else { v.y };
+    let size = |r: &LRect| if horizontal { r.w } else { r.h };
+    items.sort_by(|(_, a), (_, b)| axis(min_max(&a.bounds()).0).total_cmp(&axis(min_max(&b.bounds()).0)));
+    let first = min_max(&items[0].1.bounds()).0;
+    let last = items.iter().map(|(_, i)| axis(min_max(&i.bounds()).1)).fold(f32::NEG_INFINITY, f32::max);
+    let total: f32 = items.iter().map(|(_, i)| size(&i.bounds())).sum();
+    let gap = (last - axis(first) - total) / (items.len() - 1) as f32;
+
+    let mut cursor = axis(first);
+    let cmds = items
+        .into_iter()
+        .map(|(idx, before)| {
+            let b = before.bounds();
+            let shift = cursor - axis(min_max(&b).0);
+            cursor += size(&b) + gap;
+            let delta = if horizontal { vec2(shift, 0.0) } else { vec2(0.0, shift) };
+            Command::Replace { idx, after: before.clone().offset(delta), before }
+        })
+        .collect();
+    batch(cmds)
+}
+
+fn insert_all(level: &Level, items: Vec<Item>) -> Option<(Command, Selection)> {
+    let mut sel = Selection::default();
+    let mut cmds = Vec::new();
+    for item in items {
+        let kind = item.kind();
+        // After the level's items of this kind and the ones inserted so far
+        let idx = Item::count(level, kind) + sel.0.iter().filter(|(k, _)| *k == kind).count();
+        sel.add(kind, idx);
+        cmds.push(Command::Insert { idx, item });
+    }
+    batch(cmds).map(|cmd| (cmd, sel))
+}
+
+fn batch(mut cmds: Vec<Command>) -> Option<Command> {
+    match cmds.len() {
+        0 => None,
+        1 => cmds.pop(),
+        _ => Some(Command::Batch(cmds)),
+    }
+}
+
+fn union_bounds<'a>(items: impl Iterator<Item = &'a Item>) -> Option<LRect> {
+    let mut lo = vec2(f32::INFINITY, f32::INFINITY);
+    let mut hi = vec2(f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for item in items {
+        let (a, b) = min_max(&item.bounds());
+        lo = lo.min(a);
+        hi = hi.max(b);
+    }
+    lo.x.is_finite().then(|| {
+        let c = (lo + hi) * 0.5;
+        LRect::new(c.x, c.y, hi.x - lo.x, hi.y - lo.y)
+    })
+}
+
+fn min_max(r: &LRect) -> (Vec2, Vec2) {
+    let half = vec2(r.w * 0.5, r.h * 0.5);
+    (vec2(r.x, r.y) - half, vec2(r.x, r.y) + half)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,100 @@
+use eframe::egui::vec2;
+use level_format::{Exit, Level, Rect as LRect};
+
+use super::{align, copy, delete, distribute, duplicate, paste, Align, Selection};
+use crate::history::Command;
+use crate::ItemKind::{Exit as ExitKind, Platform};
+
+// Platforms from x = -10..10, 80..120 and 290..310, and one exit
+fn level() -> Level {
+    Level {
+        platforms: vec![LRect::new(0.0, 0.0, 20.0, 10.0), LRect::new(100.0, 50.0, 40.0, 10.0), LRect::new(300.0, 0.0, 20.0, 20.0)],
+        exits: vec![Exit { x: 500.0, y: 40.0, w: 50.0, h: 80.0, next: "level2".into() }],
+        ..Default::default()
+    }
+}
+
+fn select(items: &[(crate::ItemKind, usize)]) -> Selection {
+    let mut sel = Selection::default();
+    for (kind, idx) in items {
+        sel.add(*kind, *idx);
+    }
+    sel
+}
+
+fn applied(level: &Level, cmd: &Command) -> Level {
+    let mut out = level.clone();
+    cmd.apply(&mut out);
+    out
+}
+
+#[test]
+fn align_lines_items_up_with_the_selection_bounds() {
+    let level = level();
+    let sel = select(&[(Platform, 0), (Platform, 1)]);
+    let left = applied(&level, &align(&level, &sel, Align::Left).unwrap());
+    assert_eq!((left.platforms[0].x, left.platforms[1].x), (0.0, 10.0));
+    let top = applied(&level, &align(&level, &sel, Align::Top).unwrap());
+    assert_eq!((top.platforms[0].y, top.platforms[1].y), (50.0, 50.0));
+    let center = applied(&level, &align(&level, &sel, Align::CenterX).unwrap());
+    assert_eq!((center.platforms[0].x, center.platforms[1].x), (55.0, 55.0));
+    // Untouched: the third platform
+    assert_eq!(left.platforms[2], level.platforms[2]);
+    assert!(align(&level, &select(&[(Platform, 0)]), Align::Left).is_none());
+}
+
+#[test]
+fn distribute_keeps_the_outer_items_and_evens_the_gaps() {
+    let level = level();
+    // Selection order doesn't matter, position does
+    let sel = select(&[(Platform, 2), (Platform, 0), (Platform, 1)]);
+    let spread = applied(&level, &distribute(&level, &sel, true).unwrap());
+    // 320 wide with 80 of platforms: two gaps of 120
+    assert_eq!(spread.platforms.iter().map(|p| p.x).collect::<Vec<_>>(), vec![0.0, 150.0, 300.0]);
+    assert!(distribute(&level, &select(&[(Platform, 0), (Platform, 1)]), true).is_none());
+}
+
+#[test]
+fn delete_removes_every_selected_item_and_undoes_exactly() {
+    let level = level();
+    let sel = select(&[(Platform, 0), (ExitKind, 0), (Platform, 2)]);
+    let cmd = delete(&level, &sel).unwrap();
+    let after = applied(&level, &cmd);
+    assert_eq!(after.platforms, vec![level.platforms[1].clone()]);
+    assert!(after.exits.is_empty());
+    let mut undone = after;
+    cmd.revert(&mut undone);
+    assert_eq!(undone, level);
+}
+
+#[test]
+fn copied_items_paste_back_the_same_and_selected() {
+    let level = level();
+    let text = copy(&level, &select(&[(Platform, 1), (ExitKind, 0)])).unwrap();
+
+    let (cmd, sel) = paste(&level, &text, None).unwrap();
+    let after = applied(&level, &cmd);
+    assert_eq!(sel, select(&[(Platform, 3), (ExitKind, 1)]));
+    assert_eq!(after.platforms[3], level.platforms[1]);
+    assert_eq!(after.exits[1], level.exits[0]);
+
+    // Centered on the point it is pasted at: the copy spans x 80..525, y 0..80
+    let (cmd, _) = paste(&level, &text, Some(vec2(0.0, 0.0))).unwrap();
+    let after = applied(&level, &cmd);
+    assert_eq!((after.platforms[3].x, after.platforms[3].y), (100.0 - 302.5, 50.0 - 40.0));
+
+    assert!(paste(&level, "not = [a fragment", None).is_err());
+    assert!(copy(&level, &Selection::default()).is_none());
+}
+
+#[test]
+fn duplicates_go_after_the_items_of_their_own_kind() {
+    let level = level();
+    let sel = select(&[(Platform, 0), (ExitKind, 0), (Platform, 1)]);
+    let (cmd, copies) = duplicate(&level, &sel, vec2(0.0, -100.0)).unwrap();
+    assert_eq!(copies, select(&[(Platform, 3), (ExitKind, 1), (Platform, 4)]));
+    let after = applied(&level, &cmd);
+    assert_eq!((after.platforms.len(), after.exits.len()), (5, 2));
+    assert_eq!(after.platforms[4].y, -50.0);
+    assert_eq!(after.exits[1].y, -60.0);
+}
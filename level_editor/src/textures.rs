@@ -0,0 +1,200 @@
+// Texture previews on the canvas, read from the game's assets folder and drawn
+// the way the game covers a sprite with them. Images that can't be read show
+// the same magenta checkerboard the game uses.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use eframe::egui::{self, pos2, vec2, Color32, Mesh, Rect, TextureHandle, TextureOptions, Vec2};
+use level_format::{Texture, TextureMode, TileLayer, Tileset};
+
+use crate::paths;
+
+// More quads than this for one texture and it is stretched instead
+const MAX_TILES: f32 = 4000.0;
+
+pub const ASSETS_NOT_FOUND: &str = "The game's assets folder wasn't found next to the editor; images can't be previewed or checked";
+
+// The game's assets folder: game/assets in the workspace, or assets/ next to a
+// shipped game. Looked up once, None when the editor runs without either.
+pub fn assets_dir() -> Option<&'static PathBuf> {
+    static DIR: OnceLock<Option<PathBuf>> = OnceLock::new();
+    DIR.get_or_init(|| paths::find("game/assets").or_else(|| paths::find("assets"))).as_ref()
+}
+
+#[derive(Default)]
+pub struct TextureCache {
+    // None for images that couldn't be read
+    images: HashMap<String, Option<TextureHandle>>,
+    placeholder: Option<TextureHandle>,
+}
+
+impl TextureCache {
+    // The image at `path` under the assets folder, read on first use
+    pub fn image(&mut self, ctx: &egui::Context, path: &str) -> Option<TextureHandle> {
+        self.images
+            .entry(path.to_string())
+            .or_insert_with(|| {
+                let rgba = image::open(assets_dir()?.join(path)).ok()?.to_rgba8();
+                let size = [rgba.width() as usize, rgba.height() as usize];
+                let pixels = egui::ColorImage::from_rgba_unmultiplied(size, rgba.as_raw());
+                Some(ctx.load_texture(path, pixels, TextureOptions::NEAREST))
+            })
+            .clone()
+    }
+
+    fn placeholder(&mut self, ctx: &egui::Context) -> TextureHandle {
+        self.placeholder
+            .get_or_insert_with(|| {
+                let mut pixels = egui::ColorImage::new([8, 8], Color32::BLACK);
+                for (i, px) in pixels.pixels.iter_mut().enumerate() {
+                    let (x, y) = (i % 8, i / 8);
+                    if (x / 4 + y / 4) % 2 == 0 {
+                        *px = Color32::from_rgb(255, 0, 255);
+                    }
+                }
+                ctx.load_texture("missing texture", pixels, TextureOptions::NEAREST)
+            })
+            .clone()
+    }
+
+    // Forget every image so changed files are read again
+    pub fn reload(&mut self) {
+        self.images.clear();
+    }
+
+    // Cover `dest` (panel pixels, `zoom` of them per world unit) with `texture`
+    pub fn draw(&mut self, painter: &egui::Painter, texture: &Texture, dest: Rect, zoom: f32, tint: Color32) {
+        self.draw_region(painter, texture, Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)), dest, zoom, tint);
+    }
+
+    // One cell of a sprite sheet with `columns` x `rows` equal frames, stretched over `dest`
+    pub fn draw_frame(&mut self, painter: &egui::Painter, path: &str, grid: [u32; 2], frame: u32, flip: bool, dest: Rect) {
+        let (cols, rows) = (grid[0].max(1) as f32, grid[1].max(1) as f32);
+        let (col, row) = ((frame % grid[0].max(1)) as f32, (frame / grid[0].max(1)) as f32);
+        let mut uv = Rect::from_min_max(pos2(col / cols, row / rows), pos2((col + 1.0) / cols, (row + 1.0) / rows));
+        if flip {
+            std::mem::swap(&mut uv.min.x, &mut uv.max.x);
+        }
+        let sheet = Texture { name: String::new(), path: path.to_string(), mode: TextureMode::Stretch, border: 0.0, scale: 1.0 };
+        self.draw_region(painter, &sheet, uv, dest, 1.0, Color32::WHITE);
+    }
+
+    // Every painted cell of a tile layer on the panel rectangle `cell_rect`
+    // gives it, as one mesh; cells off the panel are skipped
+    pub fn draw_tiles(&mut self, painter: &egui::Painter, layer: &TileLayer, tileset: Option<&Tileset>, cell_rect: impl Fn(u32, u32) -> Rect) {
+        let ctx = painter.ctx().clone();
+        let image = tileset.and_then(|t| self.image(&ctx, &t.path));
+        let placeholder = self.placeholder(&ctx);
+        let mut mesh = Mesh::with_texture(image.as_ref().map_or(placeholder.id(), |t| t.id()));
+        // Tiles that can't be shown: an unknown tileset or a number past its end
+        let mut missing = Mesh::with_texture(placeholder.id());
+        let whole = Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0));
+        for row in 0..layer.rows {
+            for col in 0..layer.columns {
+                let tile = layer.get(col, row);
+                let dest = cell_rect(col, row);
+                if tile == 0 || !painter.clip_rect().intersects(dest) {
+                    continue;
+                }
+                match (tileset.and_then(|t| t.tile_cell(tile).map(|cell| (t, cell))), &image) {
+                    (Some((t, (c, r))), Some(_)) => {
+                        let (cols, rows) = (t.columns as f32, t.rows as f32);
+                        let uv = Rect::from_min_max(
+                            pos2(c as f32 / cols, r as f32 / rows),
+                            pos2((c + 1) as f32 / cols, (r + 1) as f32 / rows),
+                        );
+                        mesh.add_rect_with_uv(dest, uv, Color32::WHITE);
+                    }
+                    _ => missing.add_rect_with_uv(dest, whole, Color32::WHITE),
+                }
+            }
+        }
+        for m in [mesh, missing] {
+            if !m.is_empty() {
+                painter.add(egui::Shape::mesh(m));
+            }
+        }
+    }
+
+    // The `uv` part of the image (all of it except for sprite sheets)
+    fn draw_region(&mut self, painter: &egui::Painter, texture: &Texture, uv: Rect, dest: Rect, zoom: f32, tint: Color32) {
+        let ctx = painter.ctx().clone();
+        let mesh = match self.image(&ctx, &texture.path) {
+            Some(tex) => {
+                let mut mesh = Mesh::with_texture(tex.id());
+                let size = vec2(tex.size()[0] as f32, tex.size()[1] as f32);
+                // Panel pixels per image pixel
+                let px = texture.scale.max(0.01) * zoom;
+                match texture.mode {
+                    TextureMode::Stretch => mesh.add_rect_with_uv(dest, uv, tint),
+                    TextureMode::Tile => tile(&mut mesh, uv, dest, size * px, tint),
+                    TextureMode::Slice => slice(&mut mesh, size, texture.border, px, dest, zoom, tint),
+                }
+                mesh
+            }
+            None => {
+                // Checkers at 4 world units, like the game's tiled placeholder
+                let tex = self.placeholder(&ctx);
+                let mut mesh = Mesh::with_texture(tex.id());
+                tile(&mut mesh, Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)), dest, Vec2::splat(8.0 * zoom), Color32::WHITE);
+                mesh
+            }
+        };
+        if !mesh.is_empty() {
+            painter.add(egui::Shape::mesh(mesh));
+        }
+    }
+}
+
+// Repeat the `uv` part of the image over `dest` from its top-left corner, in
+// tiles of `tile_size` panel pixels; the last row and column are cut short
+fn tile(mesh: &mut Mesh, uv: Rect, dest: Rect, tile_size: Vec2, tint: Color32) {
+    if tile_size.x <= 0.0 || tile_size.y <= 0.0 || dest.width() <= 0.0 || dest.height() <= 0.0 {
+        return;
+    }
+    let count = (dest.width() / tile_size.x).ceil() * (dest.height() / tile_size.y).ceil();
+    if count > MAX_TILES {
+        mesh.add_rect_with_uv(dest, uv, tint);
+        return;
+    }
+    let mut y = dest.min.y;
+    while y < dest.max.y {
+        let h = tile_size.y.min(dest.max.y - y);
+        let mut x = dest.min.x;
+        while x < dest.max.x {
+            let w = tile_size.x.min(dest.max.x - x);
+            let part = Rect::from_min_max(uv.min, uv.min + vec2(uv.width() * w / tile_size.x, uv.height() * h / tile_size.y));
+            mesh.add_rect_with_uv(Rect::from_min_size(pos2(x, y), vec2(w, h)), part, tint);
+            x += w;
+        }
+        y += h;
+    }
+}
+
+// 9-slice: corners `border` image pixels wide at up to `px` panel pixels per
+// image pixel, shrunk when the rectangle is too small for them; edges and
+// middle tiled in between
+fn slice(mesh: &mut Mesh, size: Vec2, border: f32, px: f32, dest: Rect, zoom: f32, tint: Color32) {
+    let b = border.clamp(0.0, size.x.min(size.y) / 2.0);
+    // Same corner scale as Bevy's TextureSlicer, in world units per image pixel
+    let fit = (dest.size() / zoom / size).min_elem();
+    let corner = b * fit.min(px / zoom) * zoom;
+    let (u, v) = (b / size.x, b / size.y);
+    let xs = [dest.min.x, dest.min.x + corner, dest.max.x - corner, dest.max.x];
+    let ys = [dest.min.y, dest.min.y + corner, dest.max.y - corner, dest.max.y];
+    let us = [0.0, u, 1.0 - u, 1.0];
+    let vs = [0.0, v, 1.0 - v, 1.0];
+    for row in 0..3 {
+        for col in 0..3 {
+            let part_dest = Rect::from_min_max(pos2(xs[col], ys[row]), pos2(xs[col + 1], ys[row + 1]));
+            let part_uv = Rect::from_min_max(pos2(us[col], vs[row]), pos2(us[col + 1], vs[row + 1]));
+            if row != 1 && col != 1 {
+                mesh.add_rect_with_uv(part_dest, part_uv, tint);
+            } else {
+                tile(mesh, part_uv, part_dest, part_uv.size() * size * px, tint);
+            }
+        }
+    }
+}
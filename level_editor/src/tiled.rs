@@ -0,0 +1,768 @@
+// Import of maps made with Tiled (mapeditor.org), saved as TMX (XML) or TMJ
+// (JSON). Rectangle objects become platforms, objects named "start" and "exit"
+// the start and exits, and tile layers tile layers with their tilesets. What
+// the level format has no place for is left out and listed in a report.
+//
+// Tiled measures in pixels with y going down from the top of the map; the
+// level gets one world unit per pixel with y up, and the map's bottom edge a
+// little above the height where the player dies.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use game::physics::DEATH_Y;
+use level_format::{Exit, Level, Meta, Rect, Start, TileLayer, Tileset, MAX_GRID_SIDE};
+
+use crate::textures::assets_dir;
+
+// Room between the bottom of the map and the height where the player dies
+const FALL_ROOM: f32 = 100.0;
+// Size of an exit placed with a point object
+const POINT_EXIT_SIZE: (f32, f32) = (50.0, 80.0);
+// High bits of a tile id that flip or rotate the tile
+const FLIP_FLAGS: u32 = 0xF000_0000;
+
+pub struct Import {
+    pub level: Level,
+    // One line per kind of thing that was left out
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ImportError {
+    pub path: PathBuf,
+    pub message: String,
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+// Whether `path` looks like a Tiled map rather than a level
+pub fn is_tiled_map(path: &Path) -> bool {
+    matches!(extension(path).as_str(), "tmx" | "tmj" | "json")
+}
+
+pub fn import(path: &Path) -> Result<Import, ImportError> {
+    let error = |message: String| ImportError { path: path.to_path_buf(), message };
+    let text = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let map = if extension(path) == "tmx" { tmx::map(&text, dir) } else { tmj::map(&text, dir) }.map_err(error)?;
+    if map.orientation != "orthogonal" {
+        return Err(error(format!("only orthogonal maps can be imported, this one is {}", map.orientation)));
+    }
+    let name = path.file_stem().map_or("imported".to_string(), |s| s.to_string_lossy().into_owned());
+    Ok(convert(&map, name))
+}
+
+pub const USAGE: &str = "usage: level_editor [--import <map.tmx|map.tmj> [<level.toml>]]";
+
+// `level_editor --import <map> [<level.toml>]`: writes the level to the file
+// or stdout and the report to stderr; returns the exit code
+pub fn run_cli(args: &[String]) -> i32 {
+    let (map, out) = match args {
+        [flag, map] if flag == "--import" => (map, None),
+        [flag, map, out] if flag == "--import" => (map, Some(out)),
+        _ => {
+            eprintln!("{}", USAGE);
+            return 2;
+        }
+    };
+    let import = match import(Path::new(map)) {
+        Ok(import) => import,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return 1;
+        }
+    };
+    for line in &import.skipped {
+        eprintln!("skipped: {}", line);
+    }
+    let toml = match import.level.to_toml_string_pretty() {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return 1;
+        }
+    };
+    match out {
+        Some(out) => {
+            if let Err(e) = std::fs::write(out, toml) {
+                eprintln!("error: {}: {}", out, e);
+                return 1;
+            }
+            eprintln!("wrote {}", out);
+        }
+        None => print!("{}", toml),
+    }
+    0
+}
+
+fn extension(path: &Path) -> String {
+    path.extension().map_or(String::new(), |e| e.to_string_lossy().to_lowercase())
+}
+
+// The parts of a map the importer looks at, read from either format
+
+struct Map {
+    orientation: String,
+    // Rows of tiles
+    height: u32,
+    // In pixels
+    tile_width: u32,
+    tile_height: u32,
+    properties: HashMap<String, String>,
+    tilesets: Vec<MapTileset>,
+    layers: Vec<MapLayer>,
+}
+
+struct MapTileset {
+    first_gid: u32,
+    name: String,
+    // Path as written, relative to `dir`; None for a collection of images
+    image: Option<String>,
+    dir: PathBuf,
+    columns: u32,
+    tile_count: u32,
+    tile_width: u32,
+    tile_height: u32,
+    margin: u32,
+    spacing: u32,
+}
+
+struct MapLayer {
+    name: String,
+    // In pixels, added to everything in the layer
+    offset: (f32, f32),
+    properties: HashMap<String, String>,
+    // Layer settings that have no counterpart, e.g. "opacity"
+    unsupported: Vec<&'static str>,
+    kind: LayerKind,
+}
+
+enum LayerKind {
+    // Tile ids row by row, or why they couldn't be read
+    Tiles { columns: u32, rows: u32, gids: Result<Vec<u32>, String> },
+    Objects(Vec<Object>),
+    Group(Vec<MapLayer>),
+    Image,
+}
+
+struct Object {
+    name: String,
+    class: String,
+    // Top-left corner (the position itself for points), in pixels
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    rotation: f32,
+    shape: Shape,
+    properties: HashMap<String, String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Shape { Rectangle, Point, Ellipse, Polygon, Polyline, Text, Tile }
+
+impl Shape {
+    fn plural(self) -> &'static str {
+        match self {
+            Shape::Rectangle => "rectangles",
+            Shape::Point => "points",
+            Shape::Ellipse => "ellipses",
+            Shape::Polygon => "polygons",
+            Shape::Polyline => "polylines",
+            Shape::Text => "text objects",
+            Shape::Tile => "tile objects",
+        }
+    }
+}
+
+// Tile layer data: "csv", or "base64" of little-endian u32s, maybe compressed
+fn decode_data(data: &str, encoding: &str, compression: &str) -> Result<Vec<u32>, String> {
+    match encoding {
+        "csv" => data
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<u32>().map_err(|_| format!("bad tile id '{}'", s)))
+            .collect(),
+        "base64" => {
+            let compact: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+            let raw = STANDARD.decode(compact).map_err(|e| format!("bad base64: {}", e))?;
+            let mut bytes = Vec::new();
+            let read = match compression {
+                "" => {
+                    bytes = raw;
+                    Ok(0)
+                }
+                "zlib" => flate2::read::ZlibDecoder::new(raw.as_slice()).read_to_end(&mut bytes),
+                "gzip" => flate2::read::GzDecoder::new(raw.as_slice()).read_to_end(&mut bytes),
+                other => return Err(format!("{} compression isn't supported", other)),
+            };
+            read.map_err(|e| format!("can't decompress: {}", e))?;
+            if bytes.len() % 4 != 0 {
+                return Err(format!("{} bytes of data is not a whole number of tiles", bytes.len()));
+            }
+            Ok(bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+        }
+        other => Err(format!("unknown encoding '{}'", other)),
+    }
+}
+
+mod tmx {
+    use super::*;
+    use roxmltree::{Document, Node};
+
+    pub fn map(text: &str, dir: &Path) -> Result<Map, String> {
+        let doc = Document::parse(text).map_err(|e| format!("bad XML: {}", e))?;
+        let root = doc.root_element();
+        if !root.has_tag_name("map") {
+            return Err(format!("expected a <map>, found <{}>", root.tag_name().name()));
+        }
+        let infinite = attr(root, "infinite", 0u32) != 0;
+        let mut tilesets = Vec::new();
+        for node in root.children().filter(|n| n.has_tag_name("tileset")) {
+            tilesets.push(tileset(node, dir)?);
+        }
+        Ok(Map {
+            orientation: root.attribute("orientation").unwrap_or("orthogonal").to_string(),
+            height: attr(root, "height", 0),
+            tile_width: attr(root, "tilewidth", 0),
+            tile_height: attr(root, "tileheight", 0),
+            properties: properties(root),
+            tilesets,
+            layers: layers(root, infinite),
+        })
+    }
+
+    // An embedded tileset, or the external .tsx/.tsj file it points to
+    fn tileset(node: Node, dir: &Path) -> Result<MapTileset, String> {
+        let first_gid = attr(node, "firstgid", 1);
+        match node.attribute("source") {
+            Some(source) => super::external_tileset(dir, source, first_gid),
+            None => Ok(embedded(node, first_gid, dir)),
+        }
+    }
+
+    pub fn embedded(node: Node, first_gid: u32, dir: &Path) -> MapTileset {
+        let image = node.children().find(|n| n.has_tag_name("image"));
+        MapTileset {
+            first_gid,
+            name: node.attribute("name").unwrap_or_default().to_string(),
+            image: image.and_then(|n| n.attribute("source")).map(str::to_string),
+            dir: dir.to_path_buf(),
+            columns: attr(node, "columns", 0),
+            tile_count: attr(node, "tilecount", 0),
+            tile_width: attr(node, "tilewidth", 0),
+            tile_height: attr(node, "tileheight", 0),
+            margin: attr(node, "margin", 0),
+            spacing: attr(node, "spacing", 0),
+        }
+    }
+
+    fn layers(parent: Node, infinite: bool) -> Vec<MapLayer> {
+        let mut out = Vec::new();
+        for node in parent.children().filter(Node::is_element) {
+            let kind = match node.tag_name().name() {
+                "layer" => {
+                    let data = node.children().find(|n| n.has_tag_name("data"));
+                    let gids = match data {
+                        _ if infinite => Err("infinite maps aren't supported".to_string()),
+                        Some(data) => tile_data(data),
+                        None => Err("no tile data".to_string()),
+                    };
+                    LayerKind::Tiles { columns: attr(node, "width", 0), rows: attr(node, "height", 0), gids }
+                }
+                "objectgroup" => LayerKind::Objects(node.children().filter(|n| n.has_tag_name("object")).map(object).collect()),
+                "group" => LayerKind::Group(layers(node, infinite)),
+                "imagelayer" => LayerKind::Image,
+                _ => continue,
+            };
+            let mut unsupported = Vec::new();
+            if attr(node, "opacity", 1.0f32) < 1.0 {
+                unsupported.push("opacity");
+            }
+            if node.attribute("tintcolor").is_some() {
+                unsupported.push("tint color");
+            }
+            if attr(node, "parallaxx", 1.0f32) != 1.0 || attr(node, "parallaxy", 1.0f32) != 1.0 {
+                unsupported.push("parallax");
+            }
+            out.push(MapLayer {
+                name: node.attribute("name").unwrap_or_default().to_string(),
+                offset: (attr(node, "offsetx", 0.0), attr(node, "offsety", 0.0)),
+                properties: properties(node),
+                unsupported,
+                kind,
+            });
+        }
+        out
+    }
+
+    fn tile_data(data: Node) -> Result<Vec<u32>, String> {
+        match data.attribute("encoding") {
+            // Plain XML: one <tile gid="..."/> per cell
+            None => Ok(data.children().filter(|n| n.has_tag_name("tile")).map(|n| attr(n, "gid", 0)).collect()),
+            Some(encoding) => decode_data(data.text().unwrap_or_default(), encoding, data.attribute("compression").unwrap_or_default()),
+        }
+    }
+
+    fn object(node: Node) -> Object {
+        let child = |name: &str| node.children().any(|n| n.has_tag_name(name));
+        let shape = if child("point") {
+            Shape::Point
+        } else if child("ellipse") {
+            Shape::Ellipse
+        } else if child("polygon") {
+            Shape::Polygon
+        } else if child("polyline") {
+            Shape::Polyline
+        } else if child("text") {
+            Shape::Text
+        } else if node.attribute("gid").is_some() {
+            Shape::Tile
+        } else {
+            Shape::Rectangle
+        };
+        Object {
+            name: node.attribute("name").unwrap_or_default().to_string(),
+            // "class" since Tiled 1.9, "type" before
+            class: node.attribute("class").or(node.attribute("type")).unwrap_or_default().to_string(),
+            x: attr(node, "x", 0.0),
+            y: attr(node, "y", 0.0),
+            width: attr(node, "width", 0.0),
+            height: attr(node, "height", 0.0),
+            rotation: attr(node, "rotation", 0.0),
+            shape,
+            properties: properties(node),
+        }
+    }
+
+    fn properties(node: Node) -> HashMap<String, String> {
+        let list = node.children().filter(|n| n.has_tag_name("properties")).flat_map(|n| n.children());
+        list.filter(|n| n.has_tag_name("property"))
+            .filter_map(|p| {
+                // Multi-line strings are written as the element's text
+                let value = p.attribute("value").or(p.text()).unwrap_or_default();
+                Some((p.attribute("name")?.to_string(), value.to_string()))
+            })
+            .collect()
+    }
+
+    fn attr<T: std::str::FromStr>(node: Node, name: &str, default: T) -> T {
+        node.attribute(name).and_then(|v| v.parse().ok()).unwrap_or(default)
+    }
+}
+
+mod tmj {
+    use super::*;
+    use serde_json::Value;
+
+    pub fn map(text: &str, dir: &Path) -> Result<Map, String> {
+        let root: Value = serde_json::from_str(text).map_err(|e| format!("bad JSON: {}", e))?;
+        if root["type"].as_str().is_some_and(|t| t != "map") {
+            return Err(format!("expected a map, found a {}", root["type"]));
+        }
+        let infinite = root["infinite"].as_bool().unwrap_or(false);
+        let mut tilesets = Vec::new();
+        for ts in list(&root, "tilesets") {
+            let first_gid = uint(ts, "firstgid", 1);
+            tilesets.push(match ts["source"].as_str() {
+                Some(source) => super::external_tileset(dir, source, first_gid)?,
+                None => tileset(ts, first_gid, dir),
+            });
+        }
+        Ok(Map {
+            orientation: string(&root, "orientation").unwrap_or_else(|| "orthogonal".to_string()),
+            height: uint(&root, "height", 0),
+            tile_width: uint(&root, "tilewidth", 0),
+            tile_height: uint(&root, "tileheight", 0),
+            properties: properties(&root),
+            tilesets,
+            layers: layers(&root, infinite),
+        })
+    }
+
+    pub fn external_tileset(text: &str, first_gid: u32, dir: &Path) -> Result<MapTileset, String> {
+        let value: Value = serde_json::from_str(text).map_err(|e| format!("bad JSON: {}", e))?;
+        Ok(tileset(&value, first_gid, dir))
+    }
+
+    fn tileset(ts: &Value, first_gid: u32, dir: &Path) -> MapTileset {
+        MapTileset {
+            first_gid,
+            name: string(ts, "name").unwrap_or_default(),
+            image: string(ts, "image"),
+            dir: dir.to_path_buf(),
+            columns: uint(ts, "columns", 0),
+            tile_count: uint(ts, "tilecount", 0),
+            tile_width: uint(ts, "tilewidth", 0),
+            tile_height: uint(ts, "tileheight", 0),
+            margin: uint(ts, "margin", 0),
+            spacing: uint(ts, "spacing", 0),
+        }
+    }
+
+    fn layers(parent: &Value, infinite: bool) -> Vec<MapLayer> {
+        let mut out = Vec::new();
+        for layer in list(parent, "layers") {
+            let kind = match layer["type"].as_str().unwrap_or_default() {
+                "tilelayer" => {
+                    let gids = match &layer["data"] {
+                        _ if infinite => Err("infinite maps aren't supported".to_string()),
+                        Value::Array(ids) => Ok(ids.iter().map(|id| id.as_u64().unwrap_or(0) as u32).collect()),
+                        Value::String(data) => {
+                            let compression = layer["compression"].as_str().unwrap_or_default();
+                            decode_data(data, layer["encoding"].as_str().unwrap_or("base64"), compression)
+                        }
+                        _ => Err("no tile data".to_string()),
+                    };
+                    LayerKind::Tiles { columns: uint(layer, "width", 0), rows: uint(layer, "height", 0), gids }
+                }
+                "objectgroup" => LayerKind::Objects(list(layer, "objects").map(object).collect()),
+                "group" => LayerKind::Group(layers(layer, infinite)),
+                "imagelayer" => LayerKind::Image,
+                _ => continue,
+            };
+            let mut unsupported = Vec::new();
+            if float(layer, "opacity", 1.0) < 1.0 {
+                unsupported.push("opacity");
+            }
+            if layer.get("tintcolor").is_some() {
+                unsupported.push("tint color");
+            }
+            if float(layer, "parallaxx", 1.0) != 1.0 || float(layer, "parallaxy", 1.0) != 1.0 {
+                unsupported.push("parallax");
+            }
+            out.push(MapLayer {
+                name: string(layer, "name").unwrap_or_default(),
+                offset: (float(layer, "offsetx", 0.0), float(layer, "offsety", 0.0)),
+                properties: properties(layer),
+                unsupported,
+                kind,
+            });
+        }
+        out
+    }
+
+    fn object(obj: &Value) -> Object {
+        let flag = |key: &str| obj[key].as_bool().unwrap_or(false);
+        let shape = if flag("point") {
+            Shape::Point
+        } else if flag("ellipse") {
+            Shape::Ellipse
+        } else if obj.get("polygon").is_some() {
+            Shape::Polygon
+        } else if obj.get("polyline").is_some() {
+            Shape::Polyline
+        } else if obj.get("text").is_some() {
+            Shape::Text
+        } else if obj.get("gid").is_some() {
+            Shape::Tile
+        } else {
+            Shape::Rectangle
+        };
+        Object {
+            name: string(obj, "name").unwrap_or_default(),
+            class: string(obj, "class").or_else(|| string(obj, "type")).unwrap_or_default(),
+            x: float(obj, "x", 0.0),
+            y: float(obj, "y", 0.0),
+            width: float(obj, "width", 0.0),
+            height: float(obj, "height", 0.0),
+            rotation: float(obj, "rotation", 0.0),
+            shape,
+            properties: properties(obj),
+        }
+    }
+
+    fn properties(value: &Value) -> HashMap<String, String> {
+        list(value, "properties")
+            .filter_map(|p| {
+                let value = match &p["value"] {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                Some((string(p, "name")?, value))
+            })
+            .collect()
+    }
+
+    fn list<'a>(value: &'a Value, key: &str) -> impl Iterator<Item = &'a Value> {
+        value[key].as_array().into_iter().flatten()
+    }
+
+    fn string(value: &Value, key: &str) -> Option<String> {
+        value[key].as_str().map(str::to_string)
+    }
+
+    fn uint(value: &Value, key: &str, default: u32) -> u32 {
+        value[key].as_u64().map_or(default, |v| v as u32)
+    }
+
+    fn float(value: &Value, key: &str, default: f32) -> f32 {
+        value[key].as_f64().map_or(default, |v| v as f32)
+    }
+}
+
+// A tileset kept in its own .tsx or .tsj file
+fn external_tileset(dir: &Path, source: &str, first_gid: u32) -> Result<MapTileset, String> {
+    let path = dir.join(source);
+    let text = std::fs::read_to_string(&path).map_err(|e| format!("tileset {}: {}", source, e))?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    if extension(&path) == "tsx" {
+        let doc = roxmltree::Document::parse(&text).map_err(|e| format!("tileset {}: bad XML: {}", source, e))?;
+        Ok(tmx::embedded(doc.root_element(), first_gid, dir))
+    } else {
+        tmj::external_tileset(&text, first_gid, dir).map_err(|e| format!("tileset {}: {}", source, e))
+    }
+}
+
+struct Converter<'a> {
+    map: &'a Map,
+    // World y of the map's top edge
+    top: f32,
+    level: Level,
+    // Level tileset name for each of the map's tilesets that could be used
+    tilesets: Vec<Option<String>>,
+    skipped: Vec<String>,
+    found_start: bool,
+}
+
+fn convert(map: &Map, name: String) -> Import {
+    let mut c = Converter {
+        map,
+        top: DEATH_Y + FALL_ROOM + (map.height * map.tile_height) as f32,
+        level: Level {
+            meta: Meta { name, profile: map.properties.get("profile").cloned() },
+            ..Default::default()
+        },
+        tilesets: Vec::new(),
+        skipped: Vec::new(),
+        found_start: false,
+    };
+    if map.tile_width != map.tile_height {
+        c.skip(format!(
+            "{}x{} tiles: the level format has square cells, so tile layers are {} units per row",
+            map.tile_width, map.tile_height, map.tile_width
+        ));
+    }
+    for ts in &map.tilesets {
+        let name = c.tileset(ts);
+        c.tilesets.push(name);
+    }
+    for layer in &map.layers {
+        c.layer(layer, (0.0, 0.0));
+    }
+    if !c.found_start {
+        c.skip("no object named 'start': the start is at the map's top-left corner".to_string());
+        c.level.start = Start { x: map.tile_width as f32, y: c.top - map.tile_height as f32 };
+    }
+    Import { level: c.level, skipped: summarize(c.skipped) }
+}
+
+// Repeated lines folded into one with a count
+fn summarize(lines: Vec<String>) -> Vec<String> {
+    let mut out: Vec<(String, usize)> = Vec::new();
+    for line in lines {
+        match out.iter_mut().find(|(l, _)| *l == line) {
+            Some((_, n)) => *n += 1,
+            None => out.push((line, 1)),
+        }
+    }
+    out.into_iter().map(|(l, n)| if n == 1 { l } else { format!("{} ({} times)", l, n) }).collect()
+}
+
+impl Converter<'_> {
+    fn skip(&mut self, line: String) {
+        self.skipped.push(line);
+    }
+
+    fn world(&self, x: f32, y: f32) -> (f32, f32) {
+        (x, self.top - y)
+    }
+
+    // Add a tileset to the level; None when its tiles can't be used
+    fn tileset(&mut self, ts: &MapTileset) -> Option<String> {
+        let mut name = if ts.name.is_empty() { format!("tileset{}", ts.first_gid) } else { ts.name.clone() };
+        while self.level.tileset(&name).is_some() {
+            name.push('_');
+        }
+        let Some(image) = &ts.image else {
+            self.skip(format!("tileset '{}': collections of separate images aren't supported; its tiles are left out", name));
+            return None;
+        };
+        if ts.columns == 0 {
+            self.skip(format!("tileset '{}': no column count; its tiles are left out", name));
+            return None;
+        }
+        if ts.margin != 0 || ts.spacing != 0 {
+            self.skip(format!("tileset '{}': margin and spacing are ignored, tiles are cut edge to edge", name));
+        }
+        if (ts.tile_width, ts.tile_height) != (self.map.tile_width, self.map.tile_height) {
+            self.skip(format!(
+                "tileset '{}': {}x{} tiles are drawn at the map's {}x{}",
+                name, ts.tile_width, ts.tile_height, self.map.tile_width, self.map.tile_height
+            ));
+        }
+        let path = match asset_path(&ts.dir.join(image)) {
+            Some(path) => path,
+            None if assets_dir().is_none() => {
+                self.skip(format!("tileset '{}': {} kept as written; the game's assets folder wasn't found to check it", name, image));
+                image.replace('\\', "/")
+            }
+            None => {
+                self.skip(format!("tileset '{}': {} is outside the game's assets folder; copy it there and fix the path", name, image));
+                image.replace('\\', "/")
+            }
+        };
+        self.level.tilesets.push(Tileset { name: name.clone(), path, columns: ts.columns, rows: ts.tile_count.div_ceil(ts.columns) });
+        Some(name)
+    }
+
+    fn layer(&mut self, layer: &MapLayer, parent_offset: (f32, f32)) {
+        let offset = (parent_offset.0 + layer.offset.0, parent_offset.1 + layer.offset.1);
+        for what in &layer.unsupported {
+            self.skip(format!("layer '{}': {} is ignored", layer.name, what));
+        }
+        match &layer.kind {
+            LayerKind::Tiles { columns, rows, gids } => match gids {
+                Ok(gids) => self.tile_layer(layer, *columns, *rows, gids, offset),
+                Err(e) => self.skip(format!("tile layer '{}': {}", layer.name, e)),
+            },
+            LayerKind::Objects(objects) => {
+                for object in objects {
+                    self.object(&layer.name, object, offset);
+                }
+            }
+            LayerKind::Group(children) => {
+                for child in children {
+                    self.layer(child, offset);
+                }
+            }
+            LayerKind::Image => self.skip(format!("image layer '{}': image layers aren't supported, use decorations", layer.name)),
+        }
+    }
+
+    // One tile layer per tileset the map layer uses, since a level's tile layer
+    // draws from a single tileset
+    fn tile_layer(&mut self, layer: &MapLayer, columns: u32, rows: u32, gids: &[u32], offset: (f32, f32)) {
+        if columns > MAX_GRID_SIDE || rows > MAX_GRID_SIDE {
+            self.skip(format!("tile layer '{}': {}x{} is larger than the {}x{} grids levels allow", layer.name, columns, rows, MAX_GRID_SIDE, MAX_GRID_SIDE));
+            return;
+        }
+        if gids.len() != columns as usize * rows as usize {
+            self.skip(format!("tile layer '{}': {} tiles for a {}x{} grid", layer.name, gids.len(), columns, rows));
+            return;
+        }
+        let solid = layer.properties.get("solid").is_none_or(|v| v != "false");
+        let (x, y) = self.world(offset.0, offset.1);
+        let mut split: Vec<(usize, TileLayer)> = Vec::new();
+        let (mut flipped, mut dropped) = (false, false);
+        for (i, gid) in gids.iter().enumerate() {
+            flipped |= gid & FLIP_FLAGS != 0;
+            let gid = gid & !FLIP_FLAGS;
+            if gid == 0 {
+                continue;
+            }
+            let Some(ts) = self.map.tilesets.iter().rposition(|t| t.first_gid <= gid) else {
+                dropped = true;
+                continue;
+            };
+            let Some(tileset) = &self.tilesets[ts] else {
+                dropped = true;
+                continue;
+            };
+            let at = match split.iter().position(|(t, _)| *t == ts) {
+                Some(at) => at,
+                None => {
+                    let mut new = TileLayer::new(&layer.name, tileset, x, y, columns, rows, self.map.tile_width as f32);
+                    new.solid = solid;
+                    split.push((ts, new));
+                    split.len() - 1
+                }
+            };
+            split[at].1.tiles[i] = gid - self.map.tilesets[ts].first_gid + 1;
+        }
+        if flipped {
+            self.skip(format!("tile layer '{}': flipped and rotated tiles are drawn unflipped", layer.name));
+        }
+        if dropped {
+            self.skip(format!("tile layer '{}': tiles from unusable tilesets are left out", layer.name));
+        }
+        // An empty layer keeps the first usable tileset, ready to paint with
+        let first = self.tilesets.iter().enumerate().find_map(|(i, t)| Some((i, t.clone()?)));
+        if let (true, Some((ts, tileset))) = (split.is_empty(), first) {
+            let mut empty = TileLayer::new(&layer.name, &tileset, x, y, columns, rows, self.map.tile_width as f32);
+            empty.solid = solid;
+            split.push((ts, empty));
+        }
+        split.sort_by_key(|(ts, _)| *ts);
+        let several = split.len() > 1;
+        for (_, mut tiles) in split {
+            if several {
+                tiles.name = format!("{} ({})", tiles.name, tiles.tileset);
+            }
+            self.level.tile_layers.push(tiles);
+        }
+    }
+
+    fn object(&mut self, layer: &str, object: &Object, offset: (f32, f32)) {
+        let role = |s: &str| [&object.name, &object.class].iter().any(|n| n.eq_ignore_ascii_case(s));
+        if !matches!(object.shape, Shape::Rectangle | Shape::Point) {
+            self.skip(format!("layer '{}': {} aren't supported", layer, object.shape.plural()));
+            return;
+        }
+        if object.rotation != 0.0 {
+            self.skip(format!("layer '{}': rotated objects aren't supported", layer));
+            return;
+        }
+        let point = object.shape == Shape::Point;
+        let (left, top) = (object.x + offset.0, object.y + offset.1);
+        let (x, y) = self.world(left + object.width * 0.5, top + object.height * 0.5);
+        if role("start") {
+            if self.found_start {
+                self.skip("more than one start: the first one is used".to_string());
+                return;
+            }
+            self.found_start = true;
+            self.level.start = Start { x, y };
+        } else if role("exit") {
+            let next = match object.properties.get("next") {
+                Some(next) => next.clone(),
+                None => {
+                    self.skip(format!("layer '{}': exits without a 'next' property lead nowhere", layer));
+                    String::new()
+                }
+            };
+            let (w, h) = if point { POINT_EXIT_SIZE } else { (object.width, object.height) };
+            // A point marks the floor in front of the exit
+            let y = if point { y + h * 0.5 } else { y };
+            self.level.exits.push(Exit { x, y, w, h, next });
+        } else if point {
+            self.skip(format!("layer '{}': points other than 'start' and 'exit' aren't supported", layer));
+        } else if object.width <= 0.0 || object.height <= 0.0 {
+            self.skip(format!("layer '{}': empty rectangles are left out", layer));
+        } else {
+            self.level.platforms.push(Rect::new(x, y, object.width, object.height));
+        }
+    }
+}
+
+// `path` relative to the game's assets folder, when it is inside it
+fn asset_path(path: &Path) -> Option<String> {
+    let assets = assets_dir()?.canonicalize().ok()?;
+    let path = path.canonicalize().ok()?;
+    let relative = path.strip_prefix(assets).ok()?;
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+#[cfg(test)]
+mod tests;
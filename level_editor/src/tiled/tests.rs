@@ -0,0 +1,90 @@
+use std::path::{Path, PathBuf};
+
+use level_format::{Exit, Level, Rect, Start};
+
+use super::{import, run_cli};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+// The top of the 3-row, 32 pixel map: DEATH_Y + FALL_ROOM + 96
+const TOP: f32 = -404.0;
+
+#[test]
+fn tmx_objects_and_tiles_become_platforms_start_exits_and_tile_layers() {
+    let import = import(&fixture("basic.tmx")).unwrap();
+    assert!(import.skipped.is_empty(), "{:?}", import.skipped);
+    let level = import.level;
+    assert_eq!(level.meta.name, "basic");
+    assert_eq!(level.meta.profile.as_deref(), Some("floaty"));
+    assert_eq!(level.platforms, vec![Rect::new(64.0, TOP - 40.0, 64.0, 16.0)]);
+    // A point is used as the start as it is
+    assert_eq!(level.start, Start { x: 16.0, y: TOP - 64.0 });
+    assert_eq!(
+        level.exits,
+        vec![
+            // A point exit stands on the point
+            Exit { x: 112.0, y: TOP - 96.0 + 40.0, w: 50.0, h: 80.0, next: "level2".into() },
+            Exit { x: 16.0, y: TOP - 24.0, w: 32.0, h: 48.0, next: "level3".into() },
+        ]
+    );
+
+    assert_eq!(level.tilesets.len(), 1);
+    assert_eq!((level.tilesets[0].path.as_str(), level.tilesets[0].columns, level.tilesets[0].rows), ("tiles/ground.png", 4, 2));
+    assert_eq!(level.tile_layers.len(), 1);
+    let layer = &level.tile_layers[0];
+    assert_eq!((layer.name.as_str(), layer.tileset.as_str()), ("ground", "ground"));
+    assert_eq!((layer.x, layer.y, layer.columns, layer.rows, layer.tile_size), (0.0, TOP, 4, 3, 32.0));
+    assert!(layer.solid);
+    assert_eq!(layer.tiles, vec![0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 2, 1]);
+}
+
+#[test]
+fn tmj_maps_import_like_the_same_tmx_map() {
+    let tmj = import(&fixture("basic.tmj")).unwrap();
+    assert!(tmj.skipped.is_empty(), "{:?}", tmj.skipped);
+    assert_eq!(tmj.level, import(&fixture("basic.tmx")).unwrap().level);
+}
+
+#[test]
+fn what_the_level_format_cant_hold_is_reported() {
+    let import = import(&fixture("unsupported.tmx")).unwrap();
+    assert_eq!(
+        import.skipped,
+        vec![
+            "tile layer 'floor': flipped and rotated tiles are drawn unflipped".to_string(),
+            "image layer 'sky': image layers aren't supported, use decorations".to_string(),
+            "layer 'shapes': ellipses aren't supported (2 times)".to_string(),
+        ]
+    );
+    assert!(import.level.platforms.is_empty());
+    // The flipped tile is still there, unflipped
+    assert_eq!(import.level.tile_layers[0].tiles, vec![0, 0, 1, 2]);
+}
+
+#[test]
+fn infinite_maps_leave_their_tile_layers_out() {
+    let import = import(&fixture("infinite.tmx")).unwrap();
+    assert_eq!(import.skipped, vec!["tile layer 'world': infinite maps aren't supported".to_string()]);
+    assert!(import.level.tile_layers.is_empty());
+}
+
+#[test]
+fn the_command_line_import_returns_exit_codes() {
+    let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let map = fixture("basic.tmx").to_string_lossy().into_owned();
+    assert_eq!(run_cli(&args(&["--import"])), 2);
+    assert_eq!(run_cli(&args(&["--export", &map])), 2);
+    assert_eq!(run_cli(&args(&["--import", "no-such-map.tmx"])), 1);
+
+    let out = std::env::temp_dir().join(format!("tiled-import-{}.toml", std::process::id()));
+    let out_arg = out.to_string_lossy().into_owned();
+    assert_eq!(run_cli(&args(&["--import", &map, &out_arg])), 0);
+    let written = Level::from_toml_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+    std::fs::remove_file(&out).unwrap();
+    assert_eq!(written, import(&fixture("basic.tmx")).unwrap().level);
+
+    let nowhere = out.join("missing-dir/level.toml").to_string_lossy().into_owned();
+    assert_eq!(run_cli(&args(&["--import", &map, &nowhere])), 1);
+}
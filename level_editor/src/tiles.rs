@@ -0,0 +1,341 @@
+// Tile layers: their list and settings in the layers panel, the tilesets they
+// use, a palette to pick the tile to paint with, and the Tiles tool that
+// paints, erases and fills cells on the canvas.
+
+use eframe::egui::{self, vec2, Color32, Rect, Ui, Vec2};
+use level_format::{Level, TileEncoding, TileLayer, Tileset, MAX_GRID_SIDE};
+
+use crate::history::{Command, History, MergeKey};
+use crate::inspector::number;
+use crate::EditorApp;
+
+// Palette buttons, in panel pixels
+const SWATCH: f32 = 28.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Brush {
+    #[default]
+    Paint,
+    Erase,
+    // The connected area of cells like the one clicked
+    Fill,
+}
+
+#[derive(Debug)]
+pub struct TileTool {
+    // Index of the tile layer being painted on
+    pub active: Option<usize>,
+    // Tile painted, numbered from 1 like in the layer data
+    pub tile: u32,
+    pub brush: Brush,
+    // The stroke in progress, one undo step, and the cell it reached last
+    stroke: Option<MergeKey>,
+    last_cell: Option<(u32, u32)>,
+}
+
+impl Default for TileTool {
+    fn default() -> Self {
+        Self { active: None, tile: 1, brush: Brush::Paint, stroke: None, last_cell: None }
+    }
+}
+
+impl TileTool {
+    // Keep the active layer valid after undo, redo or opening another level
+    pub fn retain_existing(&mut self, level: &Level) {
+        if self.active.is_none_or(|i| i >= level.tile_layers.len()) {
+            self.active = (!level.tile_layers.is_empty()).then_some(0);
+        }
+    }
+
+    // Brush the cell under `world` while the mouse button is held; a fast
+    // stroke gets the cells in between too, and a fill happens once per press
+    pub fn stroke(&mut self, level: &mut Level, history: &mut History, world: Vec2) {
+        let first = self.stroke.is_none();
+        let key = *self.stroke.get_or_insert_with(|| history.gesture());
+        let Some(idx) = self.active.filter(|i| *i < level.tile_layers.len()) else { return; };
+        let mut layer = level.tile_layers[idx].clone();
+        let Some(cell) = layer.cell_at(world.x, world.y) else {
+            self.last_cell = None;
+            return;
+        };
+        let changed = match self.brush {
+            Brush::Fill => first && layer.fill(cell.0, cell.1, self.tile),
+            brush => {
+                let tile = if brush == Brush::Erase { 0 } else { self.tile };
+                let from = self.last_cell.unwrap_or(cell);
+                cells_between(from, cell).into_iter().fold(false, |changed, (c, r)| layer.set(c, r, tile) | changed)
+            }
+        };
+        self.last_cell = Some(cell);
+        if changed {
+            let mut after = level.tile_layers.clone();
+            after[idx] = layer;
+            history.push(level, Command::SetTileLayers { before: level.tile_layers.clone(), after }, Some(key));
+        }
+    }
+
+    pub fn end_stroke(&mut self) {
+        self.stroke = None;
+        self.last_cell = None;
+    }
+}
+
+// Cells on the straight line from `a` to `b`, both included
+fn cells_between(a: (u32, u32), b: (u32, u32)) -> Vec<(u32, u32)> {
+    let (dx, dy) = (b.0 as i64 - a.0 as i64, b.1 as i64 - a.1 as i64);
+    let steps = dx.abs().max(dy.abs()).max(1);
+    (0..=steps)
+        .map(|i| {
+            let t = i as f32 / steps as f32;
+            ((a.0 as f32 + dx as f32 * t).round() as u32, (a.1 as f32 + dy as f32 * t).round() as u32)
+        })
+        .collect()
+}
+
+impl EditorApp {
+    // The tile layer part of the layers panel
+    pub(crate) fn tile_panel(&mut self, ui: &mut Ui) {
+        let Some(level) = &self.level else { return; };
+        self.tile_tool.retain_existing(level);
+        ui.heading("Tile layers");
+        self.tile_layer_list(ui);
+        ui.separator();
+        self.tile_layer_fields(ui);
+        ui.separator();
+        self.tile_palette(ui);
+        ui.separator();
+        self.tileset_fields(ui);
+    }
+
+    fn tile_layer_list(&mut self, ui: &mut Ui) {
+        let Some(level) = self.level.as_mut() else { return; };
+        for (i, layer) in level.tile_layers.iter().enumerate() {
+            let label = format!("{} ({}x{})", layer.name, layer.columns, layer.rows);
+            if ui.selectable_label(self.tile_tool.active == Some(i), label).clicked() {
+                self.tile_tool.active = Some(i);
+            }
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Add").on_hover_text("An empty 40x20 grid of 16 unit cells at the origin").clicked() {
+                let name = (1..).map(|n| format!("tiles{n}")).find(|n| !level.tile_layers.iter().any(|l| &l.name == n)).unwrap_or_default();
+                let tileset = level.tilesets.first().map(|t| t.name.clone()).unwrap_or_default();
+                let mut after = level.tile_layers.clone();
+                after.push(TileLayer::new(&name, &tileset, 0.0, 0.0, 40, 20, 16.0));
+                self.history.push(level, Command::SetTileLayers { before: level.tile_layers.clone(), after }, None);
+                self.tile_tool.active = Some(level.tile_layers.len() - 1);
+            }
+            let delete = ui.add_enabled(self.tile_tool.active.is_some(), egui::Button::new("Delete"));
+            if let (true, Some(idx)) = (delete.clicked(), self.tile_tool.active) {
+                let mut after = level.tile_layers.clone();
+                after.remove(idx);
+                self.history.push(level, Command::SetTileLayers { before: level.tile_layers.clone(), after }, None);
+                self.tile_tool.active = None;
+            }
+        });
+    }
+
+    fn tile_layer_fields(&mut self, ui: &mut Ui) {
+        let Some(level) = self.level.as_mut() else { return; };
+        let Some(idx) = self.tile_tool.active else {
+            ui.label("No tile layer selected");
+            return;
+        };
+        let before = level.tile_layers[idx].clone();
+        let mut layer = before.clone();
+        let mut started = false;
+        egui::Grid::new("tile_layer_fields").num_columns(2).show(ui, |ui| {
+            ui.label("Name");
+            ui.text_edit_singleline(&mut layer.name);
+            ui.end_row();
+            ui.label("Tileset");
+            egui::ComboBox::from_id_source("tile_layer_tileset").selected_text(layer.tileset.clone()).show_ui(ui, |ui| {
+                for t in &level.tilesets {
+                    ui.selectable_value(&mut layer.tileset, t.name.clone(), &t.name);
+                }
+            });
+            ui.end_row();
+            ui.label("Top left").on_hover_text("World position of the grid's top-left corner");
+            ui.horizontal(|ui| {
+                number(ui, &mut layer.x, &mut started);
+                number(ui, &mut layer.y, &mut started);
+            });
+            ui.end_row();
+            ui.label("Cell size");
+            number(ui, &mut layer.tile_size, &mut started);
+            ui.end_row();
+            ui.label("Grid").on_hover_text("Columns and rows; cells outside a smaller grid are dropped");
+            ui.horizontal(|ui| {
+                let (mut columns, mut rows) = (layer.columns, layer.rows);
+                for value in [&mut columns, &mut rows] {
+                    let r = ui.add(egui::DragValue::new(value).range(1..=MAX_GRID_SIDE));
+                    started |= r.drag_started() || r.gained_focus();
+                }
+                if (columns, rows) != (layer.columns, layer.rows) {
+                    layer.resize(columns, rows);
+                }
+            });
+            ui.end_row();
+            ui.label("Solid");
+            ui.checkbox(&mut layer.solid, "").on_hover_text("Painted cells are ground, merged into rectangles when the level loads");
+            ui.end_row();
+            ui.label("Saved as");
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut layer.encoding, TileEncoding::Rle, "Runs");
+                ui.selectable_value(&mut layer.encoding, TileEncoding::Base64, "Base64");
+            });
+            ui.end_row();
+        });
+        layer.tile_size = layer.tile_size.max(1.0);
+        if started {
+            self.field_gesture = Some(self.history.gesture());
+        }
+        if layer != before {
+            let mut after = level.tile_layers.clone();
+            after[idx] = layer;
+            self.history.push(level, Command::SetTileLayers { before: level.tile_layers.clone(), after }, self.field_gesture);
+        }
+    }
+
+    // Brush and the tiles of the active layer's tileset to paint with
+    fn tile_palette(&mut self, ui: &mut Ui) {
+        let Some(level) = &self.level else { return; };
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.tile_tool.brush, Brush::Paint, "Paint");
+            ui.selectable_value(&mut self.tile_tool.brush, Brush::Erase, "Erase");
+            ui.selectable_value(&mut self.tile_tool.brush, Brush::Fill, "Fill");
+        });
+        let tileset = self.tile_tool.active.and_then(|i| level.tile_layers.get(i)).and_then(|l| level.tileset(&l.tileset));
+        let Some(tileset) = tileset else {
+            ui.label("The active layer has no tileset");
+            return;
+        };
+        let image = self.textures.image(ui.ctx(), &tileset.path);
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing = vec2(2.0, 2.0);
+            for tile in 1..=tileset.tile_count() {
+                let selected = self.tile_tool.tile == tile;
+                let clicked = match (&image, tileset.tile_cell(tile)) {
+                    (Some(tex), Some((c, r))) => {
+                        let (cols, rows) = (tileset.columns as f32, tileset.rows as f32);
+                        let uv = Rect::from_min_max(
+                            egui::pos2(c as f32 / cols, r as f32 / rows),
+                            egui::pos2((c + 1) as f32 / cols, (r + 1) as f32 / rows),
+                        );
+                        let sized = egui::load::SizedTexture::new(tex.id(), Vec2::splat(SWATCH));
+                        ui.add(egui::ImageButton::new(sized).uv(uv).selected(selected)).on_hover_text(format!("Tile {tile}")).clicked()
+                    }
+                    // No image to show: numbered buttons instead
+                    _ => ui.add(egui::Button::new(tile.to_string()).min_size(Vec2::splat(SWATCH)).selected(selected)).clicked(),
+                };
+                if clicked {
+                    self.tile_tool.tile = tile;
+                    if self.tile_tool.brush == Brush::Erase {
+                        self.tile_tool.brush = Brush::Paint;
+                    }
+                }
+            }
+        });
+    }
+
+    // The level's tilesets; a rename applies when the name field loses focus
+    // and carries the layers using the tileset along
+    fn tileset_fields(&mut self, ui: &mut Ui) {
+        let Some(level) = self.level.as_mut() else { return; };
+        let before = level.tilesets.clone();
+        let mut after = before.clone();
+        let mut started = false;
+        let mut rename = None;
+        let mut remove = None;
+        egui::CollapsingHeader::new(format!("Tilesets ({})", before.len())).id_source("tilesets").show(ui, |ui| {
+            for (i, t) in after.iter_mut().enumerate() {
+                egui::Grid::new(("tileset_fields", i)).num_columns(2).show(ui, |ui| {
+                    ui.label("Name");
+                    let id = ui.id().with("name");
+                    let mut name = ui.data_mut(|d| d.get_temp::<String>(id)).unwrap_or_else(|| t.name.clone());
+                    let edit = ui.text_edit_singleline(&mut name);
+                    if edit.lost_focus() && name.trim() != t.name {
+                        rename = Some((t.name.clone(), name.trim().to_string()));
+                    }
+                    if edit.has_focus() {
+                        ui.data_mut(|d| d.insert_temp(id, name));
+                    } else {
+                        ui.data_mut(|d| d.remove::<String>(id));
+                    }
+                    ui.end_row();
+                    ui.label("Image");
+                    started |= ui
+                        .text_edit_singleline(&mut t.path)
+                        .on_hover_text("Path in the game's assets folder")
+                        .gained_focus();
+                    ui.end_row();
+                    ui.label("Tiles").on_hover_text("Columns and rows of equal tiles in the image");
+                    ui.horizontal(|ui| {
+                        for value in [&mut t.columns, &mut t.rows] {
+                            let r = ui.add(egui::DragValue::new(value).range(1..=256));
+                            started |= r.drag_started() || r.gained_focus();
+                        }
+                    });
+                    ui.end_row();
+                });
+                if ui.small_button("Remove").clicked() {
+                    remove = Some(i);
+                }
+                ui.separator();
+            }
+            if ui.button("Add tileset").clicked() {
+                let name = (1..).map(|n| format!("tileset{n}")).find(|n| level.tileset(n).is_none()).unwrap_or_default();
+                after.push(Tileset { name, path: String::new(), columns: 1, rows: 1 });
+            }
+        });
+        if let Some(i) = remove {
+            after.remove(i);
+        }
+
+        if started {
+            self.field_gesture = Some(self.history.gesture());
+        }
+        if let Some((from, to)) = rename {
+            if to.is_empty() || level.tileset(&to).is_some() {
+                self.status = format!("Can't rename tileset '{}' to '{}'", from, to);
+                return;
+            }
+            for t in after.iter_mut().filter(|t| t.name == from) {
+                t.name = to.clone();
+            }
+            let mut layers = level.tile_layers.clone();
+            for l in layers.iter_mut().filter(|l| l.tileset == from) {
+                l.tileset = to.clone();
+            }
+            let cmds = vec![
+                Command::SetTilesets { before, after },
+                Command::SetTileLayers { before: level.tile_layers.clone(), after: layers },
+            ];
+            self.history.push(level, Command::Batch(cmds), None);
+        } else if after != before {
+            // Adding and removing are steps of their own
+            let merge = if after.len() == before.len() { self.field_gesture } else { None };
+            self.history.push(level, Command::SetTilesets { before, after }, merge);
+        }
+    }
+}
+
+// Grid lines of a tile layer and the cell under the mouse, for the Tiles tool
+pub fn draw_grid(painter: &egui::Painter, layer: &TileLayer, cell_rect: impl Fn(u32, u32, u32, u32) -> Rect, hover: Option<(u32, u32)>) {
+    let stroke = egui::Stroke { width: 1.0, color: Color32::from_rgba_unmultiplied(255, 255, 255, 40) };
+    let whole = cell_rect(0, 0, layer.columns, layer.rows);
+    // Lines only while cells are big enough to tell apart
+    if cell_rect(0, 0, 1, 1).width() >= 6.0 {
+        for col in 1..layer.columns {
+            let x = cell_rect(col, 0, 1, 1).min.x;
+            painter.line_segment([egui::pos2(x, whole.min.y), egui::pos2(x, whole.max.y)], stroke);
+        }
+        for row in 1..layer.rows {
+            let y = cell_rect(0, row, 1, 1).min.y;
+            painter.line_segment([egui::pos2(whole.min.x, y), egui::pos2(whole.max.x, y)], stroke);
+        }
+    }
+    painter.rect_stroke(whole, 0.0, egui::Stroke { width: 1.0, color: Color32::from_rgb(120, 200, 255) });
+    if let Some((col, row)) = hover {
+        painter.rect_stroke(cell_rect(col, row, 1, 1), 0.0, egui::Stroke { width: 2.0, color: Color32::WHITE });
+    }
+}
@@ -1,8 +1,14 @@
 use serde::{Deserialize, Serialize};
 
+mod tiles;
+pub use tiles::{TileDataError, TileEncoding, TileLayer, Tileset, MAX_GRID_SIDE};
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct Meta {
     pub name: String,
+    // Movement profile from the game config ("classic", "floaty", "tight", ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
@@ -11,7 +17,7 @@ pub struct Start {
     pub y: f32,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Rect {
     // Center-based coordinates in world space
     pub x: f32,
@@ -19,6 +25,79 @@ pub struct Rect {
     // Size (width, height)
     pub w: f32,
     pub h: f32,
+    // Name of a texture in the level's textures; a plain color without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub texture: Option<String>,
+}
+
+impl Rect {
+    pub fn new(x: f32, y: f32, w: f32, h: f32) -> Self {
+        Self { x, y, w, h, texture: None }
+    }
+}
+
+// How an image covers a rectangle of any size
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TextureMode {
+    // The whole image, scaled to the rectangle
+    #[default]
+    Stretch,
+    // The image repeated across the rectangle
+    Tile,
+    // 9-slice: corners kept at their size, edges and middle repeated
+    Slice,
+}
+
+// An image platforms can refer to by name
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Texture {
+    pub name: String,
+    // Image path relative to the game's assets folder
+    pub path: String,
+    #[serde(default)]
+    pub mode: TextureMode,
+    // Slice: width of the fixed edges, in image pixels
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub border: f32,
+    // Tile and Slice: world units per image pixel
+    #[serde(default = "default_texture_scale")]
+    pub scale: f32,
+}
+
+pub fn default_texture_scale() -> f32 { 1.0 }
+
+fn is_zero(v: &f32) -> bool { *v == 0.0 }
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Slope {
+    // Walkable surface as a polyline in world space, ordered left to right
+    pub points: Vec<Point>,
+    // Thickness of the solid band extruded downward from the surface
+    #[serde(default = "default_slope_depth")]
+    pub depth: f32,
+}
+
+pub fn default_slope_depth() -> f32 { 20.0 }
+
+impl Slope {
+    // Surface height under `x`, or None when `x` is outside the polyline span
+    pub fn surface_y_at(&self, x: f32) -> Option<f32> {
+        self.points.windows(2).find_map(|seg| {
+            let (a, b) = (seg[0], seg[1]);
+            if b.x <= a.x || x < a.x || x > b.x {
+                return None;
+            }
+            let t = (x - a.x) / (b.x - a.x);
+            Some(a.y + (b.y - a.y) * t)
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -30,6 +109,61 @@ pub struct Exit {
     pub next: String,
 }
 
+// A named depth that decorations are drawn at
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Layer {
+    pub name: String,
+    // Draw depth in the game's z units: platforms and exits are at 0, the player
+    // at 1 and the background hills between -20 and -9
+    #[serde(default)]
+    pub z: f32,
+    // How much the layer moves with the camera: 1 like the level itself, less
+    // for far away things, more for things close to the viewer
+    #[serde(default = "default_parallax")]
+    pub parallax: f32,
+}
+
+pub fn default_parallax() -> f32 { 1.0 }
+
+// A rectangle that is only drawn; the player passes through it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Decoration {
+    // Name of the layer it is drawn on
+    pub layer: String,
+    // Center and size, like Rect
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+    // "#rrggbb" or "#rrggbbaa"; tints the sprite when there is one
+    #[serde(default = "default_decoration_color")]
+    pub color: String,
+    // Image path relative to the game's assets folder, stretched over the rectangle
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sprite: Option<String>,
+}
+
+pub fn default_decoration_color() -> String { "#ffffff".to_string() }
+
+// RGBA from "#rrggbb" or "#rrggbbaa"
+pub fn parse_color(s: &str) -> Option<[u8; 4]> {
+    let hex = s.strip_prefix('#')?;
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return None;
+    }
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let alpha = if hex.len() == 8 { byte(6)? } else { 255 };
+    Some([byte(0)?, byte(2)?, byte(4)?, alpha])
+}
+
+pub fn format_color([r, g, b, a]: [u8; 4]) -> String {
+    if a == 255 {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    } else {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct Level {
     pub meta: Meta,
@@ -38,6 +172,18 @@ pub struct Level {
     pub platforms: Vec<Rect>,
     #[serde(default)]
     pub exits: Vec<Exit>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slopes: Vec<Slope>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<Layer>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub decorations: Vec<Decoration>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub textures: Vec<Texture>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tilesets: Vec<Tileset>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tile_layers: Vec<TileLayer>,
 }
 
 impl Level {
@@ -48,4 +194,51 @@ impl Level {
     pub fn to_toml_string_pretty(&self) -> Result<String, toml::ser::Error> {
         toml::to_string_pretty(self)
     }
+
+    pub fn layer(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|l| l.name == name)
+    }
+
+    pub fn texture(&self, name: &str) -> Option<&Texture> {
+        self.textures.iter().find(|t| t.name == name)
+    }
+
+    pub fn tileset(&self, name: &str) -> Option<&Tileset> {
+        self.tilesets.iter().find(|t| t.name == name)
+    }
+
+    // Everything the player collides with as rectangles: the platforms, then
+    // the solid tiles merged
+    pub fn solid_rects(&self) -> Vec<Rect> {
+        let tiles = self.tile_layers.iter().flat_map(|l| l.solid_rects());
+        self.platforms.iter().cloned().chain(tiles).collect()
+    }
+}
+
+// A loose set of level objects without meta or start, e.g. a selection copied
+// from one level and pasted into another through the clipboard
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Fragment {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub platforms: Vec<Rect>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exits: Vec<Exit>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slopes: Vec<Slope>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub decorations: Vec<Decoration>,
+}
+
+impl Fragment {
+    pub fn from_toml_str(s: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str::<Fragment>(s)
+    }
+
+    pub fn to_toml_string_pretty(&self) -> Result<String, toml::ser::Error> {
+        toml::to_string_pretty(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.platforms.is_empty() && self.exits.is_empty() && self.slopes.is_empty() && self.decorations.is_empty()
+    }
 }
@@ -0,0 +1,348 @@
+// Tile layers: a grid of cells painted with tiles cut from one tileset image.
+// Tiles are numbered from 1 in reading order across the image; 0 is an empty
+// cell. In the file the cells are stored run-length encoded or as base64 of
+// little-endian u32s (Tiled's uncompressed layout).
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::Rect;
+
+// Most columns or rows a tile layer can have; larger grids are an error when
+// a level is read, before any cells are decoded
+pub const MAX_GRID_SIDE: u32 = 1000;
+
+// An image cut into equal tiles
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Tileset {
+    pub name: String,
+    // Image path relative to the game's assets folder
+    pub path: String,
+    // Tiles across and down the image
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl Tileset {
+    // Saturates instead of overflowing: no tile number is past u32::MAX anyway
+    pub fn tile_count(&self) -> u32 {
+        self.columns.saturating_mul(self.rows)
+    }
+
+    // Column and row of tile `tile` (1-based) in the image
+    pub fn tile_cell(&self, tile: u32) -> Option<(u32, u32)> {
+        let i = tile.checked_sub(1).filter(|i| *i < self.tile_count())?;
+        Some((i % self.columns, i / self.columns))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TileEncoding {
+    // "count*tile" runs and single tiles, comma separated: "12*0,3*5,7"
+    #[default]
+    Rle,
+    Base64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "TileLayerDef", into = "TileLayerDef")]
+pub struct TileLayer {
+    pub name: String,
+    // Name of a tileset in the level's tilesets
+    pub tileset: String,
+    // World position of the grid's top-left corner; rows go downward
+    pub x: f32,
+    pub y: f32,
+    pub columns: u32,
+    pub rows: u32,
+    // Width and height of a cell in world units
+    pub tile_size: f32,
+    // Whether painted cells are solid ground or only drawn
+    pub solid: bool,
+    // How `tiles` is written to the file
+    pub encoding: TileEncoding,
+    // Row by row from the top, `columns * rows` of them
+    pub tiles: Vec<u32>,
+}
+
+// TileLayer as it is written, with the cells encoded
+#[derive(Serialize, Deserialize, Clone)]
+struct TileLayerDef {
+    name: String,
+    tileset: String,
+    x: f32,
+    y: f32,
+    columns: u32,
+    rows: u32,
+    tile_size: f32,
+    #[serde(default = "default_solid")]
+    solid: bool,
+    #[serde(default)]
+    encoding: TileEncoding,
+    data: String,
+}
+
+fn default_solid() -> bool { true }
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileDataError {
+    // Layer the data belongs to
+    pub layer: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for TileDataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "tile layer '{}': {}", self.layer, self.message)
+    }
+}
+
+impl std::error::Error for TileDataError {}
+
+impl TryFrom<TileLayerDef> for TileLayer {
+    type Error = TileDataError;
+
+    fn try_from(def: TileLayerDef) -> Result<Self, Self::Error> {
+        let error = |message: String| TileDataError { layer: def.name.clone(), message };
+        if def.columns > MAX_GRID_SIDE || def.rows > MAX_GRID_SIDE {
+            return Err(error(format!(
+                "{}x{} grid is larger than the {}x{} allowed",
+                def.columns, def.rows, MAX_GRID_SIDE, MAX_GRID_SIDE
+            )));
+        }
+        let expected = def.columns as usize * def.rows as usize;
+        let tiles = match def.encoding {
+            TileEncoding::Rle => decode_rle(&def.data, expected).map_err(error)?,
+            TileEncoding::Base64 => decode_base64(&def.data).map_err(error)?,
+        };
+        if tiles.len() != expected {
+            return Err(error(format!("{} tiles for a {}x{} grid, expected {}", tiles.len(), def.columns, def.rows, expected)));
+        }
+        Ok(TileLayer {
+            name: def.name,
+            tileset: def.tileset,
+            x: def.x,
+            y: def.y,
+            columns: def.columns,
+            rows: def.rows,
+            tile_size: def.tile_size,
+            solid: def.solid,
+            encoding: def.encoding,
+            tiles,
+        })
+    }
+}
+
+impl From<TileLayer> for TileLayerDef {
+    fn from(layer: TileLayer) -> Self {
+        let data = match layer.encoding {
+            TileEncoding::Rle => encode_rle(&layer.tiles),
+            TileEncoding::Base64 => encode_base64(&layer.tiles),
+        };
+        TileLayerDef {
+            name: layer.name,
+            tileset: layer.tileset,
+            x: layer.x,
+            y: layer.y,
+            columns: layer.columns,
+            rows: layer.rows,
+            tile_size: layer.tile_size,
+            solid: layer.solid,
+            encoding: layer.encoding,
+            data,
+        }
+    }
+}
+
+fn encode_rle(tiles: &[u32]) -> String {
+    let mut runs: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < tiles.len() {
+        let n = tiles[i..].iter().take_while(|t| **t == tiles[i]).count();
+        runs.push(if n == 1 { tiles[i].to_string() } else { format!("{}*{}", n, tiles[i]) });
+        i += n;
+    }
+    runs.join(",")
+}
+
+// Runs that would go past `expected` tiles are an error before anything is
+// allocated for them, so a bad run length can't ask for gigabytes
+fn decode_rle(data: &str, expected: usize) -> Result<Vec<u32>, String> {
+    let mut tiles = Vec::new();
+    for run in data.split(',').map(str::trim).filter(|r| !r.is_empty()) {
+        let (count, tile) = match run.split_once('*') {
+            Some((count, tile)) => (count.trim().parse::<usize>().map_err(|_| format!("bad run length in '{}'", run))?, tile),
+            None => (1, run),
+        };
+        let tile = tile.trim().parse::<u32>().map_err(|_| format!("bad tile number in '{}'", run))?;
+        if count > expected - tiles.len() {
+            return Err(format!("'{}' goes past the {} tiles of the grid", run, expected));
+        }
+        tiles.extend(std::iter::repeat_n(tile, count));
+    }
+    Ok(tiles)
+}
+
+fn encode_base64(tiles: &[u32]) -> String {
+    let bytes: Vec<u8> = tiles.iter().flat_map(|t| t.to_le_bytes()).collect();
+    STANDARD.encode(bytes)
+}
+
+fn decode_base64(data: &str) -> Result<Vec<u32>, String> {
+    let compact: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = STANDARD.decode(compact).map_err(|e| format!("bad base64: {}", e))?;
+    if bytes.len() % 4 != 0 {
+        return Err(format!("{} bytes of data is not a whole number of tiles", bytes.len()));
+    }
+    Ok(bytes.chunks_exact(4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).collect())
+}
+
+impl TileLayer {
+    // An empty grid
+    pub fn new(name: &str, tileset: &str, x: f32, y: f32, columns: u32, rows: u32, tile_size: f32) -> Self {
+        Self {
+            name: name.to_string(),
+            tileset: tileset.to_string(),
+            x,
+            y,
+            columns,
+            rows,
+            tile_size,
+            solid: true,
+            encoding: TileEncoding::Rle,
+            tiles: vec![0; columns as usize * rows as usize],
+        }
+    }
+
+    pub fn get(&self, col: u32, row: u32) -> u32 {
+        if col >= self.columns || row >= self.rows {
+            return 0;
+        }
+        self.tiles.get(self.index(col, row)).copied().unwrap_or(0)
+    }
+
+    // False when the cell is outside the grid or already holds `tile`
+    pub fn set(&mut self, col: u32, row: u32, tile: u32) -> bool {
+        if col >= self.columns || row >= self.rows {
+            return false;
+        }
+        let i = self.index(col, row);
+        match self.tiles.get_mut(i) {
+            Some(t) if *t != tile => {
+                *t = tile;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Position of (col, row) in `tiles`, worked out in usize so large grids
+    // can't overflow
+    fn index(&self, col: u32, row: u32) -> usize {
+        row as usize * self.columns as usize + col as usize
+    }
+
+    // Cell under a world position
+    pub fn cell_at(&self, x: f32, y: f32) -> Option<(u32, u32)> {
+        if self.tile_size <= 0.0 {
+            return None;
+        }
+        let col = ((x - self.x) / self.tile_size).floor();
+        let row = ((self.y - y) / self.tile_size).floor();
+        let inside = col >= 0.0 && row >= 0.0 && col < self.columns as f32 && row < self.rows as f32;
+        inside.then_some((col as u32, row as u32))
+    }
+
+    // Center-based world rectangle covering `w` x `h` cells from (col, row)
+    pub fn cells_rect(&self, col: u32, row: u32, w: u32, h: u32) -> Rect {
+        let s = self.tile_size;
+        Rect::new(
+            self.x + (col as f32 + w as f32 * 0.5) * s,
+            self.y - (row as f32 + h as f32 * 0.5) * s,
+            w as f32 * s,
+            h as f32 * s,
+        )
+    }
+
+    // The whole grid
+    pub fn bounds(&self) -> Rect {
+        self.cells_rect(0, 0, self.columns, self.rows)
+    }
+
+    // Replace the connected area of cells equal to the one at (col, row) with
+    // `tile`; false when nothing changed
+    pub fn fill(&mut self, col: u32, row: u32, tile: u32) -> bool {
+        let target = self.get(col, row);
+        if col >= self.columns || row >= self.rows || target == tile {
+            return false;
+        }
+        let mut stack = vec![(col, row)];
+        while let Some((c, r)) = stack.pop() {
+            if self.get(c, r) != target || !self.set(c, r, tile) {
+                continue;
+            }
+            if c > 0 { stack.push((c - 1, r)); }
+            if r > 0 { stack.push((c, r - 1)); }
+            stack.push((c + 1, r));
+            stack.push((c, r + 1));
+        }
+        true
+    }
+
+    // Change the grid size, keeping the cells that still fit where they were
+    pub fn resize(&mut self, columns: u32, rows: u32) {
+        let mut tiles = vec![0; columns as usize * rows as usize];
+        for row in 0..rows.min(self.rows) {
+            for col in 0..columns.min(self.columns) {
+                tiles[row as usize * columns as usize + col as usize] = self.get(col, row);
+            }
+        }
+        self.columns = columns;
+        self.rows = rows;
+        self.tiles = tiles;
+    }
+
+    // Painted cells merged into few rectangles for collision: runs along each
+    // row, then runs with the same span on consecutive rows stacked together.
+    // Empty when the layer isn't solid.
+    pub fn solid_rects(&self) -> Vec<Rect> {
+        let mut out = Vec::new();
+        if !self.solid {
+            return out;
+        }
+        // (first column, end column) -> first row of a rectangle still growing
+        let mut open: Vec<((u32, u32), u32)> = Vec::new();
+        for row in 0..=self.rows {
+            let mut runs = Vec::new();
+            let mut col = 0;
+            while row < self.rows && col < self.columns {
+                if self.get(col, row) == 0 {
+                    col += 1;
+                    continue;
+                }
+                let start = col;
+                while col < self.columns && self.get(col, row) != 0 {
+                    col += 1;
+                }
+                runs.push((start, col));
+            }
+            let mut next = Vec::new();
+            for (span, top) in open {
+                if runs.contains(&span) {
+                    next.push((span, top));
+                } else {
+                    out.push(self.cells_rect(span.0, top, span.1 - span.0, row - top));
+                }
+            }
+            for span in runs {
+                if !next.iter().any(|(s, _)| *s == span) {
+                    next.push((span, row));
+                }
+            }
+            open = next;
+        }
+        out
+    }
+}